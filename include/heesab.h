@@ -0,0 +1,56 @@
+/*
+ * C bindings for heesab, built with `cargo build --release --features ffi`, which produces
+ * libheesab.so (or .dylib, .dll) in target/release.
+ *
+ * Groups and splits are opaque handles that must be freed with their _free function.
+ * Results are JSON strings that must be freed with heesab_string_free. Functions returning
+ * int return 0 on success and -1 on failure; functions returning a pointer return NULL on
+ * failure. heesab_last_error then says why.
+ */
+#ifndef HEESAB_H
+#define HEESAB_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct HeesabGroup HeesabGroup;
+typedef struct HeesabSplit HeesabSplit;
+
+/* Why the last call on this thread failed, or NULL. Owned by heesab, do not free it. */
+const char *heesab_last_error(void);
+
+HeesabGroup *heesab_group_new(const char *name);
+int heesab_group_add_member(HeesabGroup *group, const char *name);
+/* Names a set of members, such as "kids", for use in selections. */
+int heesab_group_add_alias(HeesabGroup *group, const char *alias, const char *members);
+void heesab_group_free(HeesabGroup *group);
+
+/* Starts an empty bill among the group's current members. Either can be freed first. */
+HeesabSplit *heesab_split_new(const HeesabGroup *group);
+/* participants is a selection such as "all except bob". */
+int heesab_split_add_expense(HeesabSplit *split, const char *name, float amount,
+                             const char *participants);
+int heesab_split_set_tax_and_tip(HeesabSplit *split, float tax, float tip);
+int heesab_split_add_payer(HeesabSplit *split, const char *member, float amount);
+/* {"breakdown": {...}, "settlements": [{"from", "to", "amount"}]} */
+char *heesab_split_result(const HeesabSplit *split);
+void heesab_split_free(HeesabSplit *split);
+
+/*
+ * Splits a whole bill given as JSON in one call, such as
+ * {"users": [{"name": "alice", "income": 6000}, {"name": "bob"}],
+ *  "expenses": [{"name": "pizza", "amount": 20, "participants": ["alice", "bob"]}],
+ *  "tax": 2, "tip": 3, "payers": {"alice": 25}}
+ * Expenses may carry their own "tax", "tip" and "payers", and split other than equally by
+ * a billing "period" ({"start": "YYYY-MM-DD", "end": "YYYY-MM-DD"}) with "presence" per
+ * member, by "weights" per member, or by member incomes with "by_income": true.
+ */
+char *heesab_split_json(const char *input);
+void heesab_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
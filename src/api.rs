@@ -0,0 +1,1019 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::details::{Details, Filter};
+use crate::expense::{CalculatedExpense, PendingExpense};
+use crate::group::Group;
+use crate::ledger::LedgerEntry;
+use crate::period::Period;
+use crate::session::Session;
+use crate::settlement::Settlement;
+use crate::split::{ProcessedSplit, Split};
+use crate::store::Store;
+use crate::taxonomy::Taxonomy;
+use crate::user::User;
+
+/// An HTTP request reduced to what the REST API looks at, so that it can be handled without
+/// a server. `if_match` is the `If-Match` header, which carries the version of the group a
+/// change is based on.
+pub struct Request<'a> {
+    method: &'a str,
+    path: &'a str,
+    if_match: Option<&'a str>,
+    body: &'a str,
+}
+
+/// A JSON response. `version` is the group's version after the request, sent back as an
+/// `ETag` so the next change can be based on it.
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    status: u16,
+    body: String,
+    version: Option<u64>,
+}
+
+impl<'a> Request<'a> {
+    pub fn new(method: &'a str, path: &'a str, if_match: Option<&'a str>, body: &'a str) -> Self {
+        Request {
+            method,
+            path,
+            if_match,
+            body,
+        }
+    }
+}
+
+impl Response {
+    fn new(status: u16, body: Value, version: Option<u64>) -> Self {
+        Response {
+            status,
+            body: body.to_string(),
+            version,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response::new(status, json!({ "error": message }), None)
+    }
+
+    pub fn get_status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn get_body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn get_version(&self) -> Option<u64> {
+        self.version
+    }
+}
+
+#[derive(Serialize)]
+struct GroupView<'a> {
+    name: &'a str,
+    version: u64,
+    members: &'a [User],
+    aliases: &'a BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct ExpenseView<'a> {
+    id: usize,
+    #[serde(flatten)]
+    entry: &'a LedgerEntry,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewGroup {
+    name: String,
+    #[serde(default)]
+    members: Vec<NewMember>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupChange {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewMember {
+    name: String,
+    email: Option<String>,
+    phone: Option<String>,
+}
+
+/// Members cannot be renamed, since the ledger refers to them by name.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MemberChange {
+    email: Option<String>,
+    phone: Option<String>,
+    income: Option<NewIncome>,
+}
+
+/// What a member earns, or their part of a ratio, from `from` on or from the start.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewIncome {
+    amount: f32,
+    from: Option<NaiveDate>,
+}
+
+/// An expense recorded in a group's ledger. `participants` are selections such as `all` or
+/// an alias, and the keys of `payers` are member references such as names or initials.
+/// `time` is RFC 3339 with the offset where the expense happened, and dates the expense
+/// instead of `date`. `category` is one of the group's categories. With a billing `period`,
+/// the amount is split by the days each participant was present, as given in `presence`.
+/// With `by_income`, it is split by the participants' incomes on the day of the expense.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewExpense {
+    name: String,
+    date: Option<NaiveDate>,
+    time: Option<DateTime<FixedOffset>>,
+    category: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    notes: Option<String>,
+    amount: f32,
+    #[serde(default)]
+    tax: f32,
+    #[serde(default)]
+    tip: f32,
+    participants: Vec<String>,
+    payers: BTreeMap<String, f32>,
+    period: Option<NewPeriod>,
+    #[serde(default)]
+    presence: BTreeMap<String, NewPeriod>,
+    #[serde(default)]
+    by_income: bool,
+}
+
+/// Days from `start` to `end`, both included.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewPeriod {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+/// A bill to split without recording it, like the interactive session.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewSplit {
+    expenses: Vec<NewDraft>,
+    #[serde(default)]
+    tax: f32,
+    #[serde(default)]
+    tip: f32,
+    #[serde(default)]
+    payers: BTreeMap<String, f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewDraft {
+    name: String,
+    amount: f32,
+    participants: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewSettlement {
+    from: String,
+    to: String,
+    amount: f32,
+    date: Option<NaiveDate>,
+}
+
+/// Handles one REST API request against `store`. The endpoints are:
+///
+/// - `GET /groups`, `POST /groups`
+/// - `GET`, `PUT` (rename) and `DELETE /groups/<group>`
+/// - `GET`, `POST /groups/<group>/members`, and `GET`, `PUT`, `DELETE` on each member
+/// - `GET`, `POST /groups/<group>/expenses`, and `GET`, `PUT`, `DELETE` on each expense by id
+/// - `POST /groups/<group>/split` to split a bill without recording it
+/// - `GET /groups/<group>/balances` and `GET /groups/<group>/settlements/plan`
+/// - `GET`, `POST /groups/<group>/settlements`
+/// - `GET`, `PUT /groups/<group>/categories`
+///
+/// Listing expenses and balances can be filtered with the query parameters `since` and
+/// `until` (`YYYY-MM-DD`), `category` and `tag`, which can be repeated.
+///
+/// Every change to a group must send the version it was based on in `If-Match`, and is
+/// refused with 412 if the group has changed since.
+pub fn handle(store: &mut Store, request: &Request) -> Response {
+    let path: &str = request.path.split('?').next().unwrap_or_default();
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+
+    let handled: Result<Response, Response> = match (request.method, segments.as_slice()) {
+        ("GET", ["groups"]) => Ok(list_groups(store)),
+        ("POST", ["groups"]) => create_group(store, request.body),
+        ("PUT", ["groups", group]) => rename_group(store, group, request),
+        ("DELETE", ["groups", group]) => delete_group(store, group, request),
+        (_, ["groups", group, rest @ ..]) => match store.find_group_mut(group) {
+            Ok(group) => route_group(group, request, rest),
+            Err(_) => Err(Response::error(404, "no such group")),
+        },
+        _ => Err(Response::error(404, "no such endpoint")),
+    };
+    handled.unwrap_or_else(|response| response)
+}
+
+fn route_group(group: &mut Group, request: &Request, rest: &[&str]) -> Result<Response, Response> {
+    let version: Option<u64> = Some(group.get_version());
+    match (request.method, rest) {
+        ("GET", []) => Ok(Response::new(200, view(group), version)),
+        ("GET", ["members"]) => Ok(Response::new(200, json!(group.get_roster()), version)),
+        ("POST", ["members"]) => change(group, request, |group, body| {
+            let user: User = user(parse(body)?)?;
+            group
+                .add_member(user.clone())
+                .map_err(|_| Response::error(409, "a member with that name already exists"))?;
+            Ok((201, json!(user)))
+        }),
+        ("GET", ["members", member]) => {
+            let user: &User = find_member(group, member, 404)?;
+            Ok(Response::new(200, json!(user), version))
+        }
+        ("PUT", ["members", member]) => change(group, request, |group, body| {
+            let change: MemberChange = parse(body)?;
+            let user: &mut User = group
+                .find_member_mut(member)
+                .map_err(|_| Response::error(404, "no such member"))?;
+            if let Some(email) = change.email {
+                user.set_email(email.trim())
+                    .map_err(|_| Response::error(400, "email must contain @"))?;
+            }
+            if let Some(phone) = change.phone {
+                user.set_phone(phone.trim())
+                    .map_err(|_| Response::error(400, "phone must not be empty"))?;
+            }
+            if let Some(income) = change.income {
+                user.set_income(income.amount, income.from)
+                    .map_err(|_| Response::error(400, "income must be 0 or more"))?;
+            }
+            Ok((200, json!(user)))
+        }),
+        ("DELETE", ["members", member]) => change(group, request, |group, _| {
+            let user: User =
+                group
+                    .remove_member(member)
+                    .map_err(|_| match group.find_member(member) {
+                        Ok(_) => Response::error(
+                            409,
+                            "the member is in a recurring expense or the rent plan",
+                        ),
+                        Err(_) => Response::error(404, "no such member"),
+                    })?;
+            Ok((200, json!(user)))
+        }),
+        ("GET", ["expenses"]) => {
+            let filter: Filter = filter(request.path)?;
+            let expenses: Vec<ExpenseView> = group
+                .get_ledger()
+                .get_entries()
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| filter.matches(entry.get_date(), entry.get_details()))
+                .map(|(index, entry)| ExpenseView {
+                    id: index + 1,
+                    entry,
+                })
+                .collect();
+            Ok(Response::new(200, json!(expenses), version))
+        }
+        ("POST", ["expenses"]) => change(group, request, |group, body| {
+            let entry: LedgerEntry = ledger_entry(group, parse(body)?)?;
+            group.get_ledger_mut().record_entry(entry.clone());
+            let id: usize = group.get_ledger().get_entries().len();
+            Ok((201, json!(ExpenseView { id, entry: &entry })))
+        }),
+        ("GET", ["expenses", id]) => {
+            let index: usize = expense_index(group, id)?;
+            let entry: &LedgerEntry = &group.get_ledger().get_entries()[index];
+            let expense: ExpenseView = ExpenseView {
+                id: index + 1,
+                entry,
+            };
+            Ok(Response::new(200, json!(expense), version))
+        }
+        ("PUT", ["expenses", id]) => change(group, request, |group, body| {
+            let index: usize = expense_index(group, id)?;
+            let entry: LedgerEntry = ledger_entry(group, parse(body)?)?;
+            group
+                .get_ledger_mut()
+                .replace_entry(index, entry.clone())
+                .map_err(|_| Response::error(404, "no such expense"))?;
+            Ok((
+                200,
+                json!(ExpenseView {
+                    id: index + 1,
+                    entry: &entry
+                }),
+            ))
+        }),
+        ("DELETE", ["expenses", id]) => change(group, request, |group, _| {
+            let index: usize = expense_index(group, id)?;
+            let entry: LedgerEntry = group
+                .get_ledger_mut()
+                .remove_entry(index)
+                .map_err(|_| Response::error(404, "no such expense"))?;
+            Ok((
+                200,
+                json!(ExpenseView {
+                    id: index + 1,
+                    entry: &entry
+                }),
+            ))
+        }),
+        ("POST", ["split"]) => {
+            let body: NewSplit = parse(request.body)?;
+            Ok(Response::new(200, split(group, body)?, version))
+        }
+        ("GET", ["balances"]) => Ok(Response::new(
+            200,
+            json!(group.get_ledger().balances_matching(&filter(request.path)?)),
+            version,
+        )),
+        ("GET", ["settlements"]) => Ok(Response::new(
+            200,
+            json!(group.get_ledger().get_settlements()),
+            version,
+        )),
+        ("POST", ["settlements"]) => change(group, request, |group, body| {
+            let body: NewSettlement = parse(body)?;
+            let from: &User = find_member(group, &body.from, 400)?;
+            let to: &User = find_member(group, &body.to, 400)?;
+            let date: NaiveDate = body.date.unwrap_or_else(|| Local::now().date_naive());
+            let settlement: Settlement =
+                Settlement::new(from, to, body.amount, date).map_err(|_| {
+                    Response::error(400, "a settlement is between two members and more than 0")
+                })?;
+            group.get_ledger_mut().record_settlement(settlement.clone());
+            Ok((201, json!(settlement)))
+        }),
+        ("GET", ["settlements", "plan"]) => {
+            let plan: Vec<Settlement> =
+                Settlement::suggest(&group.get_ledger().balances(), Local::now().date_naive());
+            Ok(Response::new(200, json!(plan), version))
+        }
+        ("GET", ["categories"]) => Ok(Response::new(200, json!(group.get_taxonomy()), version)),
+        ("PUT", ["categories"]) => change(group, request, |group, body| {
+            let categories: Vec<String> = parse(body)?;
+            let categories: Vec<&str> = categories.iter().map(|c| c.as_str()).collect();
+            let taxonomy: Taxonomy = Taxonomy::new(&categories).map_err(|_| {
+                Response::error(400, "categories must be unique and have no empty parts")
+            })?;
+            group.set_taxonomy(taxonomy);
+            Ok((200, json!(group.get_taxonomy())))
+        }),
+        _ => Err(Response::error(404, "no such endpoint")),
+    }
+}
+
+fn list_groups(store: &Store) -> Response {
+    let groups: Vec<Value> = store.get_groups().iter().map(view).collect();
+    Response::new(200, json!(groups), None)
+}
+
+fn create_group(store: &mut Store, body: &str) -> Result<Response, Response> {
+    let body: NewGroup = parse(body)?;
+    let mut group: Group =
+        Group::new(body.name.trim()).map_err(|_| Response::error(400, "name must not be empty"))?;
+    for member in body.members {
+        group
+            .add_member(user(member)?)
+            .map_err(|_| Response::error(400, "member names must be unique"))?;
+    }
+    group.bump_version();
+
+    let created: Response = Response::new(201, view(&group), Some(group.get_version()));
+    store
+        .add_group(group)
+        .map_err(|_| Response::error(409, "a group with that name already exists"))?;
+    Ok(created)
+}
+
+fn rename_group(
+    store: &mut Store,
+    reference: &str,
+    request: &Request,
+) -> Result<Response, Response> {
+    let group: &Group = store
+        .find_group(reference)
+        .map_err(|_| Response::error(404, "no such group"))?;
+    check_version(group, request.if_match)?;
+    let body: GroupChange = parse(request.body)?;
+
+    let name: &str = body.name.trim();
+    store
+        .rename_group(reference, name)
+        .map_err(|_| Response::error(409, "group names must not be empty or already taken"))?;
+    let group: &Group = store
+        .find_group(name)
+        .map_err(|_| Response::error(404, "no such group"))?;
+    Ok(Response::new(200, view(group), Some(group.get_version())))
+}
+
+fn delete_group(
+    store: &mut Store,
+    reference: &str,
+    request: &Request,
+) -> Result<Response, Response> {
+    let group: &Group = store
+        .find_group(reference)
+        .map_err(|_| Response::error(404, "no such group"))?;
+    check_version(group, request.if_match)?;
+    let group: Group = store
+        .remove_group(reference)
+        .map_err(|_| Response::error(404, "no such group"))?;
+    Ok(Response::new(200, view(&group), None))
+}
+
+/// Applies a change to a copy of `group` if the request is based on its current version, and
+/// keeps the copy with a bumped version only if the whole change succeeds.
+fn change(
+    group: &mut Group,
+    request: &Request,
+    apply: impl FnOnce(&mut Group, &str) -> Result<(u16, Value), Response>,
+) -> Result<Response, Response> {
+    check_version(group, request.if_match)?;
+    let mut changed: Group = group.clone();
+    let (status, body) = apply(&mut changed, request.body)?;
+    changed.bump_version();
+    *group = changed;
+    Ok(Response::new(status, body, Some(group.get_version())))
+}
+
+fn check_version(group: &Group, if_match: Option<&str>) -> Result<(), Response> {
+    let version: String = group.get_version().to_string();
+    match if_match.map(|tag| tag.trim().trim_start_matches("W/").trim_matches('"')) {
+        None => Err(Response::error(
+            428,
+            "send the group's version in If-Match to change it",
+        )),
+        Some(tag) if tag == version => Ok(()),
+        Some(_) => Err(Response::new(
+            412,
+            json!({ "error": "the group has changed, fetch it again and retry" }),
+            Some(group.get_version()),
+        )),
+    }
+}
+
+fn view(group: &Group) -> Value {
+    json!(GroupView {
+        name: group.get_name(),
+        version: group.get_version(),
+        members: group.get_roster(),
+        aliases: group.get_aliases(),
+    })
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, Response> {
+    serde_json::from_str(body)
+        .map_err(|e| Response::error(400, &format!("invalid request body: {}", e)))
+}
+
+/// Validates like `User::new`, `User::set_email` and `User::set_phone`.
+fn user(member: NewMember) -> Result<User, Response> {
+    let mut user: User = User::new(member.name.trim())
+        .map_err(|_| Response::error(400, "name must not be empty"))?;
+    if let Some(email) = member.email {
+        user.set_email(email.trim())
+            .map_err(|_| Response::error(400, "email must contain @"))?;
+    }
+    if let Some(phone) = member.phone {
+        user.set_phone(phone.trim())
+            .map_err(|_| Response::error(400, "phone must not be empty"))?;
+    }
+    Ok(user)
+}
+
+fn find_member<'a>(group: &'a Group, reference: &str, status: u16) -> Result<&'a User, Response> {
+    group
+        .find_member(reference)
+        .map_err(|_| Response::error(status, &format!("{} did not match a member", reference)))
+}
+
+/// Resolves each reference with `Group::select`, so `all` and aliases work too.
+fn select<'a>(group: &'a Group, references: &[String]) -> Result<Vec<&'a User>, Response> {
+    let mut selected: Vec<&User> = Vec::new();
+    for reference in references {
+        for user in group
+            .select(reference)
+            .map_err(|e| Response::error(400, &e.to_string()))?
+        {
+            if !selected.contains(&user) {
+                selected.push(user);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+fn expense_index(group: &Group, id: &str) -> Result<usize, Response> {
+    match id.parse::<usize>() {
+        Ok(id) if id >= 1 && id <= group.get_ledger().get_entries().len() => Ok(id - 1),
+        _ => Err(Response::error(404, "no such expense")),
+    }
+}
+
+/// Runs the expense through the same validation as the interactive split before recording
+/// it, see `PendingExpense::finalize`.
+fn ledger_entry(group: &Group, body: NewExpense) -> Result<LedgerEntry, Response> {
+    let name: &str = body.name.trim();
+    let mut pending_expense: PendingExpense = PendingExpense::new(name, body.amount)
+        .map_err(|_| Response::error(400, "an expense needs a name and an amount of 0 or more"))?;
+    *pending_expense.get_details_mut() = details(group, &body)?;
+    pending_expense
+        .set_tax(body.tax)
+        .and_then(|_| pending_expense.set_tip(body.tip))
+        .map_err(|_| Response::error(400, "tax and tip must be 0 or more"))?;
+    for user in select(group, &body.participants)? {
+        pending_expense.add_participant(user);
+    }
+    for (reference, amount) in &body.payers {
+        pending_expense
+            .add_payer(find_member(group, reference, 400)?, *amount)
+            .map_err(|_| Response::error(400, "payments must be more than 0"))?;
+    }
+    let period = |period: &NewPeriod| {
+        Period::new(period.start, period.end)
+            .map_err(|_| Response::error(400, "a period cannot end before it starts"))
+    };
+    if let Some(billing_period) = &body.period {
+        pending_expense.set_billing_period(period(billing_period)?);
+    }
+    for (reference, presence) in &body.presence {
+        pending_expense.set_presence(find_member(group, reference, 400)?, period(presence)?);
+    }
+    let date: NaiveDate = body
+        .time
+        .map(|t| t.date_naive())
+        .or(body.date)
+        .unwrap_or_else(|| Local::now().date_naive());
+    if body.by_income {
+        pending_expense.split_by_income(date);
+    }
+
+    let calculated_expense: CalculatedExpense = pending_expense
+        .finalize()
+        .map_err(|_| {
+            Response::error(
+                400,
+                "an expense needs participants, payments that add up to its total, and a \
+                 billing period with someone present if presence is given, and incomes for \
+                 everyone if split by income",
+            )
+        })?
+        .calculate();
+    let split: ProcessedSplit = Split::new(vec![&calculated_expense], 0_f32, 0_f32)
+        .and_then(|split| split.process())
+        .map_err(|_| Response::error(400, "the expense could not be split"))?;
+    LedgerEntry::from_split(name, date, &split)
+        .map_err(|_| Response::error(400, "an expense needs someone who paid for it"))
+}
+
+fn details(group: &Group, body: &NewExpense) -> Result<Details, Response> {
+    let mut details: Details = Details::new();
+    if let Some(time) = body.time {
+        if body.date.is_some_and(|date| date != time.date_naive()) {
+            return Err(Response::error(400, "date and time are on different days"));
+        }
+        details.set_time(time);
+    }
+    if let Some(category) = &body.category {
+        details
+            .set_category(group.get_taxonomy(), category)
+            .map_err(|_| Response::error(400, "no such category"))?;
+    }
+    for tag in &body.tags {
+        details
+            .add_tag(tag)
+            .map_err(|_| Response::error(400, "tags must be single words"))?;
+    }
+    if let Some(notes) = &body.notes {
+        details
+            .set_notes(notes)
+            .map_err(|_| Response::error(400, "notes must be at most 1000 characters"))?;
+    }
+    Ok(details)
+}
+
+/// Reads `since`, `until`, `category` and `tag` from the query string.
+fn filter(path: &str) -> Result<Filter, Response> {
+    let mut filter: Filter = Filter::new();
+    let query: &str = path.split_once('?').map_or("", |(_, query)| query);
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value: String = decode(&value.replace('+', " "));
+        let date = || {
+            NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                .map_err(|_| Response::error(400, "dates must be YYYY-MM-DD"))
+        };
+        match key {
+            "since" => filter.set_since(date()?),
+            "until" => filter.set_until(date()?),
+            "category" => filter.set_category(&value),
+            "tag" => filter
+                .add_tag(&value)
+                .map_err(|_| Response::error(400, "tags must be single words"))?,
+            _ => return Err(Response::error(400, "unknown query parameter")),
+        }
+    }
+    Ok(filter)
+}
+
+fn split(group: &Group, body: NewSplit) -> Result<Value, Response> {
+    let mut session: Session = Session::new(
+        Some(group.get_name()),
+        group.get_roster().to_vec(),
+        group.get_aliases().clone(),
+    )
+    .map_err(|_| Response::error(400, "the group has no members"))?;
+
+    for draft in &body.expenses {
+        session
+            .add_expense(
+                draft.name.trim(),
+                draft.amount,
+                &select(group, &draft.participants)?,
+            )
+            .map_err(|_| {
+                Response::error(
+                    400,
+                    "every expense needs a name, an amount of 0 or more and participants",
+                )
+            })?;
+    }
+
+    let mut payers: Vec<(&User, f32)> = Vec::new();
+    for (reference, amount) in &body.payers {
+        payers.push((find_member(group, reference, 400)?, *amount));
+    }
+    let breakdown = session.split(body.tax, body.tip, &payers).map_err(|_| {
+        Response::error(
+            400,
+            "the split needs expenses, tax and tip of 0 or more, and payments that add up to its total",
+        )
+    })?;
+    Ok(json!(breakdown))
+}
+
+/// Undoes percent-encoding in a path segment, e.g. `Alice%20Smith`.
+fn decode(segment: &str) -> String {
+    let bytes: &[u8] = segment.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut index: usize = 0;
+    while index < bytes.len() {
+        let escaped: Option<u8> = match bytes[index] {
+            b'%' => segment
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> Store {
+        let mut store: Store = Store::new();
+        let created: Response = handle(
+            &mut store,
+            &Request::new(
+                "POST",
+                "/groups",
+                None,
+                r#"{"name": "Roommates", "members": [{"name": "Alice Smith"}, {"name": "Bob"}]}"#,
+            ),
+        );
+        assert_eq!(created.get_status(), 201);
+        store
+    }
+
+    fn body(response: &Response) -> Value {
+        serde_json::from_str(response.get_body()).unwrap()
+    }
+
+    #[test]
+    fn test_create_and_get_group() {
+        let mut store: Store = store();
+        let response: Response = handle(
+            &mut store,
+            &Request::new("GET", "/groups/roommates", None, ""),
+        );
+
+        assert_eq!(response.get_status(), 200);
+        assert_eq!(response.get_version(), Some(1));
+        assert_eq!(body(&response)["members"][0]["name"], "Alice Smith");
+
+        let duplicate: Response = handle(
+            &mut store,
+            &Request::new("POST", "/groups", None, r#"{"name": "roommates"}"#),
+        );
+        assert_eq!(duplicate.get_status(), 409);
+    }
+
+    #[test]
+    fn test_member_validation() {
+        let mut store: Store = store();
+        let response: Response = handle(
+            &mut store,
+            &Request::new(
+                "POST",
+                "/groups/1/members",
+                Some("\"1\""),
+                r#"{"name": "Carol", "email": "not an email"}"#,
+            ),
+        );
+
+        assert_eq!(response.get_status(), 400);
+        assert_eq!(body(&response)["error"], "email must contain @");
+        assert_eq!(store.get_groups()[0].get_roster().len(), 2);
+        assert_eq!(store.get_groups()[0].get_version(), 1);
+    }
+
+    #[test]
+    fn test_change_requires_current_version() {
+        let mut store: Store = store();
+        let carol: &str = r#"{"name": "Carol"}"#;
+
+        let missing: Response = handle(
+            &mut store,
+            &Request::new("POST", "/groups/1/members", None, carol),
+        );
+        assert_eq!(missing.get_status(), 428);
+
+        let added: Response = handle(
+            &mut store,
+            &Request::new("POST", "/groups/1/members", Some("1"), carol),
+        );
+        assert_eq!(added.get_status(), 201);
+        assert_eq!(added.get_version(), Some(2));
+
+        let stale: Response = handle(
+            &mut store,
+            &Request::new("DELETE", "/groups/1/members/Alice%20Smith", Some("1"), ""),
+        );
+        assert_eq!(stale.get_status(), 412);
+        assert_eq!(stale.get_version(), Some(2));
+        assert_eq!(store.get_groups()[0].get_roster().len(), 3);
+    }
+
+    #[test]
+    fn test_expenses_balances_and_plan() {
+        let mut store: Store = store();
+        let recorded: Response = handle(
+            &mut store,
+            &Request::new(
+                "POST",
+                "/groups/1/expenses",
+                Some("1"),
+                r#"{"name": "groceries", "date": "2026-10-10", "amount": 30.00,
+                    "participants": ["as", "bob"], "payers": {"alice smith": 30.00}}"#,
+            ),
+        );
+        assert_eq!(recorded.get_status(), 201);
+        assert_eq!(body(&recorded)["id"], 1);
+
+        let unbalanced: Response = handle(
+            &mut store,
+            &Request::new(
+                "PUT",
+                "/groups/1/expenses/1",
+                Some("2"),
+                r#"{"name": "groceries", "amount": 30.00,
+                    "participants": ["bob"], "payers": {"bob": 20.00}}"#,
+            ),
+        );
+        assert_eq!(unbalanced.get_status(), 400);
+
+        let balances: Response = handle(
+            &mut store,
+            &Request::new("GET", "/groups/1/balances", None, ""),
+        );
+        assert_eq!(body(&balances), json!({"Alice Smith": 15.0, "Bob": -15.0}));
+
+        let plan: Response = handle(
+            &mut store,
+            &Request::new("GET", "/groups/1/settlements/plan", None, ""),
+        );
+        assert_eq!(body(&plan)[0]["from"], "Bob");
+        assert_eq!(body(&plan)[0]["to"], "Alice Smith");
+        assert_eq!(body(&plan)[0]["amount"], 15.0);
+    }
+
+    #[test]
+    fn test_expense_prorated_by_presence() {
+        let mut store: Store = store();
+        let post = |store: &mut Store, version: &str, body: &str| {
+            handle(
+                store,
+                &Request::new("POST", "/groups/1/expenses", Some(version), body),
+            )
+        };
+        let internet: Response = post(
+            &mut store,
+            "1",
+            r#"{"name": "internet", "date": "2026-03-31", "amount": 90.00,
+                "participants": ["all"], "payers": {"as": 90.00},
+                "period": {"start": "2026-03-01", "end": "2026-03-31"},
+                "presence": {"bob": {"start": "2026-03-22", "end": "2026-04-30"}}}"#,
+        );
+        assert_eq!(internet.get_status(), 201);
+        let consumed: Value = body(&internet)["consumed"].clone();
+        assert_eq!(consumed["Alice Smith"].as_f64(), Some(68.05_f32 as f64));
+        assert_eq!(consumed["Bob"].as_f64(), Some(21.95_f32 as f64));
+
+        let backwards: Response = post(
+            &mut store,
+            "2",
+            r#"{"name": "internet", "amount": 90.00, "participants": ["all"], "payers": {},
+                "period": {"start": "2026-03-31", "end": "2026-03-01"}}"#,
+        );
+        assert_eq!(backwards.get_status(), 400);
+        let without_period: Response = post(
+            &mut store,
+            "2",
+            r#"{"name": "internet", "amount": 90.00, "participants": ["all"], "payers": {},
+                "presence": {"bob": {"start": "2026-03-22", "end": "2026-03-31"}}}"#,
+        );
+        assert_eq!(without_period.get_status(), 400);
+    }
+
+    #[test]
+    fn test_expense_split_by_income() {
+        let mut store: Store = store();
+        let mut send = |method: &str, path: &str, version: &str, body: &str| {
+            handle(&mut store, &Request::new(method, path, Some(version), body))
+        };
+        let groceries = |date: &str| {
+            format!(
+                r#"{{"name": "groceries", "date": "{}", "amount": 100.00, "by_income": true,
+                    "participants": ["all"], "payers": {{"bob": 100.00}}}}"#,
+                date
+            )
+        };
+        let before_incomes: Response =
+            send("POST", "/groups/1/expenses", "1", &groceries("2026-05-10"));
+        assert_eq!(before_incomes.get_status(), 400);
+
+        for (version, member, income) in [
+            ("1", "as", r#"{"amount": 3}"#),
+            ("2", "bob", r#"{"amount": 2}"#),
+            ("3", "bob", r#"{"amount": 3, "from": "2026-06-01"}"#),
+        ] {
+            let path: String = format!("/groups/1/members/{}", member);
+            let body: String = format!(r#"{{"income": {}}}"#, income);
+            assert_eq!(send("PUT", &path, version, &body).get_status(), 200);
+        }
+
+        let may: Response = send("POST", "/groups/1/expenses", "4", &groceries("2026-05-10"));
+        assert_eq!(may.get_status(), 201);
+        assert_eq!(
+            body(&may)["consumed"],
+            json!({"Alice Smith": 60.0, "Bob": 40.0})
+        );
+        let june: Response = send("POST", "/groups/1/expenses", "5", &groceries("2026-06-10"));
+        assert_eq!(
+            body(&june)["consumed"],
+            json!({"Alice Smith": 50.0, "Bob": 50.0})
+        );
+    }
+
+    #[test]
+    fn test_expense_details_and_filters() {
+        let mut store: Store = store();
+        let post = |store: &mut Store, version: &str, body: &str| {
+            handle(
+                store,
+                &Request::new("POST", "/groups/1/expenses", Some(version), body),
+            )
+        };
+        let rent: Response = post(
+            &mut store,
+            "1",
+            r##"{"name": "rent", "time": "2026-03-31T23:00:00-05:00", "category": "rent",
+                "tags": ["#March"], "notes": "paid late", "amount": 1000.00,
+                "participants": ["all"], "payers": {"bob": 1000.00}}"##,
+        );
+        assert_eq!(rent.get_status(), 201);
+        assert_eq!(body(&rent)["date"], "2026-03-31");
+        assert_eq!(body(&rent)["category"], "Housing:Rent");
+        assert_eq!(body(&rent)["tags"], json!(["march"]));
+        let groceries: Response = post(
+            &mut store,
+            "2",
+            r#"{"name": "groceries", "date": "2026-04-02", "category": "groceries",
+                "amount": 30.00, "participants": ["all"], "payers": {"as": 30.00}}"#,
+        );
+        assert_eq!(groceries.get_status(), 201);
+
+        for invalid in [
+            r#""category": "pets""#,
+            r#""tags": ["two words"]"#,
+            r#""date": "2026-04-01", "time": "2026-03-31T23:00:00-05:00""#,
+        ] {
+            let body: String = format!(
+                r#"{{"name": "x", "amount": 1.00, "participants": ["all"], "payers": {{}}, {}}}"#,
+                invalid
+            );
+            assert_eq!(post(&mut store, "3", &body).get_status(), 400);
+        }
+
+        let get = |store: &mut Store, path: &str| {
+            body(&handle(store, &Request::new("GET", path, None, "")))
+        };
+        let housing: Value = get(&mut store, "/groups/1/expenses?category=Housing");
+        assert_eq!(housing.as_array().unwrap().len(), 1);
+        assert_eq!(housing[0]["id"], 1);
+        assert_eq!(
+            get(&mut store, "/groups/1/expenses?since=2026-04-01&tag=march"),
+            json!([])
+        );
+        assert_eq!(
+            get(&mut store, "/groups/1/balances?since=2026-04-01"),
+            json!({"Alice Smith": 15.0, "Bob": -15.0})
+        );
+        let bad: Response = handle(
+            &mut store,
+            &Request::new("GET", "/groups/1/balances?since=April", None, ""),
+        );
+        assert_eq!(bad.get_status(), 400);
+
+        let categories: Response = handle(
+            &mut store,
+            &Request::new("PUT", "/groups/1/categories", Some("3"), r#"["Pets:Food"]"#),
+        );
+        assert_eq!(body(&categories), json!(["Pets", "Pets:Food"]));
+    }
+
+    #[test]
+    fn test_split_does_not_change_group() {
+        let mut store: Store = store();
+        let response: Response = handle(
+            &mut store,
+            &Request::new(
+                "POST",
+                "/groups/1/split",
+                None,
+                r#"{"expenses": [{"name": "pizza", "amount": 20.00, "participants": ["dave"]}],
+                    "tax": 2.00}"#,
+            ),
+        );
+
+        assert_eq!(response.get_status(), 400);
+        assert_eq!(body(&response)["error"], "dave did not match anyone");
+
+        let response: Response = handle(
+            &mut store,
+            &Request::new(
+                "POST",
+                "/groups/1/split",
+                None,
+                r#"{"expenses": [{"name": "pizza", "amount": 20.00, "participants": ["all"]}],
+                    "tax": 2.00}"#,
+            ),
+        );
+        assert_eq!(response.get_status(), 200);
+        assert_eq!(body(&response)["total"], 22.0);
+        assert_eq!(body(&response)["rows"][1]["total"], 11.0);
+        assert_eq!(store.get_groups()[0].get_version(), 1);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("Alice%20Smith"), "Alice Smith");
+        assert_eq!(decode("100%"), "100%");
+    }
+}
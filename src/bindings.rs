@@ -0,0 +1,457 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::expense::{CalculatedExpense, PendingExpense};
+use crate::group::Group;
+use crate::period::Period;
+use crate::selection;
+use crate::session::Session;
+use crate::settlement::Settlement;
+use crate::split::{Breakdown, Split};
+use crate::user::User;
+
+/// Why a JSON split was refused, in words that can be shown to whoever entered it.
+#[derive(Debug, PartialEq)]
+pub struct BindingsError(String);
+
+impl BindingsError {
+    pub(crate) fn new(message: &str) -> Self {
+        BindingsError(message.to_string())
+    }
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A whole bill, as taken by `split_json`. Users are referred to by name everywhere else.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SplitInput {
+    users: Vec<UserInput>,
+    expenses: Vec<ExpenseInput>,
+    #[serde(default)]
+    tax: f32,
+    #[serde(default)]
+    tip: f32,
+    #[serde(default)]
+    payers: BTreeMap<String, f32>,
+}
+
+/// A user, with what they earn if any expense is split by income.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserInput {
+    name: String,
+    #[serde(default)]
+    income: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpenseInput {
+    name: String,
+    amount: f32,
+    #[serde(default)]
+    tax: f32,
+    #[serde(default)]
+    tip: f32,
+    participants: Vec<String>,
+    #[serde(default)]
+    payers: BTreeMap<String, f32>,
+    #[serde(default)]
+    period: Option<PeriodInput>,
+    #[serde(default)]
+    presence: BTreeMap<String, PeriodInput>,
+    #[serde(default)]
+    weights: BTreeMap<String, f32>,
+    #[serde(default)]
+    by_income: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PeriodInput {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+/// A bill being built up one call at a time by a caller that cannot hold on to borrowed
+/// users, such as C or Python. Members are referred to by name, and the bill is only split
+/// when the result is asked for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bill {
+    session: Session,
+    tax: f32,
+    tip: f32,
+    payers: Vec<(String, f32)>,
+}
+
+#[derive(Serialize)]
+struct SplitOutput {
+    breakdown: Breakdown,
+    settlements: Vec<Transfer>,
+}
+
+#[derive(Serialize)]
+struct Transfer {
+    from: String,
+    to: String,
+    amount: f32,
+}
+
+/// Splits a bill given as JSON and returns the breakdown and suggested settlements as JSON,
+/// for callers that cannot hold on to borrowed users, such as WebAssembly or C. The input
+/// looks like:
+///
+/// ```json
+/// {
+///   "users": [{"name": "alice"}, {"name": "bob"}],
+///   "expenses": [{"name": "pizza", "amount": 20.0, "participants": ["alice", "bob"]}],
+///   "tax": 2.0,
+///   "tip": 3.0,
+///   "payers": {"alice": 25.0}
+/// }
+/// ```
+///
+/// Expenses may also carry their own `tax`, `tip` and `payers`, and be split other than
+/// equally:
+///
+/// - by days present over a billing `period`, such as `"period": {"start": "2026-03-01",
+///   "end": "2026-03-31"}`, with `"presence": {"bob": {...}}` for anyone not there throughout
+/// - by `weights`, such as `{"alice": 2.0}`, where participants without one weigh 1
+/// - with `"by_income": true`, by each participant's `income`, given with their user
+///
+/// These combine by multiplying. Settlements are only suggested when someone paid.
+pub fn split_json(input: &str) -> Result<String, BindingsError> {
+    let input: SplitInput =
+        serde_json::from_str(input).map_err(|e| BindingsError(format!("invalid input: {}", e)))?;
+
+    let mut users: Vec<User> = Vec::new();
+    for user in &input.users {
+        let income: Option<f32> = user.income;
+        let mut user: User = User::new(user.name.trim())
+            .map_err(|_| BindingsError::new("user names must not be empty"))?;
+        if let Some(income) = income {
+            user.set_income(income, None)
+                .map_err(|_| BindingsError(format!("{}'s income must be 0 or more", user)))?;
+        }
+        if users.iter().any(|u| u.get_name() == user.get_name()) {
+            return Err(BindingsError(format!("{} is listed twice", user)));
+        }
+        users.push(user);
+    }
+
+    let mut calculated_expenses: Vec<CalculatedExpense> = Vec::new();
+    for expense in &input.expenses {
+        let invalid = || BindingsError(format!("{} is not a valid expense", expense.name));
+        let mut pending_expense: PendingExpense =
+            PendingExpense::new(&expense.name, expense.amount).map_err(|_| invalid())?;
+        pending_expense
+            .set_tax(expense.tax)
+            .map_err(|_| invalid())?;
+        pending_expense
+            .set_tip(expense.tip)
+            .map_err(|_| invalid())?;
+        for name in &expense.participants {
+            pending_expense.add_participant(find(&users, name)?);
+        }
+        for (name, amount) in &expense.payers {
+            pending_expense
+                .add_payer(find(&users, name)?, *amount)
+                .map_err(|_| invalid())?;
+        }
+        let period = |period: &PeriodInput| {
+            Period::new(period.start, period.end)
+                .map_err(|_| BindingsError::new("a period cannot end before it starts"))
+        };
+        if let Some(billing_period) = &expense.period {
+            pending_expense.set_billing_period(period(billing_period)?);
+        }
+        for (name, presence) in &expense.presence {
+            pending_expense.set_presence(find(&users, name)?, period(presence)?);
+        }
+        for (name, weight) in &expense.weights {
+            pending_expense
+                .set_weight(find(&users, name)?, *weight)
+                .map_err(|_| invalid())?;
+        }
+        if expense.by_income {
+            // Incomes given here have no start date, so they are in effect on any day.
+            pending_expense.split_by_income(NaiveDate::MAX);
+        }
+        calculated_expenses.push(
+            pending_expense
+                .finalize()
+                .map_err(|_| invalid())?
+                .calculate(),
+        );
+    }
+
+    let invalid = || BindingsError::new("the split is not valid");
+    let mut split: Split = Split::new(calculated_expenses.iter().collect(), input.tax, input.tip)
+        .map_err(|_| invalid())?;
+    for (name, amount) in &input.payers {
+        split
+            .add_payer(find(&users, name)?, *amount)
+            .map_err(|_| invalid())?;
+    }
+    let breakdown: Breakdown = split.process().map_err(|_| invalid())?.breakdown();
+    output(breakdown)
+}
+
+impl Bill {
+    /// Starts an empty bill shared among `group`'s members.
+    pub fn new(group: &Group) -> Result<Self, BindingsError> {
+        let session: Session = Session::new(
+            Some(group.get_name()),
+            group.get_roster().to_vec(),
+            group.get_aliases().clone(),
+        )
+        .map_err(|_| BindingsError(format!("{} has no members", group.get_name())))?;
+        Ok(Bill {
+            session,
+            tax: 0_f32,
+            tip: 0_f32,
+            payers: Vec::new(),
+        })
+    }
+
+    /// `participants` is a selection such as `all except bob`, see `selection::select`.
+    pub fn add_expense(
+        &mut self,
+        name: &str,
+        amount: f32,
+        participants: &str,
+    ) -> Result<(), BindingsError> {
+        let roster: Vec<User> = self.session.get_roster().to_vec();
+        let selected: Vec<&User> =
+            selection::select(&roster, self.session.get_aliases(), participants)
+                .map_err(|e| BindingsError(e.to_string()))?;
+        self.session
+            .add_expense(name, amount, &selected)
+            .map_err(|_| BindingsError(format!("{} is not a valid expense", name)))
+    }
+
+    pub fn set_tax_and_tip(&mut self, tax: f32, tip: f32) -> Result<(), BindingsError> {
+        if tax < 0_f32 || tip < 0_f32 {
+            return Err(BindingsError::new("tax and tip must be 0 or more"));
+        }
+        self.tax = tax;
+        self.tip = tip;
+        Ok(())
+    }
+
+    /// Records a payment towards the bill as a whole. Paying twice adds up.
+    pub fn add_payer(&mut self, member: &str, amount: f32) -> Result<(), BindingsError> {
+        let user: &User = selection::select_one(self.session.get_roster(), member)
+            .map_err(|e| BindingsError(e.to_string()))?;
+        if amount <= 0_f32 {
+            return Err(BindingsError::new("payments must be more than 0"));
+        }
+        self.payers.push((user.get_name().to_string(), amount));
+        Ok(())
+    }
+
+    /// Splits the bill and returns the same JSON as `split_json`.
+    pub fn to_json(&self) -> Result<String, BindingsError> {
+        let mut payers: Vec<(&User, f32)> = Vec::new();
+        for (name, amount) in &self.payers {
+            payers.push((find(self.session.get_roster(), name)?, *amount));
+        }
+        let breakdown: Breakdown = self
+            .session
+            .split(self.tax, self.tip, &payers)
+            .map_err(|_| BindingsError::new("the split is not valid"))?;
+        output(breakdown)
+    }
+}
+
+fn output(breakdown: Breakdown) -> Result<String, BindingsError> {
+    let balances: BTreeMap<String, f32> = breakdown
+        .rows
+        .iter()
+        .filter_map(|r| r.balance.map(|b| (r.name.clone(), b)))
+        .collect();
+    let settlements: Vec<Transfer> = Settlement::suggest(&balances, NaiveDate::MIN)
+        .iter()
+        .map(|s| Transfer {
+            from: s.get_from().to_string(),
+            to: s.get_to().to_string(),
+            amount: s.get_amount(),
+        })
+        .collect();
+
+    serde_json::to_string(&SplitOutput {
+        breakdown,
+        settlements,
+    })
+    .map_err(|e| BindingsError(format!("invalid output: {}", e)))
+}
+
+fn find<'a>(users: &'a [User], name: &str) -> Result<&'a User, BindingsError> {
+    users
+        .iter()
+        .find(|u| u.get_name() == name)
+        .ok_or_else(|| BindingsError(format!("{} is not one of the users", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_split_json() {
+        let input: Value = json!({
+            "users": [{"name": "alice"}, {"name": "bob"}],
+            "expenses": [
+                {"name": "pizza", "amount": 20.0, "participants": ["alice", "bob"]},
+                {"name": "wine", "amount": 10.0, "participants": ["alice"]}
+            ],
+            "tax": 3.0,
+            "payers": {"bob": 33.0}
+        });
+
+        let output: Value = serde_json::from_str(&split_json(&input.to_string()).unwrap()).unwrap();
+        assert_eq!(output["breakdown"]["total"], 33.0);
+        assert_eq!(output["breakdown"]["rows"][0]["name"], "alice");
+        assert_eq!(output["breakdown"]["rows"][0]["total"], 22.0);
+        assert_eq!(
+            output["settlements"],
+            json!([{"from": "alice", "to": "bob", "amount": 22.0}])
+        );
+    }
+
+    #[test]
+    fn test_split_json_policies() {
+        let input: Value = json!({
+            "users": [
+                {"name": "alice", "income": 6000.0},
+                {"name": "bob", "income": 3000.0},
+                {"name": "carol"}
+            ],
+            "expenses": [
+                {"name": "power", "amount": 40.0, "participants": ["alice", "bob"],
+                 "period": {"start": "2026-03-01", "end": "2026-03-20"},
+                 "presence": {"bob": {"start": "2026-03-11", "end": "2026-04-30"}}},
+                {"name": "rent", "amount": 30.0, "participants": ["alice", "carol"],
+                 "weights": {"alice": 2.0}},
+                {"name": "food", "amount": 90.0, "participants": ["alice", "bob"],
+                 "by_income": true}
+            ]
+        });
+
+        let output: Value = serde_json::from_str(&split_json(&input.to_string()).unwrap()).unwrap();
+        let totals: Vec<f64> = output["breakdown"]["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["total"].as_f64().unwrap())
+            .collect();
+        assert_eq!(totals, vec![106.67, 43.33, 10.0]);
+    }
+
+    #[test]
+    fn test_split_json_failure_policies() {
+        let expense = |policy: Value| {
+            let mut expense: Value =
+                json!({"name": "food", "amount": 10.0, "participants": ["alice"]});
+            expense
+                .as_object_mut()
+                .unwrap()
+                .extend(policy.as_object().unwrap().clone());
+            json!({"users": [{"name": "alice"}, {"name": "bob"}], "expenses": [expense]})
+                .to_string()
+        };
+
+        assert_eq!(
+            split_json(&expense(json!({"by_income": true}))).unwrap_err(),
+            BindingsError::new("food is not a valid expense")
+        );
+        assert_eq!(
+            split_json(&expense(json!({"weights": {"bob": 1.0}}))).unwrap_err(),
+            BindingsError::new("food is not a valid expense")
+        );
+        assert_eq!(
+            split_json(&expense(
+                json!({"period": {"start": "2026-03-02", "end": "2026-03-01"}})
+            ))
+            .unwrap_err(),
+            BindingsError::new("a period cannot end before it starts")
+        );
+
+        let misspelled: String = json!({
+            "users": [{"name": "alice", "incmoe": 100.0}],
+            "expenses": [{"name": "food", "amount": 10.0, "participants": ["alice"]}]
+        })
+        .to_string();
+        assert!(split_json(&misspelled)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `incmoe`"));
+    }
+
+    #[test]
+    fn test_bill_matches_split_json() {
+        let mut group: Group = Group::new("Dinner").unwrap();
+        group.add_member(User::new("alice").unwrap()).unwrap();
+        group.add_member(User::new("bob").unwrap()).unwrap();
+
+        let mut bill: Bill = Bill::new(&group).unwrap();
+        bill.add_expense("pizza", 20.00, "all").unwrap();
+        bill.add_expense("wine", 10.00, "a").unwrap();
+        bill.set_tax_and_tip(3.00, 0.00).unwrap();
+        bill.add_payer("b", 33.00).unwrap();
+
+        let input: Value = json!({
+            "users": [{"name": "alice"}, {"name": "bob"}],
+            "expenses": [
+                {"name": "pizza", "amount": 20.0, "participants": ["alice", "bob"]},
+                {"name": "wine", "amount": 10.0, "participants": ["alice"]}
+            ],
+            "tax": 3.0,
+            "payers": {"bob": 33.0}
+        });
+        assert_eq!(
+            bill.to_json().unwrap(),
+            split_json(&input.to_string()).unwrap()
+        );
+        assert_eq!(
+            bill.add_expense("cake", 5.00, "carol").unwrap_err(),
+            BindingsError::new("carol did not match anyone")
+        );
+    }
+
+    #[test]
+    fn test_split_json_failure_unknown_user() {
+        let input: Value = json!({
+            "users": [{"name": "alice"}],
+            "expenses": [{"name": "pizza", "amount": 20.0, "participants": ["bob"]}]
+        });
+
+        assert_eq!(
+            split_json(&input.to_string()).unwrap_err(),
+            BindingsError::new("bob is not one of the users")
+        );
+    }
+
+    #[test]
+    fn test_split_json_failure_payments_do_not_add_up() {
+        let input: Value = json!({
+            "users": [{"name": "alice"}],
+            "expenses": [{"name": "pizza", "amount": 20.0, "participants": ["alice"]}],
+            "payers": {"alice": 19.0}
+        });
+
+        assert_eq!(
+            split_json(&input.to_string()).unwrap_err(),
+            BindingsError::new("the split is not valid")
+        );
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Local, NaiveDate};
+
+use crate::expense::{CalculatedExpense, PendingExpense};
+use crate::group::Group;
+use crate::ledger::LedgerEntry;
+use crate::settlement::Settlement;
+use crate::split::{ProcessedSplit, Split};
+use crate::store::Store;
+use crate::user::User;
+
+const HELP: &str = "Commands:
+/expense <amount> <what> [@member...] [paid:<member>] [by:income] - shared by everyone unless members are mentioned, paid by you unless paid: says otherwise, equally unless by:income splits it by income
+/balance - who owes what
+/settle - suggested repayments
+/settle [@from] @to <amount> - record a repayment, from you unless @from is given";
+
+/// Why a chat command could not be carried out, worded as a reply to whoever sent it.
+#[derive(Debug, PartialEq)]
+pub struct ChatError(String);
+
+impl fmt::Display for ChatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// One message from a chat. `chat` names the group the chat is tied to, and `sender` must
+/// be the name of one of its members for commands that act on the sender's behalf.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    pub chat: String,
+    pub sender: String,
+    pub text: String,
+}
+
+/// Where chat messages come from and replies go to, such as a Matrix room or a Telegram bot.
+pub trait Transport {
+    /// Waits for the next message, or returns `None` once the transport is closed.
+    fn receive(&mut self) -> Option<ChatMessage>;
+
+    fn reply(&mut self, chat: &str, text: &str);
+}
+
+/// A parsed chat command. Members are still references, such as `@me` or initials, until
+/// the command is executed against a group.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Expense {
+        amount: f32,
+        name: String,
+        participants: Vec<String>,
+        payer: Option<String>,
+        by_income: bool,
+    },
+    Balance,
+    Plan,
+    Settle {
+        from: Option<String>,
+        to: String,
+        amount: f32,
+    },
+    Help,
+}
+
+/// Parses commands such as `/expense 48.00 pizza @alice @bob @me paid:alice`, `/balance`
+/// and `/settle @bob 12`. `by:income` splits an expense by income, see `User::income_on`.
+pub fn parse(text: &str) -> Result<Command, ChatError> {
+    let mut words = text.split_whitespace();
+    let command: &str = words.next().unwrap_or_default();
+    let words: Vec<&str> = words.collect();
+    let mentions: Vec<String> = words
+        .iter()
+        .filter_map(|w| w.strip_prefix('@'))
+        .map(|w| w.to_string())
+        .collect();
+
+    match command.split('@').next().unwrap_or_default() {
+        "/expense" => {
+            let amount: f32 = words.first().and_then(|w| parse_amount(w)).ok_or_else(|| {
+                ChatError("Start with the amount, e.g. /expense 48.00 pizza".to_string())
+            })?;
+            let mut payer: Option<String> = None;
+            let mut by_income: bool = false;
+            let mut name: Vec<&str> = Vec::new();
+            for word in &words[1..] {
+                if let Some(reference) = word.strip_prefix("paid:") {
+                    payer = Some(reference.trim_start_matches('@').to_string());
+                } else if word.eq_ignore_ascii_case("by:income") {
+                    by_income = true;
+                } else if !word.starts_with('@') {
+                    name.push(word);
+                }
+            }
+            if name.is_empty() {
+                return Err(ChatError(
+                    "Say what the expense was for, e.g. /expense 48.00 pizza".to_string(),
+                ));
+            }
+            Ok(Command::Expense {
+                amount,
+                name: name.join(" "),
+                participants: mentions,
+                payer,
+                by_income,
+            })
+        }
+        "/balance" | "/balances" => Ok(Command::Balance),
+        "/settle" if words.is_empty() => Ok(Command::Plan),
+        "/settle" => {
+            let amount: Option<f32> = words.iter().find_map(|w| parse_amount(w));
+            let usage = || ChatError("Use /settle [@from] @to <amount>".to_string());
+            let (from, to) = match mentions.as_slice() {
+                [to] => (None, to.clone()),
+                [from, to] => (Some(from.clone()), to.clone()),
+                _ => return Err(usage()),
+            };
+            Ok(Command::Settle {
+                from,
+                to,
+                amount: amount.ok_or_else(usage)?,
+            })
+        }
+        "/help" | "/start" => Ok(Command::Help),
+        _ => Err(ChatError(format!("I don't know {}. {}", command, HELP))),
+    }
+}
+
+/// Carries out `command` on `group` for `sender` and returns the reply. Only expenses and
+/// settlements change the group's ledger.
+pub fn execute(
+    group: &mut Group,
+    sender: &str,
+    command: Command,
+    date: NaiveDate,
+) -> Result<String, ChatError> {
+    match command {
+        Command::Expense { amount, .. } => {
+            let entry: LedgerEntry = expense(group, sender, &command, date)?;
+            let shares: Vec<String> = entry
+                .get_consumed()
+                .iter()
+                .map(|(name, share)| format!("{} ${:.2}", name, share))
+                .collect();
+            let paid: Vec<&str> = entry.get_paid().keys().map(|n| n.as_str()).collect();
+            let reply: String = format!(
+                "Recorded {} for ${:.2}, paid by {}. Shares: {}.",
+                entry.get_name(),
+                amount,
+                paid.join(", "),
+                shares.join(", ")
+            );
+            group.get_ledger_mut().record_entry(entry);
+            Ok(reply)
+        }
+        Command::Balance => Ok(balances(&group.get_ledger().balances())),
+        Command::Plan => {
+            let plan: Vec<Settlement> = Settlement::suggest(&group.get_ledger().balances(), date);
+            if plan.is_empty() {
+                return Ok("Everyone is settled up.".to_string());
+            }
+            let lines: Vec<String> = plan
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{} pays {} ${:.2}",
+                        s.get_from(),
+                        s.get_to(),
+                        s.get_amount()
+                    )
+                })
+                .collect();
+            Ok(lines.join("\n"))
+        }
+        Command::Settle { from, to, amount } => {
+            let from: &User = match from {
+                Some(from) => member(group, sender, &from)?,
+                None => member(group, sender, "me")?,
+            };
+            let to: &User = member(group, sender, &to)?;
+            let settlement: Settlement = Settlement::new(from, to, amount, date).map_err(|_| {
+                ChatError("A repayment is between two people and more than $0.".to_string())
+            })?;
+            let reply: String = format!(
+                "Recorded that {} paid {} ${:.2}.",
+                settlement.get_from(),
+                settlement.get_to(),
+                settlement.get_amount()
+            );
+            group.get_ledger_mut().record_settlement(settlement);
+            Ok(reply)
+        }
+        Command::Help => Ok(HELP.to_string()),
+    }
+}
+
+/// Answers one message against the group the chat is tied to. Returns `None` for messages
+/// that are not commands, and whether the group changed along with the reply otherwise.
+pub fn handle(store: &mut Store, message: &ChatMessage, date: NaiveDate) -> Option<(String, bool)> {
+    if !message.text.starts_with('/') {
+        return None;
+    }
+    let group: &mut Group = match store.find_group_mut(&message.chat) {
+        Ok(group) => group,
+        Err(_) => {
+            return Some((
+                format!(
+                    "This chat has no group yet. Create one named {} first.",
+                    message.chat
+                ),
+                false,
+            ))
+        }
+    };
+
+    let command: Command = match parse(&message.text) {
+        Ok(command) => command,
+        Err(e) => return Some((e.to_string(), false)),
+    };
+    let changes: bool = matches!(command, Command::Expense { .. } | Command::Settle { .. });
+    match execute(group, &message.sender, command, date) {
+        Ok(reply) => Some((reply, changes)),
+        Err(e) => Some((e.to_string(), false)),
+    }
+}
+
+/// Answers messages from `transport` until it closes, calling `save` after every change so
+/// the ledger persists between messages.
+pub fn run<T: Transport>(transport: &mut T, store: &mut Store, mut save: impl FnMut(&Store)) {
+    while let Some(message) = transport.receive() {
+        if let Some((reply, changed)) = handle(store, &message, Local::now().date_naive()) {
+            if changed {
+                save(store);
+            }
+            transport.reply(&message.chat, &reply);
+        }
+    }
+}
+
+fn expense(
+    group: &Group,
+    sender: &str,
+    command: &Command,
+    date: NaiveDate,
+) -> Result<LedgerEntry, ChatError> {
+    let Command::Expense {
+        amount,
+        name,
+        participants,
+        payer,
+        by_income,
+    } = command
+    else {
+        unreachable!("only expenses are recorded as expenses");
+    };
+    let (amount, by_income): (f32, bool) = (*amount, *by_income);
+    let mut pending_expense: PendingExpense = PendingExpense::new(name, amount)
+        .map_err(|_| ChatError("The amount must be more than $0.".to_string()))?;
+    let mut selected: Vec<&User> = Vec::new();
+    if participants.is_empty() {
+        selected.extend(group.get_roster());
+    }
+    for reference in participants {
+        let users: Vec<&User> = match reference.as_str() {
+            "me" => vec![member(group, sender, reference)?],
+            _ => group
+                .select(reference)
+                .map_err(|e| ChatError(format!("@{}.", e)))?,
+        };
+        for user in users {
+            if !selected.contains(&user) {
+                selected.push(user);
+            }
+        }
+    }
+    for user in &selected {
+        pending_expense.add_participant(user);
+    }
+    if by_income {
+        if let Some(user) = selected.iter().find(|u| u.income_on(date).is_none()) {
+            return Err(ChatError(format!(
+                "{} has no income recorded to split by.",
+                user
+            )));
+        }
+        pending_expense.split_by_income(date);
+    }
+    let payer: &User = member(group, sender, payer.as_deref().unwrap_or("me"))?;
+    pending_expense
+        .add_payer(payer, amount)
+        .map_err(|_| ChatError("The amount must be more than $0.".to_string()))?;
+
+    let invalid = || ChatError("The group has nobody to share that with.".to_string());
+    let calculated_expense: CalculatedExpense = pending_expense
+        .finalize()
+        .map_err(|_| invalid())?
+        .calculate();
+    let split: ProcessedSplit = Split::new(vec![&calculated_expense], 0_f32, 0_f32)
+        .and_then(|split| split.process())
+        .map_err(|_| invalid())?;
+    LedgerEntry::from_split(name, date, &split).map_err(|_| invalid())
+}
+
+/// Resolves `reference` to a member, where `me` is the sender. The sender is matched by
+/// full name only, so that nobody acts as someone else by accident.
+fn member<'a>(group: &'a Group, sender: &str, reference: &str) -> Result<&'a User, ChatError> {
+    if reference.eq_ignore_ascii_case("me") {
+        return group
+            .get_roster()
+            .iter()
+            .find(|u| u.get_name().eq_ignore_ascii_case(sender))
+            .ok_or_else(|| ChatError(format!("{} is not in {}.", sender, group.get_name())));
+    }
+    group
+        .find_member(reference)
+        .map_err(|_| ChatError(format!("@{} did not match anyone.", reference)))
+}
+
+fn balances(balances: &BTreeMap<String, f32>) -> String {
+    let lines: Vec<String> = balances
+        .iter()
+        .filter(|(_, balance)| **balance != 0_f32)
+        .map(|(name, balance)| {
+            if *balance > 0_f32 {
+                format!("{} is owed ${:.2}", name, balance)
+            } else {
+                format!("{} owes ${:.2}", name, -balance)
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        return "Everyone is settled up.".to_string();
+    }
+    lines.join("\n")
+}
+
+fn parse_amount(word: &str) -> Option<f32> {
+    word.trim_start_matches('$')
+        .parse::<f32>()
+        .ok()
+        .filter(|amount| amount.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Delivers scripted messages and keeps every reply.
+    struct FakeTransport {
+        incoming: VecDeque<ChatMessage>,
+        replies: Vec<(String, String)>,
+    }
+
+    impl Transport for FakeTransport {
+        fn receive(&mut self) -> Option<ChatMessage> {
+            self.incoming.pop_front()
+        }
+
+        fn reply(&mut self, chat: &str, text: &str) {
+            self.replies.push((chat.to_string(), text.to_string()));
+        }
+    }
+
+    fn transport(messages: &[(&str, &str)]) -> FakeTransport {
+        FakeTransport {
+            incoming: messages
+                .iter()
+                .map(|(sender, text)| ChatMessage {
+                    chat: "Roommates".to_string(),
+                    sender: sender.to_string(),
+                    text: text.to_string(),
+                })
+                .collect(),
+            replies: Vec::new(),
+        }
+    }
+
+    fn store() -> Store {
+        let mut group: Group = Group::new("Roommates").unwrap();
+        for name in ["alice", "bob", "carol"] {
+            group.add_member(User::new(name).unwrap()).unwrap();
+        }
+        let mut store: Store = Store::new();
+        store.add_group(group).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_parse_expense() {
+        assert_eq!(
+            parse("/expense 48.00 pizza night @alice @bob @me paid:alice"),
+            Ok(Command::Expense {
+                amount: 48.00,
+                name: "pizza night".to_string(),
+                participants: vec!["alice".to_string(), "bob".to_string(), "me".to_string()],
+                payer: Some("alice".to_string()),
+                by_income: false,
+            })
+        );
+        assert!(parse("/expense pizza 48").is_err());
+        assert!(matches!(
+            parse("/expense 90 power By:Income"),
+            Ok(Command::Expense { by_income: true, name, .. }) if name == "power"
+        ));
+    }
+
+    #[test]
+    fn test_parse_settle() {
+        assert_eq!(parse("/settle"), Ok(Command::Plan));
+        assert_eq!(
+            parse("/settle @bob $12.50"),
+            Ok(Command::Settle {
+                from: None,
+                to: "bob".to_string(),
+                amount: 12.50,
+            })
+        );
+        assert!(parse("/settle 12").is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let mut store: Store = store();
+        let mut saves: usize = 0;
+        let mut transport: FakeTransport = transport(&[
+            ("carol", "/expense 48.00 pizza @alice @bob @me paid:alice"),
+            ("bob", "hello"),
+            ("bob", "/balance"),
+            ("bob", "/settle"),
+            ("bob", "/settle @alice 16"),
+            ("carol", "/settle @dave 16"),
+            ("carol", "/balance"),
+        ]);
+
+        run(&mut transport, &mut store, |_| saves += 1);
+
+        let replies: Vec<&str> = transport.replies.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(
+            replies,
+            vec![
+                "Recorded pizza for $48.00, paid by alice. Shares: alice $16.00, bob $16.00, carol $16.00.",
+                "alice is owed $32.00\nbob owes $16.00\ncarol owes $16.00",
+                "bob pays alice $16.00\ncarol pays alice $16.00",
+                "Recorded that bob paid alice $16.00.",
+                "@dave did not match anyone.",
+                "alice is owed $16.00\ncarol owes $16.00",
+            ]
+        );
+        assert_eq!(saves, 2);
+    }
+
+    #[test]
+    fn test_run_expense_by_income() {
+        let mut store: Store = store();
+        let group: &mut Group = store.find_group_mut("1").unwrap();
+        group
+            .find_member_mut("alice")
+            .unwrap()
+            .set_income(2_f32, None)
+            .unwrap();
+        group
+            .find_member_mut("bob")
+            .unwrap()
+            .set_income(1_f32, None)
+            .unwrap();
+        let mut transport: FakeTransport = transport(&[
+            ("alice", "/expense 30 power @alice @bob by:income"),
+            ("alice", "/expense 30 power by:income"),
+        ]);
+
+        run(&mut transport, &mut store, |_| {});
+
+        let replies: Vec<&str> = transport.replies.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(
+            replies,
+            vec![
+                "Recorded power for $30.00, paid by alice. Shares: alice $20.00, bob $10.00.",
+                "carol has no income recorded to split by.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_failure_sender_not_a_member() {
+        let mut store: Store = store();
+        let mut transport: FakeTransport = transport(&[("dave", "/expense 10 cake")]);
+
+        run(&mut transport, &mut store, |_| {});
+
+        assert_eq!(transport.replies[0].1, "dave is not in Roommates.");
+        assert!(store.get_groups()[0].get_ledger().get_entries().is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use heesab::details::{Details, Filter};
+use heesab::group::Group;
+use heesab::ledger::Ledger;
+use heesab::payment::{self, PaymentHandle};
+use heesab::settlement::Settlement;
+use heesab::store::Store;
+
+/// `heesab balances [<group>] [--since <date>] [--until <date>] [--category <category>]
+/// [--tag <tag>]...` lists the ledger and outstanding balances. With a filter, only matching
+/// expenses count and repayments are not suggested, since the balances are only partial.
+pub fn run(args: &[String]) {
+    let (references, filter): (Vec<&str>, Filter) = match super::parse_filter(args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            println!("Error: {}.", message);
+            return;
+        }
+    };
+    if references.len() > 1 {
+        super::print_usage();
+        return;
+    }
+    let group_reference: Option<&str> = references.first().copied();
+
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, group_reference) {
+        Some(group) => group,
+        None => return,
+    };
+    let ledger: &Ledger = group.get_ledger();
+
+    let mut history: Vec<(NaiveDate, String)> = Vec::new();
+    for entry in ledger.entries_matching(&filter) {
+        let details: &Details = entry.get_details();
+        let labels: Vec<String> = details
+            .get_category()
+            .map(|c| c.to_string())
+            .into_iter()
+            .chain(details.get_tags().iter().map(|t| format!("#{}", t)))
+            .collect();
+        let paid: Vec<String> = entry
+            .get_paid()
+            .iter()
+            .map(|(k, v)| format!("{} paid ${}", k, v))
+            .collect();
+        let consumed: Vec<String> = entry
+            .get_consumed()
+            .iter()
+            .map(|(k, v)| format!("{}'s share ${}", k, v))
+            .collect();
+        history.push((
+            entry.get_date(),
+            format!(
+                "{}{} - {}; {}",
+                entry.get_name(),
+                if labels.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", labels.join(" "))
+                },
+                paid.join(", "),
+                consumed.join(", ")
+            ),
+        ));
+    }
+    let settlements = ledger
+        .get_settlements()
+        .iter()
+        .filter(|s| filter.is_dates_only() && filter.matches_date(s.get_date()));
+    for settlement in settlements {
+        history.push((
+            settlement.get_date(),
+            format!(
+                "settlement - {} paid {} ${}",
+                settlement.get_from(),
+                settlement.get_to(),
+                settlement.get_amount()
+            ),
+        ));
+    }
+    history.sort_by_key(|(date, _)| *date);
+
+    if history.is_empty() {
+        match filter == Filter::new() {
+            true => println!("Nothing recorded for {} yet.", group.get_name()),
+            false => println!("Nothing recorded for {} matches.", group.get_name()),
+        }
+        return;
+    }
+
+    for (date, line) in &history {
+        println!("{} {}", date, line);
+    }
+
+    if filter != Filter::new() {
+        println!("\nShares of matching expenses:");
+        for (name, share) in ledger.shares_matching(&filter) {
+            println!("{} ${}", name, share);
+        }
+    }
+
+    println!("\nOutstanding balances:");
+    let balances: BTreeMap<String, f32> = ledger.balances_matching(&filter);
+    for (name, balance) in &balances {
+        if *balance > 0_f32 {
+            println!("{} is owed ${}", name, balance);
+        } else if *balance < 0_f32 {
+            println!("{} owes ${}", name, -balance);
+        } else {
+            println!("{} is settled up", name);
+        }
+    }
+
+    let suggestions: Vec<Settlement> = Settlement::suggest(&balances, NaiveDate::MIN);
+    if suggestions.is_empty() || filter != Filter::new() {
+        return;
+    }
+    println!("\nSuggested repayments:");
+    for suggestion in &suggestions {
+        println!(
+            "{} pays {} ${:.2}",
+            suggestion.get_from(),
+            suggestion.get_to(),
+            suggestion.get_amount()
+        );
+        for (handle, request) in requests(group, suggestion) {
+            match handle {
+                // The EPC payload is only meant to be scanned.
+                PaymentHandle::Iban(iban) => println!(
+                    "  iban: {} (heesab qr {} {} {} iban for a SEPA QR code)",
+                    iban,
+                    group.get_name(),
+                    suggestion.get_from(),
+                    suggestion.get_to()
+                ),
+                _ => println!("  {}: {}", handle.get_kind(), request),
+            }
+        }
+    }
+}
+
+/// Payment requests for each of the payee's handles, pre-filled with the amount and a memo
+/// listing the expenses behind the repayment.
+pub fn requests<'a>(group: &'a Group, settlement: &Settlement) -> Vec<(&'a PaymentHandle, String)> {
+    let payee = match group.find_member(settlement.get_to()) {
+        Ok(payee) => payee,
+        Err(_) => return Vec::new(),
+    };
+    let memo: String = payment::memo(
+        group.get_ledger(),
+        settlement.get_from(),
+        settlement.get_to(),
+    );
+    payee
+        .get_payment_handles()
+        .iter()
+        .map(|h| {
+            (
+                h,
+                h.request(payee.get_name(), settlement.get_amount(), &memo),
+            )
+        })
+        .collect()
+}
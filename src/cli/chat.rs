@@ -0,0 +1,49 @@
+use std::io::{self, BufRead};
+
+use heesab::chat::{self, ChatMessage, Transport};
+use heesab::store::Store;
+
+/// A chat on the terminal, with every line sent by the same member. Useful for trying out
+/// commands before wiring up a real chat.
+struct Terminal {
+    chat: String,
+    sender: String,
+}
+
+impl Transport for Terminal {
+    fn receive(&mut self) -> Option<ChatMessage> {
+        let mut line: String = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(ChatMessage {
+                chat: self.chat.clone(),
+                sender: self.sender.clone(),
+                text: line.trim().to_string(),
+            }),
+        }
+    }
+
+    fn reply(&mut self, _chat: &str, text: &str) {
+        println!("{}", text);
+    }
+}
+
+pub fn run(args: &[String]) {
+    if args.len() != 2 {
+        super::print_usage();
+        return;
+    }
+
+    let mut store: Store = super::load_store();
+    let chat: String = match super::pick_group(&mut store, Some(&args[0])) {
+        Some(group) => group.get_name().to_string(),
+        None => return,
+    };
+    println!("Chatting in {} as {}. Try /help.", chat, args[1]);
+
+    let mut terminal: Terminal = Terminal {
+        chat,
+        sender: args[1].clone(),
+    };
+    chat::run(&mut terminal, &mut store, super::save_store);
+}
@@ -0,0 +1,45 @@
+use std::fs;
+
+use heesab::export::{self, Accounts, Dialect};
+use heesab::group::Group;
+use heesab::store::Store;
+
+/// `heesab export <ledger|hledger|beancount> <group> <member> [<accounts.json>]` prints the
+/// member's side of the group's ledger. The JSON file overrides account names, see `Accounts`.
+pub fn run(args: &[String]) {
+    if args.len() != 3 && args.len() != 4 {
+        super::print_usage();
+        return;
+    }
+    let dialect: Dialect = match Dialect::new(&args[0]) {
+        Ok(dialect) => dialect,
+        Err(_) => {
+            println!("Error: Please export to ledger, hledger or beancount.");
+            return;
+        }
+    };
+    let accounts: Accounts = match args.get(3) {
+        None => Accounts::default(),
+        Some(path) => match fs::read_to_string(path).map(|json| serde_json::from_str(&json)) {
+            Ok(Ok(accounts)) => accounts,
+            Ok(Err(e)) => {
+                println!("Error: {} is not a valid account mapping: {}", path, e);
+                return;
+            }
+            Err(_) => {
+                println!("Error: Could not read {}.", path);
+                return;
+            }
+        },
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, Some(&args[1])) {
+        Some(group) => group,
+        None => return,
+    };
+    match export::export(group, &args[2], dialect, &accounts) {
+        Ok(output) => print!("{}", output),
+        Err(_) => println!("Error: {} did not match a member.", args[2]),
+    }
+}
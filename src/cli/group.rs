@@ -0,0 +1,275 @@
+use chrono::NaiveDate;
+
+use heesab::group::{Group, GroupError};
+use heesab::payment::PaymentHandle;
+use heesab::store::Store;
+use heesab::taxonomy::Taxonomy;
+use heesab::user::{User, UserError};
+
+pub fn run(args: &[String]) {
+    match args.first().map(|a| a.as_str()) {
+        Some("list") => list(),
+        Some("create") if args.len() >= 2 => create(&args[1], &args[2..]),
+        Some("show") if args.len() == 2 => show(&args[1]),
+        Some("add") if args.len() == 3 || args.len() == 4 => {
+            add(&args[1], &args[2], args.get(3).map(|a| a.as_str()))
+        }
+        Some("remove") if args.len() == 3 => remove(&args[1], &args[2]),
+        Some("alias") if args.len() == 4 => alias(&args[1], &args[2], &args[3]),
+        Some("handle") if args.len() == 5 => handle(&args[1], &args[2], &args[3], &args[4]),
+        Some("categories") if args.len() >= 2 => categories(&args[1], &args[2..]),
+        Some("income") if args.len() == 4 || args.len() == 5 => income(
+            &args[1],
+            &args[2],
+            &args[3],
+            args.get(4).map(|a| a.as_str()),
+        ),
+        _ => super::print_usage(),
+    }
+}
+
+fn list() {
+    let store: Store = super::load_store();
+    if store.get_groups().is_empty() {
+        println!("No groups yet. Create one with heesab group create <group> [<member>...]");
+    }
+    for (index, group) in store.get_groups().iter().enumerate() {
+        let names: Vec<&str> = group.get_roster().iter().map(|u| u.get_name()).collect();
+        println!("{}. {} - {}", index + 1, group.get_name(), names.join(", "));
+    }
+}
+
+fn create(name: &str, members: &[String]) {
+    let mut group: Group = match Group::new(name.trim()) {
+        Ok(group) => group,
+        Err(_) => {
+            println!("Error: Group name is invalid.");
+            return;
+        }
+    };
+
+    for member in members {
+        let maybe_user: Result<User, UserError> = User::new(member.trim());
+        let maybe_added: Result<(), GroupError> = match maybe_user {
+            Ok(user) => group.add_member(user),
+            Err(_) => Err(GroupError),
+        };
+        if maybe_added.is_err() {
+            println!("Error: {} is not a valid, unique name.", member);
+            return;
+        }
+    }
+
+    let mut store: Store = super::load_store();
+    if store.add_group(group).is_err() {
+        println!("Error: A group named {} already exists.", name);
+        return;
+    }
+    super::save_store(&store);
+    println!("Created {}.", name);
+}
+
+fn show(reference: &str) {
+    let mut store: Store = super::load_store();
+    if let Some(group) = super::pick_group(&mut store, Some(reference)) {
+        println!("{}", group.get_name());
+        for (index, user) in group.get_roster().iter().enumerate() {
+            match user.get_email() {
+                None => println!("{}. {} ({})", index + 1, user, user.initials()),
+                Some(email) => {
+                    println!("{}. {} ({}) <{}>", index + 1, user, user.initials(), email)
+                }
+            }
+        }
+        for user in group.get_roster() {
+            for handle in user.get_payment_handles() {
+                println!("{} {}: {}", user, handle.get_kind(), handle.get_handle());
+            }
+        }
+        for (alias, members) in group.get_aliases() {
+            println!("{} = {}", alias, members.join(", "));
+        }
+        for user in group.get_roster() {
+            for (from, income) in user.get_incomes() {
+                match from {
+                    None => println!("{} income: {}", user, income),
+                    Some(from) => println!("{} income: {} from {}", user, income, from),
+                }
+            }
+        }
+    }
+}
+
+fn add(reference: &str, name: &str, email: Option<&str>) {
+    let mut user: User = match User::new(name.trim()) {
+        Ok(user) => user,
+        Err(_) => {
+            println!("Error: Name is invalid.");
+            return;
+        }
+    };
+    if let Some(email) = email {
+        if user.set_email(email.trim()).is_err() {
+            println!("Error: Email is invalid.");
+            return;
+        }
+    }
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    if group.add_member(user).is_err() {
+        println!("Error: {} is already a member.", name);
+        return;
+    }
+    let group_name: String = group.get_name().to_string();
+    super::save_store(&store);
+    println!("Added {} to {}.", name, group_name);
+}
+
+fn remove(reference: &str, member: &str) {
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    match group.remove_member(member) {
+        Ok(user) => {
+            let group_name: String = group.get_name().to_string();
+            super::save_store(&store);
+            println!("Removed {} from {}.", user, group_name);
+        }
+        Err(_) => match group.find_member(member).map(|u| u.get_name().to_string()) {
+            Ok(name) if group.in_recurring(&name) => println!(
+                "Error: {} is in a recurring expense. Remove it first with heesab recurring remove.",
+                name
+            ),
+            Ok(name) => println!(
+                "Error: {} lives in a room of the rent plan. Move them out first with heesab rent {} assign <room> <members>.",
+                name,
+                group.get_name()
+            ),
+            Err(_) => println!("Error: {} did not match a member.", member),
+        },
+    }
+}
+
+fn alias(reference: &str, alias: &str, members: &str) {
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    if group.add_alias(alias, members).is_err() {
+        println!(
+            "Error: {} must select members and must not be a member's name.",
+            alias
+        );
+        return;
+    }
+    let group_name: String = group.get_name().to_string();
+    super::save_store(&store);
+    println!("Added alias {} to {}.", alias, group_name);
+}
+
+fn handle(reference: &str, member: &str, kind: &str, handle: &str) {
+    let handle: PaymentHandle = match PaymentHandle::new(kind, handle) {
+        Ok(handle) => handle,
+        Err(_) => {
+            println!(
+                "Error: Please give a UPI address, a valid IBAN, or a PayPal.me or Venmo username."
+            );
+            return;
+        }
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let user: &mut User = match group.find_member_mut(member) {
+        Ok(user) => user,
+        Err(_) => {
+            println!("Error: {} did not match a member.", member);
+            return;
+        }
+    };
+    user.set_payment_handle(handle);
+    let name: String = user.get_name().to_string();
+    super::save_store(&store);
+    println!("Saved {}'s {} handle.", name, kind.to_lowercase());
+}
+
+/// Records what a member earns, or their part of a ratio, for expenses split by income. Earlier
+/// incomes are kept for expenses dated before `from`.
+fn income(reference: &str, member: &str, income: &str, from: Option<&str>) {
+    let income: f32 = match income.parse::<f32>() {
+        Ok(income) => income,
+        Err(_) => {
+            println!("Error: Could not parse income.");
+            return;
+        }
+    };
+    let from: Option<NaiveDate> = match from.map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d")) {
+        None => None,
+        Some(Ok(from)) => Some(from),
+        Some(Err(_)) => {
+            println!("Error: Could not parse date. Use YYYY-MM-DD.");
+            return;
+        }
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let user: &mut User = match group.find_member_mut(member) {
+        Ok(user) => user,
+        Err(_) => {
+            println!("Error: {} did not match a member.", member);
+            return;
+        }
+    };
+    if user.set_income(income, from).is_err() {
+        println!("Error: Income must be 0 or more.");
+        return;
+    }
+    let name: String = user.get_name().to_string();
+    super::save_store(&store);
+    match from {
+        None => println!("Saved {}'s income of {}.", name, income),
+        Some(from) => println!("Saved {}'s income of {} from {}.", name, income, from),
+    }
+}
+
+/// Lists the group's categories, or replaces them with `categories` if any are given.
+fn categories(group: &str, categories: &[String]) {
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(group)) {
+        Some(group) => group,
+        None => return,
+    };
+
+    if !categories.is_empty() {
+        let categories: Vec<&str> = categories.iter().map(|c| c.as_str()).collect();
+        match Taxonomy::new(&categories) {
+            Ok(taxonomy) => group.set_taxonomy(taxonomy),
+            Err(_) => {
+                println!("Error: Categories must be unique and written like Food:Groceries.");
+                return;
+            }
+        }
+    }
+    for category in group.get_taxonomy().get_categories() {
+        let depth: usize = category.matches(':').count();
+        let name: &str = category.rsplit(':').next().unwrap_or_default();
+        println!("{}{}", "  ".repeat(depth), name);
+    }
+    if !categories.is_empty() {
+        super::save_store(&store);
+    }
+}
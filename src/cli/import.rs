@@ -0,0 +1,86 @@
+use std::fs;
+
+use heesab::group::Group;
+use heesab::import::{self, Format, ImportReport};
+use heesab::store::Store;
+
+/// `heesab import <splitwise|tricount> <file.csv> <group> [--dry-run]` creates a group from
+/// another app's export. With `--dry-run` the report is printed and nothing is saved.
+pub fn run(args: &[String]) {
+    let dry_run: bool = args.iter().any(|a| a == "--dry-run");
+    let args: Vec<&String> = args.iter().filter(|a| *a != "--dry-run").collect();
+    if args.len() != 3 {
+        super::print_usage();
+        return;
+    }
+
+    let format: Format = match Format::new(args[0]) {
+        Ok(format) => format,
+        Err(e) => {
+            println!("Error: {}.", e);
+            return;
+        }
+    };
+    let csv: String = match fs::read_to_string(args[1]) {
+        Ok(csv) => csv,
+        Err(_) => {
+            println!("Error: Could not read {}.", args[1]);
+            return;
+        }
+    };
+    let (group, report): (Group, ImportReport) = match import::import(format, args[2].trim(), &csv)
+    {
+        Ok(imported) => imported,
+        Err(e) => {
+            println!("Error: {}.", e);
+            return;
+        }
+    };
+
+    print_report(&report);
+    if dry_run {
+        println!("\nDry run, nothing was saved.");
+        return;
+    }
+    let mut store: Store = super::load_store();
+    let name: String = group.get_name().to_string();
+    if store.add_group(group).is_err() {
+        println!("Error: A group named {} already exists.", name);
+        return;
+    }
+    super::save_store(&store);
+    println!("\nCreated {}.", name);
+}
+
+fn print_report(report: &ImportReport) {
+    println!("Members: {}", report.get_members().join(", "));
+    println!(
+        "Expenses: {}, payments: {}",
+        report.get_entries(),
+        report.get_settlements()
+    );
+    if !report.get_unmapped().is_empty() {
+        println!("\nCould not be imported:");
+        for line in report.get_unmapped() {
+            println!("  {}", line);
+        }
+    }
+    if !report.get_notes().is_empty() {
+        println!("\nImported with assumptions:");
+        for line in report.get_notes() {
+            println!("  {}", line);
+        }
+    }
+    if !report.is_balance_checked() {
+        println!(
+            "\nBalances could not be checked against the export, since it does not state them."
+        );
+    } else if report.get_mismatches().is_empty() {
+        println!("\nBalances match the export.");
+    } else {
+        println!("\nBalances that differ from the export:");
+        for line in report.get_mismatches() {
+            println!("  {}", line);
+        }
+    }
+}
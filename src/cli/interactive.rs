@@ -0,0 +1,572 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::process;
+
+use chrono::{Local, NaiveDate};
+
+use heesab::details::Filter;
+use heesab::group::Group;
+use heesab::ledger::LedgerEntry;
+use heesab::receipt::Receipt;
+use heesab::selection::{self, SelectionError};
+use heesab::session::{DraftExpense, Session};
+use heesab::split::Breakdown;
+use heesab::store::Store;
+use heesab::taxonomy::Taxonomy;
+use heesab::user::User;
+use heesab::user::UserError;
+
+pub fn run() {
+    if super::session_path().exists() {
+        println!("An unfinished bill was saved. Run heesab resume to continue it, or carry on to start over and replace it.");
+    }
+
+    let mut session: Session = match choose_group() {
+        Some(group) => Session::new(
+            Some(group.get_name()),
+            group.get_roster().to_vec(),
+            group.get_aliases().clone(),
+        ),
+        None => Session::new(None, enter_participants(), BTreeMap::new()),
+    }
+    .expect("Error: No participants. Exiting.");
+    super::save_session(&session);
+
+    enter_expenses(&mut session);
+    finish(&mut session);
+}
+
+/// Picks up a saved session at the step it was left on: entering expenses, or entering who
+/// paid once tax and tip are known.
+pub fn resume() {
+    let mut session: Session = match Session::load(&super::session_path()) {
+        Ok(session) => session,
+        Err(_) => {
+            println!("Nothing to resume. Run heesab to start a new bill.");
+            return;
+        }
+    };
+
+    let names: Vec<&str> = session.get_roster().iter().map(|u| u.get_name()).collect();
+    println!(
+        "Resuming a bill for {}{}.",
+        names.join(", "),
+        session
+            .get_group()
+            .map_or(String::new(), |g| format!(" in {}", g))
+    );
+    list_expenses(&session);
+
+    match session.get_tax_and_tip() {
+        Some((tax, tip)) => println!("Tax is ${} and tip is ${}.", tax, tip),
+        None => enter_expenses(&mut session),
+    }
+    finish(&mut session);
+}
+
+/// Reads one line from stdin. Exits if stdin is closed, since the last step was already saved.
+fn read_line() -> String {
+    let mut line: String = String::new();
+    let read: usize = io::stdin()
+        .read_line(&mut line)
+        .expect("Failed to read line");
+    if read == 0 {
+        if super::session_path().exists() {
+            println!("\nProgress saved. Run heesab resume to continue.");
+        }
+        process::exit(0);
+    }
+    line
+}
+
+fn enter_expenses(session: &mut Session) {
+    loop {
+        println!("\nEnter an expense in the following format: <expense name>:<amount>. Type receipt <file> to add every item on a text receipt, list, edit <number>, details <number>, remove <number> or preview [--category <category>] [--tag <tag>]... to review what you have entered, or done to move to next step.");
+        let expense_input: String = read_line();
+
+        let expense_input: &str = expense_input.trim();
+        let (command, argument) = expense_input
+            .split_once(' ')
+            .map_or((expense_input, ""), |(c, a)| (c, a.trim()));
+
+        match (command, argument.parse::<usize>()) {
+            ("done", _) if argument.is_empty() => {
+                if session.get_expenses().is_empty() {
+                    println!("Error: Enter at least one expense first.");
+                    continue;
+                }
+                return;
+            }
+            ("list", _) if argument.is_empty() => list_expenses(session),
+            ("preview", _) if argument.is_empty() => match session.preview() {
+                Ok(breakdown) => breakdown.print(),
+                Err(_) => println!("Error: Enter at least one expense first."),
+            },
+            ("preview", _) if argument.starts_with("--") => preview_matching(session, argument),
+            ("details", Ok(number)) => edit_details(session, number),
+            ("receipt", _) if !argument.is_empty() && parse_expense(expense_input).is_none() => {
+                add_receipt(session, argument)
+            }
+            ("edit", Ok(number)) => edit_expense(session, number),
+            ("remove", Ok(number)) => match session.remove_expense(number.wrapping_sub(1)) {
+                Ok(expense) => println!("Removed {}.", expense.get_name()),
+                Err(_) => println!("Error: {} is not an expense number.", number),
+            },
+            _ => add_expense(session, expense_input),
+        }
+        super::save_session(session);
+    }
+}
+
+fn add_expense(session: &mut Session, expense_input: &str) {
+    let (name, amount) = match parse_expense(expense_input) {
+        Some(expense_details) => expense_details,
+        None => {
+            println!("Error: invalid format. Please try again.");
+            return;
+        }
+    };
+
+    let participants: Vec<User> = choose_participants(session).into_iter().cloned().collect();
+    let participants: Vec<&User> = participants.iter().collect();
+    if session.add_expense(name, amount, &participants).is_err() {
+        println!("Error: Expense details are invalid. Please try again.");
+    }
+}
+
+/// Adds each item on a plain-text receipt as its own expense, asking who shared it.
+fn add_receipt(session: &mut Session, path: &str) {
+    let receipt: Receipt = match fs::read_to_string(path).map(|text| Receipt::parse(&text)) {
+        Ok(Ok(receipt)) => receipt,
+        Ok(Err(_)) => {
+            println!("Error: No items found in {}.", path);
+            return;
+        }
+        Err(_) => {
+            println!("Error: Could not read {}.", path);
+            return;
+        }
+    };
+    for warning in receipt.get_warnings() {
+        println!("Warning: {}", warning);
+    }
+
+    for item in receipt.get_items() {
+        println!(
+            "\n{} x{} ${:.2}",
+            item.get_name(),
+            item.get_quantity(),
+            item.get_price()
+        );
+        let participants: Vec<User> = choose_participants(session).into_iter().cloned().collect();
+        let participants: Vec<&User> = participants.iter().collect();
+        if session
+            .add_expense(item.get_name(), item.get_price(), &participants)
+            .is_err()
+        {
+            println!(
+                "Error: Could not add {}. Add it by hand instead.",
+                item.get_name()
+            );
+        }
+    }
+    println!(
+        "\nThe receipt shows ${:.2} tax and ${:.2} tip. Enter {:.2}:{:.2} when asked for tax and tip.",
+        receipt.get_tax(),
+        receipt.get_tip(),
+        receipt.get_tax(),
+        receipt.get_tip()
+    );
+}
+
+fn parse_expense(expense_input: &str) -> Option<(&str, f32)> {
+    let expense_details: Vec<&str> = expense_input.split(':').collect();
+    if expense_details.len() != 2 {
+        return None;
+    }
+    let amount: f32 = expense_details[1].trim().parse::<f32>().ok()?;
+    Some((expense_details[0].trim(), amount))
+}
+
+fn list_expenses(session: &Session) {
+    if session.get_expenses().is_empty() {
+        println!("No expenses entered yet.");
+    }
+    for (index, expense) in session.get_expenses().iter().enumerate() {
+        println!(
+            "{}. {} - ${} - {}",
+            index + 1,
+            expense.get_name(),
+            expense.get_amount(),
+            expense.get_participants().join(", ")
+        );
+    }
+}
+
+fn edit_expense(session: &mut Session, number: usize) {
+    let index: usize = number.wrapping_sub(1);
+    let expense: DraftExpense = match session.get_expenses().get(index) {
+        Some(expense) => expense.clone(),
+        None => {
+            println!("Error: {} is not an expense number.", number);
+            return;
+        }
+    };
+
+    loop {
+        println!(
+            "\nEditing {} - ${}. Enter a new <expense name>:<amount>, or leave blank to keep it.",
+            expense.get_name(),
+            expense.get_amount()
+        );
+        let expense_input: String = read_line();
+
+        let expense_input: &str = expense_input.trim();
+        if expense_input.is_empty() {
+            break;
+        }
+        let edited: bool = match parse_expense(expense_input) {
+            Some((name, amount)) => session.edit_name_and_amount(index, name, amount).is_ok(),
+            None => false,
+        };
+        if edited {
+            break;
+        }
+        println!("Error: invalid format. Please try again.");
+    }
+
+    println!(
+        "\nParticipants are {}. Enter edit to change them, or leave blank to keep them.",
+        expense.get_participants().join(", ")
+    );
+    let participants_input: String = read_line();
+
+    if participants_input.trim() == "edit" {
+        let participants: Vec<User> = choose_participants(session).into_iter().cloned().collect();
+        let participants: Vec<&User> = participants.iter().collect();
+        session
+            .edit_participants(index, &participants)
+            .expect("Error: Participants are not on the roster. Exiting.");
+    }
+    println!("Updated expense {}.", number);
+}
+
+/// Sets an expense's category, tags and notes, which are kept when the bill is recorded.
+fn edit_details(session: &mut Session, number: usize) {
+    let index: usize = number.wrapping_sub(1);
+    let expense: DraftExpense = match session.get_expenses().get(index) {
+        Some(expense) => expense.clone(),
+        None => {
+            println!("Error: {} is not an expense number.", number);
+            return;
+        }
+    };
+    let taxonomy: Taxonomy = super::session_taxonomy(session);
+
+    loop {
+        println!(
+            "\n{} has details: {}\nEnter new details in the following format, or leave blank to keep them: <category> #<tag>... notes: <notes>. Categories are {}.",
+            expense.get_name(),
+            match super::format_details(expense.get_details()) {
+                details if details.is_empty() => "none".to_string(),
+                details => details,
+            },
+            taxonomy.get_categories().join(", ")
+        );
+        let details_input: String = read_line();
+
+        let details_input: &str = details_input.trim();
+        if details_input.is_empty() {
+            return;
+        }
+        match super::parse_details(&taxonomy, details_input) {
+            Ok(details) => {
+                session
+                    .set_details(index, details)
+                    .expect("the expense exists");
+                println!("Updated the details of {}.", expense.get_name());
+                return;
+            }
+            Err(message) => println!("Error: {}. Please try again.", message),
+        }
+    }
+}
+
+/// Shows everyone's share of the expenses matching a filter, such as `--category food`.
+fn preview_matching(session: &Session, argument: &str) {
+    let args: Vec<String> = argument.split_whitespace().map(String::from).collect();
+    let filter: Filter = match super::parse_filter(&args) {
+        Ok((others, filter)) if others.is_empty() => filter,
+        Ok((others, _)) => {
+            println!("Error: Unknown option {}.", others[0]);
+            return;
+        }
+        Err(message) => {
+            println!("Error: {}.", message);
+            return;
+        }
+    };
+    match session.subtotals_matching(Local::now().date_naive(), &filter) {
+        Ok(subtotals) if subtotals.is_empty() => println!("No expenses match."),
+        Ok(subtotals) => {
+            for (name, subtotal) in subtotals {
+                println!("{}: ${:.2}", name, subtotal);
+            }
+        }
+        Err(_) => println!("Error: Enter at least one expense first."),
+    }
+}
+
+fn finish(session: &mut Session) {
+    loop {
+        let (tax, tip): (f32, f32) = match session.get_tax_and_tip() {
+            Some(tax_and_tip) => tax_and_tip,
+            None => {
+                enter_tax_and_tip(session);
+                continue;
+            }
+        };
+
+        println!(
+            "\nEnter who paid by name, initials or number in the following format, or leave blank to skip: <participant>=<amount>:<participant>=<amount>:..."
+        );
+        let payers_input: String = read_line();
+
+        let mut payers: Vec<(&User, f32)> = Vec::new();
+        for payment in payers_input.trim().split(':').filter(|p| !p.is_empty()) {
+            let maybe_payer = payment.split_once('=').map(|(reference, amount)| {
+                (
+                    selection::select_one(session.get_roster(), reference),
+                    amount.trim().parse::<f32>(),
+                )
+            });
+            match maybe_payer {
+                Some((Ok(user), Ok(amount))) if amount > 0_f32 => payers.push((user, amount)),
+                _ => {
+                    println!(
+                        "Error: {} is not a valid payment. Please try again.",
+                        payment
+                    );
+                    payers.clear();
+                    break;
+                }
+            }
+        }
+        if payers.is_empty() && !payers_input.trim().is_empty() {
+            continue;
+        }
+
+        match session.split(tax, tip, &payers) {
+            Ok(breakdown) => {
+                breakdown.print();
+                record(session, &breakdown);
+                let _ = fs::remove_file(super::session_path());
+                break;
+            }
+            Err(_) => {
+                println!("Error: payments do not add up to the total. Please try again.");
+            }
+        }
+    }
+}
+
+fn enter_tax_and_tip(session: &mut Session) {
+    loop {
+        println!("\nEnter tax and tip info in the following format: <tax>:<tip>");
+        let calc_input: String = read_line();
+
+        let calcs: Vec<&str> = calc_input.trim().split(':').collect();
+
+        if calcs.len() != 2 {
+            println!("Error: invalid format. Please try again.");
+            continue;
+        }
+
+        let (maybe_tax, maybe_tip) = (calcs[0].parse::<f32>(), calcs[1].parse::<f32>());
+        if maybe_tax.is_err() || maybe_tip.is_err() {
+            println!("Error: invalid format. Please try again.");
+            continue;
+        }
+
+        if session
+            .set_tax_and_tip(maybe_tax.unwrap(), maybe_tip.unwrap())
+            .is_err()
+        {
+            println!("Error: invalid tax or tip details. Please try again.");
+            continue;
+        }
+        super::save_session(session);
+        return;
+    }
+}
+
+fn choose_group() -> Option<Group> {
+    let store: Store = super::load_store();
+    if store.get_groups().is_empty() {
+        return None;
+    }
+
+    loop {
+        println!();
+        for (index, group) in store.get_groups().iter().enumerate() {
+            println!("{}. {}", index + 1, group.get_name());
+        }
+        println!("Pick a group from above by name or number, or leave blank to enter participants instead.");
+
+        let group_input: String = read_line();
+
+        if group_input.trim().is_empty() {
+            return None;
+        }
+        match store.find_group(group_input.trim()) {
+            Ok(group) => return Some(group.clone()),
+            Err(_) => println!("Error: did not match an existing group. Please try again."),
+        }
+    }
+}
+
+fn enter_participants() -> Vec<User> {
+    let mut roster: Vec<User> = Vec::new();
+    let mut valid_participants: bool = false;
+    while !valid_participants {
+        roster.clear();
+        println!(
+            "Enter all unique participant names in the following format: <participant one name>:<participant two name>:..."
+        );
+        let participants: String = read_line();
+
+        valid_participants = true;
+        for name in participants.trim().split(':') {
+            let maybe_user: Result<User, UserError> = User::new(name.trim());
+            match maybe_user {
+                Err(_) => {
+                    println!("Error: Name is invalid. Please try again.");
+                    valid_participants = false;
+                }
+                Ok(user)
+                    if roster
+                        .iter()
+                        .any(|u| u.get_name().eq_ignore_ascii_case(user.get_name())) =>
+                {
+                    println!("Error: Cannot use duplicate name. Please try again.");
+                    valid_participants = false;
+                }
+                Ok(user) => {
+                    roster.push(user);
+                }
+            }
+        }
+    }
+    roster
+}
+
+/// Keeps asking until the input selects at least one participant, so a typo never silently
+/// drops someone from an expense.
+fn choose_participants(session: &Session) -> Vec<&User> {
+    loop {
+        println!();
+        print_roster(session.get_roster());
+        for (alias, members) in session.get_aliases() {
+            println!("{} = {}", alias, members.join(", "));
+        }
+        println!(
+            "Enter participants from above by name, initials, prefix, number or alias in the following format: <participant one>:<participant two>:... You can also use all, or all except <participant>:..."
+        );
+        let expense_participants: String = read_line();
+
+        let maybe_selected: Result<Vec<&User>, SelectionError> = selection::select(
+            session.get_roster(),
+            session.get_aliases(),
+            expense_participants.trim(),
+        );
+        match maybe_selected {
+            Ok(selected) => return selected,
+            Err(e) => println!("Error: {}. Please try again.", e),
+        }
+    }
+}
+
+fn print_roster(roster: &[User]) {
+    for (index, user) in roster.iter().enumerate() {
+        println!("{}. {} ({})", index + 1, user, user.initials());
+    }
+}
+
+/// Records the split in its group's ledger. Participants entered by hand can be saved as a
+/// new group first so the split has somewhere to go.
+fn record(session: &Session, breakdown: &Breakdown) {
+    if breakdown.rows.iter().all(|r| r.balance.is_none()) {
+        return;
+    }
+
+    let mut store: Store = super::load_store();
+    let group_name: String = match session.get_group() {
+        Some(group_name) => group_name.to_string(),
+        None => match save_as_group(&mut store, session.get_roster()) {
+            Some(group_name) => group_name,
+            None => return,
+        },
+    };
+
+    loop {
+        println!(
+            "\nTo record this split in {}, enter it in the following format, or leave blank to skip: <name>:<YYYY-MM-DD>. The date defaults to today.",
+            group_name
+        );
+        let record_input: String = read_line();
+
+        let record_input: &str = record_input.trim();
+        if record_input.is_empty() {
+            return;
+        }
+
+        let (name, date) = match record_input.split_once(':') {
+            None => (record_input, Ok(Local::now().date_naive())),
+            Some((name, date)) => (name, NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")),
+        };
+
+        let maybe_entry =
+            date.map(|date| LedgerEntry::from_breakdown(name.trim(), date, breakdown));
+        match (maybe_entry, store.find_group_mut(&group_name)) {
+            (Ok(Ok(mut entry)), Ok(group)) => {
+                entry.set_details(session.merged_details());
+                group.get_ledger_mut().record_entry(entry);
+                super::save_store(&store);
+                println!("Recorded {} in {}.", name.trim(), group_name);
+                return;
+            }
+            _ => println!("Error: invalid format. Please try again."),
+        }
+    }
+}
+
+fn save_as_group(store: &mut Store, roster: &[User]) -> Option<String> {
+    loop {
+        println!("\nTo keep track of balances, enter a name to save these participants as a group, or leave blank to skip.");
+        let group_input: String = read_line();
+
+        let group_name: &str = group_input.trim();
+        if group_name.is_empty() {
+            return None;
+        }
+
+        let mut group: Group = match Group::new(group_name) {
+            Ok(group) => group,
+            Err(_) => continue,
+        };
+        if roster
+            .iter()
+            .any(|user| group.add_member(user.clone()).is_err())
+        {
+            println!("Error: Participants are not unique, so they cannot be saved as a group.");
+            return None;
+        }
+        if store.add_group(group).is_err() {
+            println!("Error: A group with that name already exists. Please try again.");
+            continue;
+        }
+        super::save_store(store);
+        return Some(group_name.to_string());
+    }
+}
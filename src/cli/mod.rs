@@ -0,0 +1,282 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+
+use heesab::details::{Details, Filter};
+use heesab::group::Group;
+use heesab::session::Session;
+use heesab::store::{Store, StoreError};
+use heesab::taxonomy::Taxonomy;
+
+pub mod balances;
+pub mod chat;
+pub mod export;
+pub mod group;
+pub mod import;
+pub mod interactive;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod reconcile;
+pub mod recurring;
+pub mod rent;
+pub mod report;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod settle;
+pub mod statement;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+/// Groups are stored at `$HEESAB_DATA` if set, otherwise at `~/.heesab/groups.json`.
+pub fn data_path() -> PathBuf {
+    if let Ok(path) = env::var("HEESAB_DATA") {
+        return PathBuf::from(path);
+    }
+    let home: String = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".heesab").join("groups.json")
+}
+
+/// The unfinished interactive session is autosaved next to the groups.
+pub fn session_path() -> PathBuf {
+    data_path().with_file_name("session.json")
+}
+
+pub fn save_session(session: &Session) {
+    save_session_at(session, &session_path());
+}
+
+pub fn save_session_at(session: &Session, path: &Path) {
+    if session.save(path).is_err() {
+        println!("Warning: Could not save progress. Carrying on without it.");
+    }
+}
+
+/// Before groups, the one ledger lived at `$HEESAB_LEDGER` if set, otherwise next to the
+/// groups, at `~/.heesab/ledger.json` by default.
+fn ledger_path() -> PathBuf {
+    if let Ok(path) = env::var("HEESAB_LEDGER") {
+        return PathBuf::from(path);
+    }
+    data_path().with_file_name("ledger.json")
+}
+
+/// The first time groups are loaded, a ledger saved before groups is imported as a group
+/// named Ledger. The old file is left in place.
+pub fn load_store() -> Store {
+    let maybe_store: Result<Store, StoreError> = Store::load(&data_path());
+    let mut store: Store = maybe_store.expect("Error: Could not read saved groups. Exiting.");
+    let ledger_path: PathBuf = ledger_path();
+    if !data_path().exists() && ledger_path.exists() {
+        match store.import_ledger("Ledger", &ledger_path) {
+            Ok(()) => {
+                save_store(&store);
+                println!(
+                    "Imported the ledger at {} as the group Ledger.",
+                    ledger_path.display()
+                );
+            }
+            Err(e) => println!(
+                "Warning: Could not import the ledger at {}, {}. Carrying on without it.",
+                ledger_path.display(),
+                e
+            ),
+        }
+    }
+    store
+}
+
+pub fn save_store(store: &Store) {
+    store
+        .save(&data_path())
+        .expect("Error: Could not write saved groups. Exiting.");
+}
+
+/// Finds the group named by `reference`, or the only group if there is exactly one and no
+/// reference was given.
+pub fn pick_group<'a>(store: &'a mut Store, reference: Option<&str>) -> Option<&'a mut Group> {
+    let reference: String = match reference {
+        Some(reference) => reference.to_string(),
+        None if store.get_groups().len() == 1 => "1".to_string(),
+        None => {
+            println!("Error: Please name a group. See heesab group list.");
+            return None;
+        }
+    };
+    match store.find_group_mut(&reference) {
+        Ok(group) => Some(group),
+        Err(_) => {
+            println!("Error: {} did not match an existing group.", reference);
+            None
+        }
+    }
+}
+
+/// Reads `--since <date>`, `--until <date>`, `--category <category>` and any number of
+/// `--tag <tag>`, returning the other arguments alongside the filter.
+pub fn parse_filter(args: &[String]) -> Result<(Vec<&str>, Filter), String> {
+    let mut others: Vec<&str> = Vec::new();
+    let mut filter: Filter = Filter::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            others.push(arg);
+            continue;
+        }
+        let value: &String = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?;
+        let date = || {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| "Could not parse date. Use YYYY-MM-DD".to_string())
+        };
+        match arg.as_str() {
+            "--since" => filter.set_since(date()?),
+            "--until" => filter.set_until(date()?),
+            "--category" => filter.set_category(value),
+            "--tag" => filter
+                .add_tag(value)
+                .map_err(|_| format!("{} is not a valid tag", value))?,
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+    Ok((others, filter))
+}
+
+/// Reads expense details written as `<category> #<tag>... notes: <notes>`, such as
+/// `groceries #weekly notes: from the market`. Every part is optional.
+pub fn parse_details(taxonomy: &Taxonomy, input: &str) -> Result<Details, String> {
+    let (words, notes): (&str, &str) = input.split_once("notes:").unwrap_or((input, ""));
+    let mut details: Details = Details::new();
+    let (tags, category): (Vec<&str>, Vec<&str>) =
+        words.split_whitespace().partition(|w| w.starts_with('#'));
+    if !category.is_empty() {
+        let category: String = category.join(" ");
+        details
+            .set_category(taxonomy, &category)
+            .map_err(|_| format!("{} is not a category", category))?;
+    }
+    for tag in tags {
+        details
+            .add_tag(tag)
+            .map_err(|_| format!("{} is not a valid tag", tag))?;
+    }
+    details
+        .set_notes(notes)
+        .map_err(|_| "Notes can be at most 1000 characters".to_string())?;
+    Ok(details)
+}
+
+/// Writes details the way `parse_details` reads them.
+pub fn format_details(details: &Details) -> String {
+    let mut parts: Vec<String> = details
+        .get_category()
+        .map(String::from)
+        .into_iter()
+        .collect();
+    parts.extend(details.get_tags().iter().map(|t| format!("#{}", t)));
+    if let Some(notes) = details.get_notes() {
+        parts.push(format!("notes: {}", notes));
+    }
+    parts.join(" ")
+}
+
+/// The categories of the group a session came from, or the default ones.
+pub fn session_taxonomy(session: &Session) -> Taxonomy {
+    let store: Store = load_store();
+    session
+        .get_group()
+        .and_then(|group| store.find_group(group).ok())
+        .map(|group| group.get_taxonomy().clone())
+        .unwrap_or_default()
+}
+
+pub fn print_usage() {
+    println!("Usage:");
+    println!(
+        "  heesab                                                   split a bill interactively"
+    );
+    println!(
+        "  heesab resume                                            resume an unfinished split"
+    );
+    #[cfg(feature = "tui")]
+    println!("  heesab tui [<group>]                                     split a bill full screen");
+    println!("  heesab group list                                        list groups");
+    println!("  heesab group create <group> [<member>...]                create a group");
+    println!("  heesab group show <group>                                show a group's roster");
+    println!("  heesab group add <group> <member> [<email>]              add a member to a group");
+    println!(
+        "  heesab group remove <group> <member>                     remove a member from a group"
+    );
+    println!("  heesab group alias <group> <alias> <members>             name a set of members");
+    println!("  heesab group handle <group> <member> <upi|iban|paypal|venmo> <handle>");
+    println!(
+        "                                                           save how a member gets paid"
+    );
+    println!("  heesab group income <group> <member> <amount> [YYYY-MM-DD]");
+    println!(
+        "                                                           set income for splits by income"
+    );
+    println!("  heesab import <splitwise|tricount> <file.csv> <group> [--dry-run]");
+    println!(
+        "                                                           create a group from an export"
+    );
+    println!("  heesab export <ledger|hledger|beancount> <group> <member> [<accounts.json>]");
+    println!(
+        "                                                           export for plain-text accounting"
+    );
+    println!("  heesab statement <group> <member> <file.ofx|file.qif|file.xml>");
+    println!(
+        "                                                           add expenses from a statement"
+    );
+    println!(
+        "  heesab group categories <group> [<category>...]          list or set expense categories"
+    );
+    println!("  heesab balances [<group>] [--since <date>] [--until <date>] [--category <category>] [--tag <tag>]...");
+    println!(
+        "                                                           show outstanding balances"
+    );
+    println!(
+        "  heesab report [<group>] [<kind>] [--csv|--json] [--since <date>] [--until <date>] ..."
+    );
+    println!("                                                           show spending reports");
+    #[cfg(feature = "qr")]
+    println!("  heesab qr <group> <from> <to> [<kind>] [<file.svg|file.png>]");
+    #[cfg(feature = "qr")]
+    println!("                                                           show a QR code to repay");
+    println!("  heesab settle [<group>] <from> <to> <amount> [YYYY-MM-DD]  record a repayment");
+    println!("  heesab recurring list [<group>]                          list recurring expenses");
+    println!(
+        "  heesab recurring add <group> <name> <amount> <monthly[:<day>]|weekly[:<weeks>]|yearly>"
+    );
+    println!("      <payer> <participants> [--start <date>] [--end <date>] [--category <category>] [--tag <tag>]...");
+    println!("                                                           add a recurring expense");
+    println!("  heesab recurring change <group> <name> <amount> <YYYY-MM-DD>");
+    println!(
+        "                                                           change the amount from a date"
+    );
+    println!("  heesab recurring end <group> <name> <YYYY-MM-DD>         stop a recurring expense");
+    println!(
+        "  heesab recurring remove <group> <name>                   remove a recurring expense"
+    );
+    println!("  heesab rent <group>                                      show the rent plan");
+    println!(
+        "  heesab rent <group> plan <plan.json>                     set how rent is split by room"
+    );
+    println!("  heesab rent <group> assign <room> <members|nobody>       move members into a room");
+    println!("  heesab rent <group> split <amount>                       explain who pays what");
+    println!(
+        "  heesab run-recurring [<group>] [YYYY-MM-DD]              record recurring expenses due"
+    );
+    println!("  heesab reconcile <group> <member> <file.ofx|file.qif|file.xml>");
+    println!(
+        "                                                           check repayments against a statement"
+    );
+    println!(
+        "  heesab chat <group> <member>                             try chat commands as a member"
+    );
+    #[cfg(feature = "serve")]
+    println!(
+        "  heesab serve [<address>]                                 serve the web UI and REST API"
+    );
+}
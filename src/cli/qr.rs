@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use qrcode::render::{svg, unicode};
+use qrcode::{Color, QrCode};
+
+use heesab::group::Group;
+use heesab::payment::PaymentHandle;
+use heesab::settlement::Settlement;
+use heesab::store::Store;
+
+/// Pixels per module in saved PNGs.
+const PNG_SCALE: usize = 8;
+/// Light modules around the code, as scanners expect.
+const QUIET_ZONE: usize = 4;
+
+/// `heesab qr <group> <from> <to> [<kind>] [<file.svg|file.png>]` shows a QR code for the
+/// suggested repayment from `from` to `to`, using the payee's first handle unless `kind`
+/// picks another.
+pub fn run(args: &[String]) {
+    if args.len() < 3 || args.len() > 5 {
+        super::print_usage();
+        return;
+    }
+    let mut kind: Option<&str> = None;
+    let mut file: Option<&Path> = None;
+    for arg in &args[3..] {
+        if arg.ends_with(".svg") || arg.ends_with(".png") {
+            file = Some(Path::new(arg));
+        } else {
+            kind = Some(arg);
+        }
+    }
+
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, Some(&args[0])) {
+        Some(group) => group,
+        None => return,
+    };
+    let (from, to) = match (group.find_member(&args[1]), group.find_member(&args[2])) {
+        (Ok(from), Ok(to)) => (from.get_name(), to.get_name()),
+        _ => {
+            println!("Error: {} or {} did not match a member.", args[1], args[2]);
+            return;
+        }
+    };
+    let suggestions: Vec<Settlement> =
+        Settlement::suggest(&group.get_ledger().balances(), NaiveDate::MIN);
+    let settlement: &Settlement = match suggestions
+        .iter()
+        .find(|s| s.get_from() == from && s.get_to() == to)
+    {
+        Some(settlement) => settlement,
+        None => {
+            println!("{} does not need to pay {}. See heesab balances.", from, to);
+            return;
+        }
+    };
+
+    let requests: Vec<(&PaymentHandle, String)> = super::balances::requests(group, settlement);
+    let request: &String = match requests
+        .iter()
+        .find(|(h, _)| kind.is_none_or(|k| h.get_kind() == k.to_lowercase()))
+    {
+        Some((_, request)) => request,
+        None => {
+            println!(
+                "Error: {} has no matching payment handle. Add one with heesab group handle.",
+                to
+            );
+            return;
+        }
+    };
+
+    let code: QrCode = match QrCode::new(request.as_bytes()) {
+        Ok(code) => code,
+        Err(_) => {
+            println!("Error: The payment request is too long for a QR code.");
+            return;
+        }
+    };
+    let path: &Path = match file {
+        Some(path) => path,
+        None => {
+            println!("{} pays {} ${:.2}", from, to, settlement.get_amount());
+            println!("{}", to_terminal(&code));
+            return;
+        }
+    };
+    let saved: std::io::Result<()> = if path.extension().is_some_and(|e| e == "svg") {
+        fs::write(path, to_svg(&code))
+    } else {
+        fs::write(path, to_png(&code))
+    };
+    match saved {
+        Ok(()) => println!("Saved {}.", path.display()),
+        Err(e) => println!("Error: Could not save the QR code: {}", e),
+    }
+}
+
+/// Half-height block characters, two modules per line. Colours are swapped so the code
+/// reads correctly as light text on the usual dark terminal.
+fn to_terminal(code: &QrCode) -> String {
+    code.render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build()
+}
+
+fn to_svg(code: &QrCode) -> String {
+    code.render::<svg::Color>().min_dimensions(256, 256).build()
+}
+
+/// An 8-bit greyscale PNG.
+fn to_png(code: &QrCode) -> Vec<u8> {
+    let width: usize = code.width();
+    let colors: Vec<Color> = code.to_colors();
+    let size: usize = (width + 2 * QUIET_ZONE) * PNG_SCALE;
+
+    let mut pixels: Vec<u8> = vec![255; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color == Color::Light {
+            continue;
+        }
+        let x: usize = (index % width + QUIET_ZONE) * PNG_SCALE;
+        let y: usize = (index / width + QUIET_ZONE) * PNG_SCALE;
+        for row in y..y + PNG_SCALE {
+            pixels[row * size + x..row * size + x + PNG_SCALE].fill(0);
+        }
+    }
+
+    let mut png: Vec<u8> = Vec::new();
+    let mut encoder: png::Encoder<&mut Vec<u8>> =
+        png::Encoder::new(&mut png, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .expect("Error: Could not encode the QR code. Exiting.");
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renderings() {
+        let code: QrCode = QrCode::new(b"https://paypal.me/alice/16.50").unwrap();
+
+        let terminal: String = to_terminal(&code);
+        assert_eq!(
+            terminal.lines().count(),
+            (code.width() + 2 * QUIET_ZONE).div_ceil(2)
+        );
+        assert!(to_svg(&code).starts_with("<?xml"));
+
+        let png: Vec<u8> = to_png(&code);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
@@ -0,0 +1,109 @@
+use heesab::group::Group;
+use heesab::reconcile::{self, Reconciliation};
+use heesab::settlement::Settlement;
+use heesab::statement::{self, Transaction};
+use heesab::store::Store;
+
+/// `heesab reconcile <group> <member> <file>` checks `member`'s bank statement for the
+/// group's repayments, marks the ones it finds as settled and reports what it could not match.
+pub fn run(args: &[String]) {
+    if args.len() != 3 {
+        super::print_usage();
+        return;
+    }
+
+    let transactions: Vec<Transaction> =
+        match super::statement::read(&args[2], statement::parse_all) {
+            Some(transactions) => transactions,
+            None => return,
+        };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(&args[0])) {
+        Some(group) => group,
+        None => return,
+    };
+    let reconciliation: Reconciliation = match reconcile::reconcile(group, &args[1], &transactions)
+    {
+        Ok(reconciliation) => reconciliation,
+        Err(_) => {
+            println!(
+                "Error: {} is not a member of {}.",
+                args[1],
+                group.get_name()
+            );
+            return;
+        }
+    };
+    super::save_store(&store);
+
+    print_matches(
+        "Recorded repayments on the statement:",
+        reconciliation.get_confirmed(),
+    );
+    print_matches("Now settled:", reconciliation.get_settled());
+    if !reconciliation.get_unmatched_settlements().is_empty() {
+        println!("\nRecorded repayments missing from the statement:");
+        for settlement in reconciliation.get_unmatched_settlements() {
+            println!("  {}", describe(settlement));
+        }
+    }
+    if !reconciliation.get_unmatched_transactions().is_empty() {
+        println!("\nTransfers that match no repayment:");
+        for transaction in reconciliation.get_unmatched_transactions() {
+            println!("  {}", describe_transaction(transaction));
+        }
+    }
+    if reconciliation.get_open().is_empty() {
+        println!("\nNothing outstanding.");
+    } else {
+        println!("\nStill outstanding:");
+        for settlement in reconciliation.get_open() {
+            println!(
+                "  {} pays {} ${:.2}",
+                settlement.get_from(),
+                settlement.get_to(),
+                settlement.get_amount()
+            );
+        }
+    }
+}
+
+fn print_matches(heading: &str, matches: &[(Transaction, Settlement)]) {
+    if matches.is_empty() {
+        return;
+    }
+    println!("\n{}", heading);
+    for (transaction, settlement) in matches {
+        println!(
+            "  {}, matched {}",
+            describe(settlement),
+            describe_transaction(transaction)
+        );
+    }
+}
+
+fn describe(settlement: &Settlement) -> String {
+    format!(
+        "{} paid {} ${:.2} on {}",
+        settlement.get_from(),
+        settlement.get_to(),
+        settlement.get_amount(),
+        settlement.get_date()
+    )
+}
+
+fn describe_transaction(transaction: &Transaction) -> String {
+    let direction: &str = if transaction.get_amount() < 0_f32 {
+        "from"
+    } else {
+        "to"
+    };
+    format!(
+        "${:.2} {} {} on {}",
+        transaction.get_amount().abs(),
+        direction,
+        transaction.get_merchant(),
+        transaction.get_date()
+    )
+}
@@ -0,0 +1,369 @@
+use chrono::{Local, NaiveDate};
+
+use heesab::details::Details;
+use heesab::group::Group;
+use heesab::ledger::LedgerEntry;
+use heesab::recurring::{RecurringExpense, Schedule};
+use heesab::store::Store;
+use heesab::user::User;
+
+pub fn run(args: &[String]) {
+    match args.first().map(|a| a.as_str()) {
+        Some("list") if args.len() <= 2 => list(args.get(1).map(|a| a.as_str())),
+        Some("add") if args.len() >= 7 => add(&args[1], &args[2..]),
+        Some("change") if args.len() == 5 => change(&args[1], &args[2], &args[3], &args[4]),
+        Some("end") if args.len() == 4 => end(&args[1], &args[2], &args[3]),
+        Some("remove") if args.len() == 3 => remove(&args[1], &args[2]),
+        _ => super::print_usage(),
+    }
+}
+
+/// `heesab run-recurring [<group>] [YYYY-MM-DD]` records the recurring expenses due on or
+/// before the date, today by default, in one group or in every group.
+pub fn run_due(args: &[String]) {
+    let (group_reference, date): (Option<&str>, Option<&str>) = match args {
+        [] => (None, None),
+        [arg] if parse_date(arg).is_some() => (None, Some(arg)),
+        [group] => (Some(group), None),
+        [group, date] => (Some(group), Some(date)),
+        _ => {
+            super::print_usage();
+            return;
+        }
+    };
+    let until: NaiveDate = match date {
+        None => Local::now().date_naive(),
+        Some(date) => match parse_date(date) {
+            Some(date) => date,
+            None => {
+                println!("Error: Could not parse date. Use YYYY-MM-DD.");
+                return;
+            }
+        },
+    };
+
+    let mut store: Store = super::load_store();
+    let references: Vec<String> = match group_reference {
+        Some(reference) => vec![reference.to_string()],
+        None => (1..=store.get_groups().len())
+            .map(|i| i.to_string())
+            .collect(),
+    };
+    let mut recorded: usize = 0;
+    for reference in &references {
+        let group: &mut Group = match super::pick_group(&mut store, Some(reference)) {
+            Some(group) => group,
+            None => return,
+        };
+        let entries: Vec<LedgerEntry> = group.run_recurring(until);
+        for entry in &entries {
+            println!(
+                "Recorded {} on {} in {}: {} paid ${:.2}",
+                entry.get_name(),
+                entry.get_date(),
+                group.get_name(),
+                entry
+                    .get_paid()
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                entry.get_paid().values().sum::<f32>()
+            );
+        }
+        recorded += entries.len();
+    }
+    // Saved even when nothing was due, since each expense remembers how far it has run.
+    super::save_store(&store);
+    if recorded == 0 {
+        println!("Nothing was due through {}.", until);
+    }
+}
+
+fn list(group_reference: Option<&str>) {
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, group_reference) {
+        Some(group) => group,
+        None => return,
+    };
+    if group.get_recurring().is_empty() {
+        println!("No recurring expenses in {} yet.", group.get_name());
+    }
+    for (index, recurring) in group.get_recurring().iter().enumerate() {
+        let weights: Vec<String> = recurring
+            .get_weights()
+            .iter()
+            .map(|(name, weight)| format!("{} x{}", name, weight))
+            .collect();
+        println!(
+            "{}. {} - ${:.2} {} from {}{}, paid by {}, split {}",
+            index + 1,
+            recurring.get_name(),
+            recurring.get_amount(),
+            recurring.get_schedule(),
+            recurring.get_start(),
+            recurring
+                .get_end()
+                .map(|end| format!(" until {}", end))
+                .unwrap_or_default(),
+            recurring.get_payer(),
+            weights.join(", ")
+        );
+        for (from, amount) in recurring.get_changes() {
+            println!("   ${:.2} from {}", amount, from);
+        }
+        if let Some(through) = recurring.get_through() {
+            println!("   recorded through {}", through);
+        }
+    }
+}
+
+/// `heesab recurring add <group> <name> <amount> <schedule> <payer> <participants>
+/// [--start <date>] [--end <date>] [--category <category>] [--tag <tag>]...`. Participants
+/// are a selection such as `all`, weighted like `alice=2:bob=1`, or `rent`.
+fn add(group_reference: &str, args: &[String]) {
+    let (name, amount, schedule, payer, participants) =
+        (&args[0], &args[1], &args[2], &args[3], &args[4]);
+    let mut start: NaiveDate = Local::now().date_naive();
+    let mut end: Option<NaiveDate> = None;
+    let mut category: Option<&str> = None;
+    let mut tags: Vec<&str> = Vec::new();
+    let mut options = args[5..].iter();
+    while let Some(option) = options.next() {
+        let value: &str = match options.next() {
+            Some(value) => value,
+            None => {
+                println!("Error: {} needs a value.", option);
+                return;
+            }
+        };
+        match (option.as_str(), parse_date(value)) {
+            ("--start", Some(date)) => start = date,
+            ("--end", Some(date)) => end = Some(date),
+            ("--start", None) | ("--end", None) => {
+                println!("Error: Could not parse date. Use YYYY-MM-DD.");
+                return;
+            }
+            ("--category", _) => category = Some(value),
+            ("--tag", _) => tags.push(value),
+            _ => {
+                println!("Error: Unknown option {}.", option);
+                return;
+            }
+        }
+    }
+
+    let amount: f32 = match amount.parse::<f32>() {
+        Ok(amount) => amount,
+        Err(_) => {
+            println!("Error: Could not parse amount.");
+            return;
+        }
+    };
+    let schedule: Schedule = match Schedule::new(schedule, start) {
+        Ok(schedule) => schedule,
+        Err(_) => {
+            println!("Error: Use a schedule of monthly[:<day>], weekly[:<weeks>] or yearly.");
+            return;
+        }
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let payer: User = match group.find_member(payer) {
+        Ok(payer) => payer.clone(),
+        Err(_) => {
+            println!("Error: {} is not a member of {}.", payer, group.get_name());
+            return;
+        }
+    };
+    let weighted: Vec<(User, f32)> = match weigh(group, participants) {
+        Ok(weighted) => weighted,
+        Err(message) => {
+            println!("Error: {}.", message);
+            return;
+        }
+    };
+    let weighted: Vec<(&User, f32)> = weighted.iter().map(|(u, w)| (u, *w)).collect();
+
+    let mut details: Details = Details::new();
+    if let Some(category) = category {
+        if details
+            .set_category(group.get_taxonomy(), category)
+            .is_err()
+        {
+            println!(
+                "Error: {} is not a category. See heesab group categories {}.",
+                category,
+                group.get_name()
+            );
+            return;
+        }
+    }
+    for tag in tags {
+        if details.add_tag(tag).is_err() {
+            println!("Error: {} is not a valid tag.", tag);
+            return;
+        }
+    }
+
+    let mut recurring: RecurringExpense =
+        match RecurringExpense::new(name, amount, schedule, start, &payer, &weighted) {
+            Ok(recurring) => recurring,
+            Err(_) => {
+                println!("Error: Recurring expense details are invalid.");
+                return;
+            }
+        };
+    if recurring.set_end(end).is_err() {
+        println!("Error: The end date is before the start date.");
+        return;
+    }
+    recurring
+        .set_details(details)
+        .expect("details without a time are accepted");
+    let description: String = format!("{} {} from {}", name, schedule, start);
+    if group.add_recurring(recurring).is_err() {
+        println!("Error: A recurring expense named {} already exists.", name);
+        return;
+    }
+    super::save_store(&store);
+    println!(
+        "Added {}. Record what is due with heesab run-recurring.",
+        description
+    );
+}
+
+fn change(group_reference: &str, reference: &str, amount: &str, from: &str) {
+    let (amount, from): (f32, NaiveDate) = match (amount.parse::<f32>(), parse_date(from)) {
+        (Ok(amount), Some(from)) => (amount, from),
+        _ => {
+            println!("Error: Could not parse amount or date. Use YYYY-MM-DD.");
+            return;
+        }
+    };
+    edit(group_reference, reference, |recurring| {
+        recurring
+            .change_amount(from, amount)
+            .map(|_| {
+                format!(
+                    "{} costs ${:.2} from {}.",
+                    recurring.get_name(),
+                    amount,
+                    from
+                )
+            })
+            .map_err(|_| "Amount cannot be negative".to_string())
+    });
+}
+
+fn end(group_reference: &str, reference: &str, date: &str) {
+    let date: NaiveDate = match parse_date(date) {
+        Some(date) => date,
+        None => {
+            println!("Error: Could not parse date. Use YYYY-MM-DD.");
+            return;
+        }
+    };
+    edit(group_reference, reference, |recurring| {
+        recurring
+            .set_end(Some(date))
+            .map(|_| format!("{} ends on {}.", recurring.get_name(), date))
+            .map_err(|_| "The end date is before the start date".to_string())
+    });
+}
+
+fn remove(group_reference: &str, reference: &str) {
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    match group.remove_recurring(reference) {
+        Ok(recurring) => {
+            super::save_store(&store);
+            println!(
+                "Removed {}. Expenses already recorded are kept.",
+                recurring.get_name()
+            );
+        }
+        Err(_) => println!("Error: {} did not match a recurring expense.", reference),
+    }
+}
+
+fn edit(
+    group_reference: &str,
+    reference: &str,
+    apply: impl FnOnce(&mut RecurringExpense) -> Result<String, String>,
+) {
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let recurring: &mut RecurringExpense = match group.find_recurring_mut(reference) {
+        Ok(recurring) => recurring,
+        Err(_) => {
+            println!("Error: {} did not match a recurring expense.", reference);
+            return;
+        }
+    };
+    match apply(recurring) {
+        Ok(message) => {
+            super::save_store(&store);
+            println!("{}", message);
+        }
+        Err(message) => println!("Error: {}.", message),
+    }
+}
+
+/// Reads a selection such as `all except bob`, where everyone weighs the same, references
+/// with weights such as `alice=2:bob=1`, or `rent` for the weights of the group's rent plan.
+/// Those are copied, so later changes to the plan do not change the expense.
+fn weigh(group: &Group, participants: &str) -> Result<Vec<(User, f32)>, String> {
+    if participants.trim().eq_ignore_ascii_case("rent") {
+        let plan = group.get_rent_plan().ok_or_else(|| {
+            format!(
+                "{} has no rent plan yet, see heesab rent {} plan <plan.json>",
+                group.get_name(),
+                group.get_name()
+            )
+        })?;
+        let mut weighted: Vec<(User, f32)> = Vec::new();
+        for (name, weight) in plan.weights() {
+            let user: &User = group
+                .find_member(&name)
+                .map_err(|_| format!("{} is no longer a member", name))?;
+            weighted.push((user.clone(), weight));
+        }
+        return Ok(weighted);
+    }
+
+    if !participants.contains('=') {
+        let selected: Vec<&User> = group.select(participants).map_err(|e| e.to_string())?;
+        return Ok(selected.into_iter().map(|u| (u.clone(), 1_f32)).collect());
+    }
+
+    let mut weighted: Vec<(User, f32)> = Vec::new();
+    for part in participants.split([':', ',']) {
+        let (reference, weight): (&str, &str) = part.split_once('=').unwrap_or((part, "1"));
+        let weight: f32 = weight
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| format!("{} is not a valid weight", weight))?;
+        for user in group.select(reference).map_err(|e| e.to_string())? {
+            if weighted.iter().any(|(u, _)| u == user) {
+                return Err(format!("{} is listed twice", user));
+            }
+            weighted.push((user.clone(), weight));
+        }
+    }
+    Ok(weighted)
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::Deserialize;
+
+use heesab::group::Group;
+use heesab::rent::{RentPlan, RentShare, Room};
+use heesab::store::Store;
+use heesab::user::User;
+
+/// A rent plan as written by hand, such as:
+///
+/// ```json
+/// {"common": 0.25, "attributes": {"area": 1, "bathroom": 10},
+///  "rooms": [{"name": "Big", "area": 20, "bathroom": 1, "occupants": ["alice"]}]}
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlanFile {
+    common: f32,
+    attributes: BTreeMap<String, f32>,
+    rooms: Vec<RoomFile>,
+}
+
+#[derive(Deserialize)]
+struct RoomFile {
+    name: String,
+    #[serde(default)]
+    occupants: Vec<String>,
+    #[serde(flatten)]
+    attributes: BTreeMap<String, f32>,
+}
+
+/// `heesab rent <group> [plan <plan.json> | assign <room> <members> | split <amount>]`
+/// shows, sets or applies the group's rent plan.
+pub fn run(args: &[String]) {
+    match args.get(1).map(|a| a.as_str()) {
+        None if args.len() == 1 => show(&args[0]),
+        Some("plan") if args.len() == 3 => plan(&args[0], &args[2]),
+        Some("assign") if args.len() == 4 => assign(&args[0], &args[2], &args[3]),
+        Some("split") if args.len() == 3 => split(&args[0], &args[2]),
+        _ => super::print_usage(),
+    }
+}
+
+fn show(group_reference: &str) {
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let plan: &RentPlan = match group.get_rent_plan() {
+        Some(plan) => plan,
+        None => {
+            println!(
+                "No rent plan for {} yet. Set one with heesab rent {} plan <plan.json>",
+                group.get_name(),
+                group.get_name()
+            );
+            return;
+        }
+    };
+    println!(
+        "{}% of the rent is for common areas, the rest is split by room points.",
+        plan.get_common() * 100_f32
+    );
+    for room in plan.get_rooms() {
+        let occupants: String = match room.get_occupants() {
+            [] => "empty".to_string(),
+            occupants => occupants.join(", "),
+        };
+        println!(
+            "{} ({}): {}",
+            room.get_name(),
+            points(plan, room),
+            occupants
+        );
+    }
+}
+
+fn plan(group_reference: &str, path: &str) {
+    let file: PlanFile = match fs::read_to_string(path).map(|json| serde_json::from_str(&json)) {
+        Ok(Ok(file)) => file,
+        Ok(Err(_)) => {
+            println!(
+                "Error: Could not parse {}. It needs common, attributes and rooms.",
+                path
+            );
+            return;
+        }
+        Err(_) => {
+            println!("Error: Could not read {}.", path);
+            return;
+        }
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let attributes: Vec<(&str, f32)> = file
+        .attributes
+        .iter()
+        .map(|(name, weight)| (name.as_str(), *weight))
+        .collect();
+    let mut plan: RentPlan = match RentPlan::new(file.common, &attributes) {
+        Ok(plan) => plan,
+        Err(_) => {
+            println!("Error: common must be between 0 and 1, and weights 0 or more.");
+            return;
+        }
+    };
+    for room in &file.rooms {
+        let occupants: Vec<&User> = match members(group, &room.occupants) {
+            Some(occupants) => occupants,
+            None => return,
+        };
+        let values: Vec<(&str, f32)> = room
+            .attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+        if plan.add_room(&room.name, &values, &occupants).is_err() {
+            println!(
+                "Error: {} must have a unique name, values of 0 or more for the plan's attributes, and occupants without another room.",
+                room.name
+            );
+            return;
+        }
+    }
+
+    let following: Vec<String> = following_plan(group);
+    if group.set_rent_plan(plan).is_err() {
+        println!(
+            "Error: Every occupant must be a member of {}.",
+            group.get_name()
+        );
+        return;
+    }
+    let group_name: String = group.get_name().to_string();
+    let changed: bool = following_plan(group) != following;
+    super::save_store(&store);
+    println!("Saved the rent plan for {}.", group_reference);
+    if changed {
+        warn_following(&group_name, &following);
+    }
+}
+
+fn assign(group_reference: &str, room: &str, occupants: &str) {
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let mut plan: RentPlan = match group.get_rent_plan() {
+        Some(plan) => plan.clone(),
+        None => {
+            println!("Error: {} has no rent plan yet.", group.get_name());
+            return;
+        }
+    };
+    let selected: Vec<&User> = match occupants.trim().eq_ignore_ascii_case("nobody") {
+        true => Vec::new(),
+        false => match group.select(occupants) {
+            Ok(selected) => selected,
+            Err(e) => {
+                println!("Error: {}.", e);
+                return;
+            }
+        },
+    };
+    if plan.assign(room, &selected).is_err() {
+        println!(
+            "Error: {} is not a room, or someone selected lives in another room.",
+            room
+        );
+        return;
+    }
+
+    let following: Vec<String> = following_plan(group);
+    if group.set_rent_plan(plan).is_err() {
+        println!(
+            "Error: Every occupant must be a member of {}.",
+            group.get_name()
+        );
+        return;
+    }
+    let group_name: String = group.get_name().to_string();
+    let changed: bool = following_plan(group) != following;
+    super::save_store(&store);
+    println!("Assigned {}.", room);
+    if changed {
+        warn_following(&group_name, &following);
+    }
+}
+
+fn split(group_reference: &str, amount: &str) {
+    let amount: f32 = match amount.parse::<f32>() {
+        Ok(amount) => amount,
+        Err(_) => {
+            println!("Error: Could not parse amount.");
+            return;
+        }
+    };
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, Some(group_reference)) {
+        Some(group) => group,
+        None => return,
+    };
+    let plan: &RentPlan = match group.get_rent_plan() {
+        Some(plan) => plan,
+        None => {
+            println!("Error: {} has no rent plan yet.", group.get_name());
+            return;
+        }
+    };
+    let shares: Vec<RentShare> = match plan.split(amount) {
+        Ok(shares) => shares,
+        Err(_) => {
+            println!("Error: Nobody lives in any room, or the amount is negative.");
+            return;
+        }
+    };
+
+    println!(
+        "Rent of ${:.2}: {}% for common areas split {} ways, the rest by room points.",
+        amount,
+        plan.get_common() * 100_f32,
+        shares.len()
+    );
+    for room in plan.get_rooms() {
+        if room.get_occupants().is_empty() {
+            continue;
+        }
+        println!("{} ({})", room.get_name(), points(plan, room));
+        for share in shares.iter().filter(|s| s.room == room.get_name()) {
+            println!(
+                "  {} ${:.2} (${:.2} common + ${:.2} room)",
+                share.occupant, share.total, share.common, share.private
+            );
+        }
+    }
+    println!(
+        "\nTo repeat this every month: heesab recurring add {} Rent {:.2} monthly:1 <payer> rent",
+        group.get_name(),
+        amount
+    );
+}
+
+/// How a room's points add up, such as `area 20 x 1 + bathroom 1 x 10 = 30 points`.
+fn points(plan: &RentPlan, room: &Room) -> String {
+    let terms: Vec<String> = room
+        .get_attributes()
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{} {} x {}",
+                name,
+                value,
+                plan.get_attributes().get(name).unwrap_or(&0_f32)
+            )
+        })
+        .collect();
+    match terms.is_empty() {
+        true => "0 points".to_string(),
+        false => format!("{} = {} points", terms.join(" + "), plan.points(room)),
+    }
+}
+
+/// Recurring expenses whose weights are the rent plan's, as added with `rent` as the
+/// participants.
+fn following_plan(group: &Group) -> Vec<String> {
+    let weights: BTreeMap<String, f32> = match group.get_rent_plan() {
+        Some(plan) => plan.weights(),
+        None => return Vec::new(),
+    };
+    group
+        .get_recurring()
+        .iter()
+        .filter(|r| *r.get_weights() == weights)
+        .map(|r| r.get_name().to_string())
+        .collect()
+}
+
+/// Recurring expenses copy the plan's weights when they are added, so they keep splitting
+/// by the plan as it was.
+fn warn_following(group_name: &str, following: &[String]) {
+    for name in following {
+        println!(
+            "Warning: The recurring expense {} still splits by the previous rent plan. To follow this one, remove it with heesab recurring remove {} {} and add it again with rent as the participants.",
+            name, group_name, name
+        );
+    }
+}
+
+fn members<'a>(group: &'a Group, references: &[String]) -> Option<Vec<&'a User>> {
+    let mut members: Vec<&User> = Vec::new();
+    for reference in references {
+        match group.find_member(reference) {
+            Ok(member) => members.push(member),
+            Err(_) => {
+                println!(
+                    "Error: {} is not a member of {}.",
+                    reference,
+                    group.get_name()
+                );
+                return None;
+            }
+        }
+    }
+    Some(members)
+}
@@ -0,0 +1,68 @@
+use heesab::details::Filter;
+use heesab::group::Group;
+use heesab::report::{Report, ReportFormat, ReportKind};
+use heesab::store::Store;
+
+/// `heesab report [<group>] [<kind>] [--csv|--json]` with the filters of `heesab balances`
+/// shows spending reports. The kind is one of categories, people, months, merchants or
+/// shares, and every report is shown if it is left out.
+pub fn run(args: &[String]) {
+    let format: ReportFormat = match (
+        args.iter().any(|a| a == "--csv"),
+        args.iter().any(|a| a == "--json"),
+    ) {
+        (false, false) => ReportFormat::Table,
+        (true, false) => ReportFormat::Csv,
+        (false, true) => ReportFormat::Json,
+        (true, true) => {
+            println!("Error: Pick one of --csv and --json.");
+            return;
+        }
+    };
+    let args: Vec<String> = args
+        .iter()
+        .filter(|a| *a != "--csv" && *a != "--json")
+        .cloned()
+        .collect();
+    let (references, filter): (Vec<&str>, Filter) = match super::parse_filter(&args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            println!("Error: {}.", message);
+            return;
+        }
+    };
+
+    // A lone argument is a kind if it names one, otherwise a group.
+    let (group_reference, kind): (Option<&str>, Option<ReportKind>) = match references[..] {
+        [] => (None, None),
+        [only] => match ReportKind::new(only) {
+            Ok(kind) => (None, Some(kind)),
+            Err(_) => (Some(only), None),
+        },
+        [group, kind] => match ReportKind::new(kind) {
+            Ok(kind) => (Some(group), Some(kind)),
+            Err(_) => {
+                println!("Error: Reports are categories, people, months, merchants or shares.");
+                return;
+            }
+        },
+        _ => {
+            super::print_usage();
+            return;
+        }
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &Group = match super::pick_group(&mut store, group_reference) {
+        Some(group) => group,
+        None => return,
+    };
+    let report: Report = Report::new(group.get_ledger(), &filter);
+    match report.render(kind, format) {
+        Ok(rendered) => print!("{}", rendered),
+        Err(_) => println!("Error: CSV holds one report. Name which one."),
+    }
+    if format == ReportFormat::Json {
+        println!();
+    }
+}
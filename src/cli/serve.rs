@@ -0,0 +1,92 @@
+use tiny_http::{Header, Server};
+
+use heesab::api::{self, Request, Response};
+use heesab::store::Store;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+/// The web front-end, a single page that only calls the REST API so that every amount it
+/// shows is calculated the same way as on the command line.
+const INDEX: &str = include_str!("web/index.html");
+
+/// Serves the web front-end at `/` and the REST API described in `api::handle`. Requests are
+/// handled one at a time, and each reads and writes the same saved groups as the rest of
+/// heesab. Listen on `0.0.0.0:<port>` to reach it from other devices on the network.
+pub fn run(args: &[String]) {
+    let address: &str = args.first().map_or(DEFAULT_ADDRESS, |a| a.as_str());
+    let server: Server = match Server::http(address) {
+        Ok(server) => server,
+        Err(_) => {
+            println!("Error: Could not listen on {}.", address);
+            return;
+        }
+    };
+    println!("Serving heesab on http://{}", address);
+
+    for mut request in server.incoming_requests() {
+        let mut body: String = String::new();
+        let read: bool = request.as_reader().read_to_string(&mut body).is_ok();
+        let if_match: Option<String> = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("If-Match"))
+            .map(|h| h.value.as_str().to_string());
+        let method: String = request.method().as_str().to_string();
+        let url: String = request.url().to_string();
+
+        let page: bool = method == "GET" && (url == "/" || url == "/index.html");
+        let (status, body, version) = if page {
+            (200, INDEX.to_string(), None)
+        } else if read {
+            respond(&method, &url, if_match.as_deref(), &body)
+        } else {
+            (400, error("request body must be UTF-8"), None)
+        };
+        let content_type: &str = if page {
+            "text/html; charset=utf-8"
+        } else {
+            "application/json"
+        };
+
+        let mut response = tiny_http::Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header("Content-Type", content_type));
+        if let Some(version) = version {
+            response = response.with_header(header("ETag", &format!("\"{}\"", version)));
+        }
+        if request.respond(response).is_err() {
+            println!("Warning: Could not send a response to {} {}.", method, url);
+        }
+    }
+}
+
+/// Reloads the saved groups for every request so changes made with the CLI are picked up,
+/// and saves them again after a successful change.
+fn respond(
+    method: &str,
+    url: &str,
+    if_match: Option<&str>,
+    body: &str,
+) -> (u16, String, Option<u64>) {
+    let mut store: Store = match Store::load(&super::data_path()) {
+        Ok(store) => store,
+        Err(_) => return (500, error("could not read saved groups"), None),
+    };
+    let response: Response = api::handle(&mut store, &Request::new(method, url, if_match, body));
+    if method != "GET" && response.get_status() < 300 && store.save(&super::data_path()).is_err() {
+        return (500, error("could not write saved groups"), None);
+    }
+    (
+        response.get_status(),
+        response.get_body().to_string(),
+        response.get_version(),
+    )
+}
+
+fn error(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("Header is valid")
+}
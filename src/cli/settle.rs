@@ -0,0 +1,74 @@
+use chrono::{Local, NaiveDate};
+
+use heesab::group::Group;
+use heesab::settlement::{Settlement, SettlementError};
+use heesab::store::Store;
+use heesab::user::User;
+
+pub fn run(args: &[String]) {
+    let parse_date = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d");
+
+    // The group is optional when there is only one, so four arguments can either start with
+    // a group or end with a date.
+    let (group_reference, args, date) = match args.len() {
+        3 => (None, args, None),
+        4 if parse_date(&args[3]).is_ok() => (None, &args[..3], Some(&args[3])),
+        4 => (Some(args[0].as_str()), &args[1..], None),
+        5 => (Some(args[0].as_str()), &args[1..4], Some(&args[4])),
+        _ => {
+            super::print_usage();
+            return;
+        }
+    };
+
+    let amount: f32 = match args[2].parse::<f32>() {
+        Ok(amount) => amount,
+        Err(_) => {
+            println!("Error: Could not parse amount.");
+            return;
+        }
+    };
+
+    let date: NaiveDate = match date {
+        None => Local::now().date_naive(),
+        Some(date) => match parse_date(date) {
+            Ok(date) => date,
+            Err(_) => {
+                println!("Error: Could not parse date. Use YYYY-MM-DD.");
+                return;
+            }
+        },
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, group_reference) {
+        Some(group) => group,
+        None => return,
+    };
+
+    let (from, to): (User, User) = match (group.find_member(&args[0]), group.find_member(&args[1]))
+    {
+        (Ok(from), Ok(to)) => (from.clone(), to.clone()),
+        _ => {
+            println!(
+                "Error: Both people must be members of {}.",
+                group.get_name()
+            );
+            return;
+        }
+    };
+
+    let maybe_settlement: Result<Settlement, SettlementError> =
+        Settlement::new(&from, &to, amount, date);
+    let settlement: Settlement = match maybe_settlement {
+        Ok(settlement) => settlement,
+        Err(_) => {
+            println!("Error: Settlement details are invalid.");
+            return;
+        }
+    };
+
+    group.get_ledger_mut().record_settlement(settlement);
+    super::save_store(&store);
+    println!("Recorded: {} paid {} ${} on {}", from, to, amount, date);
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use heesab::expense::CalculatedExpense;
+use heesab::group::Group;
+use heesab::ledger::LedgerEntry;
+use heesab::selection::{self, SelectionError};
+use heesab::split::{ProcessedSplit, Split};
+use heesab::statement::{self, Draft, StatementError, StatementFormat, Transaction};
+use heesab::store::Store;
+use heesab::user::User;
+
+/// `heesab statement <group> <member> <file>` turns a bank or card statement into expenses
+/// paid by `member`. Each transaction is offered in turn and only the ones given
+/// participants are recorded.
+pub fn run(args: &[String]) {
+    if args.len() != 3 {
+        super::print_usage();
+        return;
+    }
+
+    let transactions: Vec<Transaction> = match read(&args[2], statement::parse) {
+        Some(transactions) => transactions,
+        None => return,
+    };
+
+    let mut store: Store = super::load_store();
+    let group: &mut Group = match super::pick_group(&mut store, Some(&args[0])) {
+        Some(group) => group,
+        None => return,
+    };
+    let group_name: String = group.get_name().to_string();
+    let roster: Vec<User> = group.get_roster().to_vec();
+    let aliases: BTreeMap<String, Vec<String>> = group.get_aliases().clone();
+    let payer: &User = match selection::select_one(&roster, &args[1]) {
+        Ok(payer) => payer,
+        Err(_) => {
+            println!("Error: {} is not a member of {}.", args[1], group_name);
+            return;
+        }
+    };
+    let drafts: Vec<Draft> = match statement::drafts(transactions, payer, group.get_ledger()) {
+        Ok(drafts) => drafts,
+        Err(_) => {
+            println!(
+                "Error: {} has a transaction that is not a valid expense.",
+                args[2]
+            );
+            return;
+        }
+    };
+    if drafts.is_empty() {
+        println!("No outgoing transactions in {}.", args[2]);
+        return;
+    }
+
+    for (index, user) in roster.iter().enumerate() {
+        println!("{}. {} ({})", index + 1, user, user.initials());
+    }
+    println!(
+        "\nFor each transaction paid by {}, enter who shared it by name, initials, prefix, number or alias in the following format: <participant one>:<participant two>:... Leave blank to skip it.",
+        payer
+    );
+
+    let mut entries: Vec<LedgerEntry> = Vec::new();
+    'drafts: for mut draft in drafts {
+        let transaction: Transaction = draft.get_transaction().clone();
+        println!(
+            "\n{} {} ${}",
+            transaction.get_date(),
+            transaction.get_merchant(),
+            transaction.get_amount()
+        );
+        if let Some(duplicate) = draft.get_duplicate() {
+            println!("Looks like {}, which is already recorded.", duplicate);
+        }
+
+        let participants: Vec<&User> = loop {
+            let mut line: String = String::new();
+            if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                break 'drafts;
+            }
+            if line.trim().is_empty() {
+                continue 'drafts;
+            }
+            let maybe_selected: Result<Vec<&User>, SelectionError> =
+                selection::select(&roster, &aliases, line.trim());
+            match maybe_selected {
+                Ok(selected) => break selected,
+                Err(e) => println!("Error: {}. Please try again.", e),
+            }
+        };
+        for participant in participants {
+            draft.get_expense_mut().add_participant(participant);
+        }
+
+        let expense: CalculatedExpense = match draft.into_expense().finalize() {
+            Ok(expense) => expense.calculate(),
+            Err(_) => {
+                println!(
+                    "Error: Could not split {}. Skipped.",
+                    transaction.get_merchant()
+                );
+                continue;
+            }
+        };
+        let maybe_entry = Split::new(vec![&expense], 0.00, 0.00)
+            .and_then(|split| split.process())
+            .map(|split: ProcessedSplit| {
+                LedgerEntry::from_split(transaction.get_merchant(), transaction.get_date(), &split)
+            });
+        match maybe_entry {
+            Ok(Ok(entry)) => entries.push(entry),
+            _ => println!(
+                "Error: Could not split {}. Skipped.",
+                transaction.get_merchant()
+            ),
+        }
+    }
+
+    if entries.is_empty() {
+        println!("\nNothing recorded.");
+        return;
+    }
+    let count: usize = entries.len();
+    let group: &mut Group = match store.find_group_mut(&group_name) {
+        Ok(group) => group,
+        Err(_) => return,
+    };
+    for entry in entries {
+        group.get_ledger_mut().record_entry(entry);
+    }
+    super::save_store(&store);
+    println!("\nRecorded {} expenses in {}.", count, group_name);
+}
+
+/// Reads the statement at `path` with `parse`, picking the format from the extension. Prints
+/// what went wrong and returns `None` on failure.
+pub fn read(
+    path: &str,
+    parse: fn(StatementFormat, &str) -> Result<Vec<Transaction>, StatementError>,
+) -> Option<Vec<Transaction>> {
+    let format: StatementFormat = match Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(StatementFormat::from_extension)
+    {
+        Some(Ok(format)) => format,
+        Some(Err(e)) => {
+            println!("Error: {}.", e);
+            return None;
+        }
+        None => {
+            println!("Error: Name the statement .ofx, .qfx, .qif or .xml.");
+            return None;
+        }
+    };
+    let contents: String = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => {
+            println!("Error: Could not read {}.", path);
+            return None;
+        }
+    };
+    match parse(format, &contents) {
+        Ok(transactions) => Some(transactions),
+        Err(e) => {
+            println!("Error: {}.", e);
+            None
+        }
+    }
+}
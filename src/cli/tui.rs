@@ -0,0 +1,616 @@
+use std::io;
+use std::path::PathBuf;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use heesab::session::{DraftExpense, Session};
+use heesab::split::Breakdown;
+use heesab::store::Store;
+use heesab::taxonomy::Taxonomy;
+use heesab::user::User;
+
+const HELP: &str = "tab switch pane  arrows move  enter edit  space share  n new  d delete  e details  t tax  p tip  q quit";
+
+/// Builds a bill full screen. Starts from a group's roster, or picks up the saved session when
+/// no group is named. Every change is autosaved, so `heesab resume` can record who paid.
+pub fn run(args: &[String]) {
+    let (session, taxonomy): (Session, Taxonomy) = match args.first() {
+        Some(reference) => {
+            let mut store: Store = super::load_store();
+            let group = match super::pick_group(&mut store, Some(reference)) {
+                Some(group) => group,
+                None => return,
+            };
+            match Session::new(
+                Some(group.get_name()),
+                group.get_roster().to_vec(),
+                group.get_aliases().clone(),
+            ) {
+                Ok(session) => (session, group.get_taxonomy().clone()),
+                Err(_) => {
+                    println!("Error: {} has no members.", group.get_name());
+                    return;
+                }
+            }
+        }
+        None => match Session::load(&super::session_path()) {
+            Ok(session) => {
+                let taxonomy: Taxonomy = super::session_taxonomy(&session);
+                (session, taxonomy)
+            }
+            Err(_) => {
+                println!("Error: Please name a group, or start a bill with heesab first.");
+                return;
+            }
+        },
+    };
+
+    let mut app: App = App::new(session, taxonomy, super::session_path());
+    let mut terminal: DefaultTerminal = ratatui::init();
+    let result: io::Result<()> = app.run(&mut terminal);
+    ratatui::restore();
+    result.expect("Error: Terminal failed. Exiting.");
+    println!("Bill saved. Run heesab resume to record who paid.");
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pane {
+    Participants,
+    Expenses,
+}
+
+/// What a line being typed in will be used for once Enter is pressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    Participant,
+    Name,
+    Amount,
+    Details,
+    Tax,
+    Tip,
+}
+
+/// The state of the screen, kept apart from drawing so key handling can be tested.
+struct App {
+    session: Session,
+    /// Where every change is autosaved.
+    session_path: PathBuf,
+    /// The categories expense details are filed under.
+    taxonomy: Taxonomy,
+    pane: Pane,
+    participant: usize,
+    expense: usize,
+    /// 0 is the name, 1 the amount and from 2 on the roster, one column per participant.
+    column: usize,
+    editing: Option<(Field, String)>,
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(session: Session, taxonomy: Taxonomy, session_path: PathBuf) -> Self {
+        App {
+            session,
+            session_path,
+            taxonomy,
+            pane: Pane::Expenses,
+            participant: 0,
+            expense: 0,
+            column: 0,
+            editing: None,
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.editing.is_some() {
+            self.handle_edit_key(key.code);
+            return;
+        }
+        self.message.clear();
+
+        match (self.pane, key.code) {
+            (_, KeyCode::Char('q')) | (_, KeyCode::Esc) => self.quit = true,
+            (_, KeyCode::Tab) | (_, KeyCode::BackTab) => {
+                self.pane = match self.pane {
+                    Pane::Participants => Pane::Expenses,
+                    Pane::Expenses => Pane::Participants,
+                }
+            }
+            (_, KeyCode::Char('t')) => self.start_editing(Field::Tax),
+            (_, KeyCode::Char('p')) => self.start_editing(Field::Tip),
+            (Pane::Participants, KeyCode::Up) => {
+                self.participant = self.participant.saturating_sub(1)
+            }
+            (Pane::Participants, KeyCode::Down) => {
+                self.participant = (self.participant + 1).min(self.session.get_roster().len() - 1)
+            }
+            (Pane::Participants, KeyCode::Char('n')) => self.start_editing(Field::Participant),
+            (Pane::Participants, KeyCode::Char('d')) => self.remove_participant(),
+            (Pane::Expenses, KeyCode::Up) => self.expense = self.expense.saturating_sub(1),
+            (Pane::Expenses, KeyCode::Down) => {
+                self.expense = (self.expense + 1).min(self.session.get_expenses().len().max(1) - 1)
+            }
+            (Pane::Expenses, KeyCode::Left) => self.column = self.column.saturating_sub(1),
+            (Pane::Expenses, KeyCode::Right) => {
+                self.column = (self.column + 1).min(self.session.get_roster().len() + 1)
+            }
+            (Pane::Expenses, KeyCode::Char('n')) => self.add_expense(),
+            (Pane::Expenses, KeyCode::Char('d')) => self.remove_expense(),
+            (Pane::Expenses, KeyCode::Char('e')) => self.start_editing(Field::Details),
+            (Pane::Expenses, KeyCode::Enter) => match self.column {
+                0 => self.start_editing(Field::Name),
+                1 => self.start_editing(Field::Amount),
+                _ => self.toggle_participant(),
+            },
+            (Pane::Expenses, KeyCode::Char(' ')) if self.column >= 2 => self.toggle_participant(),
+            _ => {}
+        }
+    }
+
+    fn handle_edit_key(&mut self, code: KeyCode) {
+        let (field, mut buffer) = match self.editing.take() {
+            Some(editing) => editing,
+            None => return,
+        };
+        match code {
+            KeyCode::Esc => return,
+            KeyCode::Enter => {
+                self.commit(field, buffer.trim());
+                return;
+            }
+            KeyCode::Backspace => {
+                buffer.pop();
+            }
+            KeyCode::Char(c) => buffer.push(c),
+            _ => {}
+        }
+        self.editing = Some((field, buffer));
+    }
+
+    /// Starts typing into `field`, prefilled with its current value.
+    fn start_editing(&mut self, field: Field) {
+        let expense: Option<&DraftExpense> = self.session.get_expenses().get(self.expense);
+        let (tax, tip) = self.session.get_tax_and_tip().unwrap_or((0_f32, 0_f32));
+        let buffer: String = match field {
+            Field::Participant => String::new(),
+            Field::Name | Field::Amount | Field::Details if expense.is_none() => {
+                self.message = "Add an expense first with n.".to_string();
+                return;
+            }
+            Field::Name => expense.map_or(String::new(), |e| e.get_name().to_string()),
+            Field::Amount => expense.map_or(String::new(), |e| e.get_amount().to_string()),
+            Field::Details => {
+                expense.map_or(String::new(), |e| super::format_details(e.get_details()))
+            }
+            Field::Tax => tax.to_string(),
+            Field::Tip => tip.to_string(),
+        };
+        self.editing = Some((field, buffer));
+    }
+
+    fn commit(&mut self, field: Field, input: &str) {
+        let (tax, tip) = self.session.get_tax_and_tip().unwrap_or((0_f32, 0_f32));
+        let committed: Result<(), String> = match field {
+            Field::Participant => User::new(input)
+                .map_err(|_| ())
+                .and_then(|user| self.session.add_participant(user).map_err(|_| ()))
+                .map_err(|_| "Name must be valid and unique.".to_string()),
+            Field::Name => self
+                .session
+                .edit_name(self.expense, input)
+                .map_err(|_| "Name must not be empty.".to_string()),
+            Field::Amount => parse_amount(input)
+                .and_then(|amount| self.session.edit_amount(self.expense, amount).ok())
+                .ok_or("Amount must be a number that is 0 or more.".to_string()),
+            Field::Details => super::parse_details(&self.taxonomy, input)
+                .map(|details| {
+                    self.session
+                        .set_details(self.expense, details)
+                        .expect("the expense exists")
+                })
+                .map_err(|message| {
+                    format!("{}. Use <category> #<tag>... notes: <notes>.", message)
+                }),
+            Field::Tax => parse_amount(input)
+                .and_then(|tax| self.session.set_tax_and_tip(tax, tip).ok())
+                .ok_or(
+                    "Tax must be a number that is 0 or more, once there is an expense.".to_string(),
+                ),
+            Field::Tip => parse_amount(input)
+                .and_then(|tip| self.session.set_tax_and_tip(tax, tip).ok())
+                .ok_or(
+                    "Tip must be a number that is 0 or more, once there is an expense.".to_string(),
+                ),
+        };
+        match committed {
+            Ok(()) => self.save(),
+            Err(message) => self.message = message,
+        }
+    }
+
+    fn save(&self) {
+        super::save_session_at(&self.session, &self.session_path);
+    }
+
+    /// New expenses are shared by everyone until unticked, and start with their name being typed.
+    fn add_expense(&mut self) {
+        let roster: Vec<User> = self.session.get_roster().to_vec();
+        let everyone: Vec<&User> = roster.iter().collect();
+        let name: String = format!("Item {}", self.session.get_expenses().len() + 1);
+        if self.session.add_expense(&name, 0_f32, &everyone).is_err() {
+            return;
+        }
+        self.save();
+        self.expense = self.session.get_expenses().len() - 1;
+        self.column = 0;
+        self.editing = Some((Field::Name, name));
+    }
+
+    fn remove_expense(&mut self) {
+        if let Ok(expense) = self.session.remove_expense(self.expense) {
+            self.save();
+            self.message = format!("Removed {}.", expense.get_name());
+            self.expense = self
+                .expense
+                .min(self.session.get_expenses().len().max(1) - 1);
+        }
+    }
+
+    fn toggle_participant(&mut self) {
+        let roster: Vec<User> = self.session.get_roster().to_vec();
+        let expense: &DraftExpense = match self.session.get_expenses().get(self.expense) {
+            Some(expense) => expense,
+            None => return,
+        };
+        let toggled: &str = roster[self.column - 2].get_name();
+        let shared: Vec<&User> = roster
+            .iter()
+            .filter(|u| {
+                let sharing: bool = expense.get_participants().iter().any(|p| p == u.get_name());
+                sharing != (u.get_name() == toggled)
+            })
+            .collect();
+        match self.session.edit_participants(self.expense, &shared) {
+            Ok(()) => self.save(),
+            Err(_) => self.message = "Someone has to share every expense.".to_string(),
+        }
+    }
+
+    fn remove_participant(&mut self) {
+        let name: String = self.session.get_roster()[self.participant]
+            .get_name()
+            .to_string();
+        match self.session.remove_participant(&name) {
+            Ok(_) => {
+                self.save();
+                self.participant = self.participant.min(self.session.get_roster().len() - 1);
+                self.column = self.column.min(self.session.get_roster().len() + 1);
+            }
+            Err(_) => {
+                self.message = format!("{} still shares an expense, untick them first.", name)
+            }
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(5), Constraint::Length(2)]).areas(frame.area());
+        let [participants, expenses, totals] = Layout::horizontal([
+            Constraint::Length(20),
+            Constraint::Min(30),
+            Constraint::Length(44),
+        ])
+        .areas(main);
+
+        self.draw_participants(frame, participants);
+        self.draw_expenses(frame, expenses);
+        self.draw_totals(frame, totals);
+
+        let first: String = match &self.editing {
+            Some((field, buffer)) => format!("{:?}: {}_", field, buffer),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => HELP.to_string(),
+        };
+        let (tax, tip) = self.session.get_tax_and_tip().unwrap_or((0_f32, 0_f32));
+        let second: String = format!("Tax: ${:.2}  Tip: ${:.2}", tax, tip);
+        frame.render_widget(
+            Paragraph::new(vec![Line::from(first), Line::from(second)]),
+            status,
+        );
+    }
+
+    fn draw_participants(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .session
+            .get_roster()
+            .iter()
+            .map(|u| ListItem::new(u.get_name().to_string()))
+            .collect();
+        let list: List = List::new(items)
+            .block(self.block("Participants", Pane::Participants))
+            .highlight_style(self.highlight(Pane::Participants));
+        let mut state: ListState = ListState::default().with_selected(Some(self.participant));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_expenses(&self, frame: &mut Frame, area: Rect) {
+        let roster: &[User] = self.session.get_roster();
+        let mut header: Vec<Cell> = vec![Cell::from("Item"), Cell::from("Amount")];
+        header.extend(roster.iter().map(|u| Cell::from(u.initials())));
+
+        let rows: Vec<Row> = self
+            .session
+            .get_expenses()
+            .iter()
+            .enumerate()
+            .map(|(row, expense)| {
+                let mut cells: Vec<String> = vec![
+                    expense.get_name().to_string(),
+                    format!("${:.2}", expense.get_amount()),
+                ];
+                cells.extend(roster.iter().map(|u| {
+                    let sharing: bool =
+                        expense.get_participants().iter().any(|p| p == u.get_name());
+                    if sharing { "[x]" } else { "[ ]" }.to_string()
+                }));
+                Row::new(cells.into_iter().enumerate().map(|(column, text)| {
+                    let cell: Cell = Cell::from(text);
+                    if row == self.expense && column == self.column && self.pane == Pane::Expenses {
+                        cell.style(Style::default().add_modifier(Modifier::REVERSED))
+                    } else {
+                        cell
+                    }
+                }))
+            })
+            .collect();
+
+        let mut widths: Vec<Constraint> = vec![Constraint::Min(12), Constraint::Length(10)];
+        widths.extend(roster.iter().map(|_| Constraint::Length(4)));
+        let table: Table = Table::new(rows, widths)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(self.block("Expenses", Pane::Expenses))
+            .row_highlight_style(Style::default().add_modifier(Modifier::BOLD));
+        let mut state: TableState = TableState::default().with_selected(Some(self.expense));
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn draw_totals(&self, frame: &mut Frame, area: Rect) {
+        let block: Block = Block::bordered().title("Totals");
+        let breakdown: Breakdown = match self.totals() {
+            Some(breakdown) => breakdown,
+            None => {
+                frame.render_widget(
+                    Paragraph::new("Add an expense with n to see totals.").block(block),
+                    area,
+                );
+                return;
+            }
+        };
+
+        let mut rows: Vec<Row> = breakdown
+            .rows
+            .iter()
+            .map(|row| {
+                Row::new(vec![
+                    row.name.clone(),
+                    format!("{:.2}", row.subtotal),
+                    format!("{:.2}", row.tax),
+                    format!("{:.2}", row.tip),
+                    format!("{:.2}", row.total),
+                ])
+            })
+            .collect();
+        rows.push(
+            Row::new(vec![
+                "Total".to_string(),
+                format!("{:.2}", breakdown.subtotal),
+                format!("{:.2}", breakdown.tax),
+                format!("{:.2}", breakdown.tip),
+                format!("{:.2}", breakdown.total),
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        );
+        let widths: [Constraint; 5] = [
+            Constraint::Min(8),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(8),
+        ];
+        let table: Table = Table::new(rows, widths)
+            .header(Row::new(vec!["", "Subtotal", "Tax", "Tip", "Total"]))
+            .block(block);
+        frame.render_widget(table, area);
+    }
+
+    /// The live split of what has been entered so far.
+    fn totals(&self) -> Option<Breakdown> {
+        let (tax, tip) = self.session.get_tax_and_tip().unwrap_or((0_f32, 0_f32));
+        self.session.split(tax, tip, &[]).ok()
+    }
+
+    fn block(&self, title: &'static str, pane: Pane) -> Block<'static> {
+        let block: Block = Block::bordered().title(title);
+        if self.pane == pane {
+            block.border_style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            block
+        }
+    }
+
+    fn highlight(&self, pane: Pane) -> Style {
+        if self.pane == pane {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        }
+    }
+}
+
+fn parse_amount(input: &str) -> Option<f32> {
+    input
+        .trim_start_matches('$')
+        .parse::<f32>()
+        .ok()
+        .filter(|amount| amount.is_finite() && *amount >= 0_f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyModifiers;
+    use ratatui::Terminal;
+
+    use crate::cli::format_details;
+
+    /// A scratch directory removed when the test ends.
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn app(test: &str) -> (App, TempDir) {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("heesab-tui-{}-{}", std::process::id(), test));
+        let roster: Vec<User> = vec![User::new("alice").unwrap(), User::new("bob").unwrap()];
+        let app: App = App::new(
+            Session::new(None, roster, BTreeMap::new()).unwrap(),
+            Taxonomy::default(),
+            dir.join("session.json"),
+        );
+        (app, TempDir(dir))
+    }
+
+    fn press(app: &mut App, codes: &[KeyCode]) {
+        for code in codes {
+            app.handle_key(KeyEvent::new(*code, KeyModifiers::NONE));
+        }
+    }
+
+    fn typing(text: &str) -> Vec<KeyCode> {
+        text.chars().map(KeyCode::Char).collect()
+    }
+
+    #[test]
+    fn test_add_and_edit_expense() {
+        let (mut app, _dir): (App, TempDir) = app("add_and_edit_expense");
+        press(&mut app, &[KeyCode::Char('n'), KeyCode::Backspace]);
+        press(&mut app, &[KeyCode::Backspace; 6]);
+        press(&mut app, &typing("pizza"));
+        press(&mut app, &[KeyCode::Enter, KeyCode::Right, KeyCode::Enter]);
+        press(&mut app, &[KeyCode::Backspace]);
+        press(&mut app, &typing("30"));
+        press(&mut app, &[KeyCode::Enter]);
+
+        let expense: &DraftExpense = &app.session.get_expenses()[0];
+        assert_eq!(expense.get_name(), "pizza");
+        assert_eq!(expense.get_amount(), 30.00);
+        assert_eq!(expense.get_participants(), ["alice", "bob"]);
+        assert_eq!(app.totals().unwrap().rows[1].total, 15.00);
+    }
+
+    #[test]
+    fn test_toggle_participant() {
+        let (mut app, _dir): (App, TempDir) = app("toggle_participant");
+        press(&mut app, &[KeyCode::Char('n'), KeyCode::Enter]);
+        press(&mut app, &[KeyCode::Right, KeyCode::Right, KeyCode::Right]);
+        press(&mut app, &[KeyCode::Char(' ')]);
+        assert_eq!(app.session.get_expenses()[0].get_participants(), ["alice"]);
+
+        press(&mut app, &[KeyCode::Left, KeyCode::Char(' ')]);
+        assert_eq!(app.session.get_expenses()[0].get_participants(), ["alice"]);
+        assert_eq!(app.message, "Someone has to share every expense.");
+    }
+
+    #[test]
+    fn test_bad_amount_keeps_previous_value() {
+        let (mut app, _dir): (App, TempDir) = app("bad_amount_keeps_previous_value");
+        press(
+            &mut app,
+            &[KeyCode::Char('n'), KeyCode::Enter, KeyCode::Right],
+        );
+        press(&mut app, &[KeyCode::Enter]);
+        press(&mut app, &typing("x"));
+        press(&mut app, &[KeyCode::Enter]);
+
+        assert_eq!(app.session.get_expenses()[0].get_amount(), 0.00);
+        assert_eq!(app.message, "Amount must be a number that is 0 or more.");
+    }
+
+    #[test]
+    fn test_edit_details() {
+        let (mut app, _dir): (App, TempDir) = app("edit_details");
+        press(
+            &mut app,
+            &[KeyCode::Char('n'), KeyCode::Enter, KeyCode::Char('e')],
+        );
+        press(&mut app, &typing("groceries #weekly notes: market"));
+        press(&mut app, &[KeyCode::Enter]);
+
+        let details = app.session.get_expenses()[0].get_details();
+        assert_eq!(details.get_category(), Some("Food:Groceries"));
+        assert_eq!(details.get_notes(), Some("market"));
+        assert_eq!(
+            format_details(details),
+            "Food:Groceries #weekly notes: market"
+        );
+
+        press(&mut app, &[KeyCode::Char('e')]);
+        press(&mut app, &[KeyCode::Backspace; 40]);
+        press(&mut app, &typing("sailing"));
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(
+            app.message,
+            "sailing is not a category. Use <category> #<tag>... notes: <notes>."
+        );
+    }
+
+    #[test]
+    fn test_draw_totals() {
+        let (mut app, _dir): (App, TempDir) = app("draw_totals");
+        press(
+            &mut app,
+            &[KeyCode::Char('n'), KeyCode::Enter, KeyCode::Right],
+        );
+        press(&mut app, &[KeyCode::Enter, KeyCode::Backspace]);
+        press(&mut app, &typing("12.5"));
+        press(&mut app, &[KeyCode::Enter]);
+
+        let mut terminal: Terminal<TestBackend> = Terminal::new(TestBackend::new(120, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("$12.50"));
+        assert!(screen.contains("6.25"));
+    }
+}
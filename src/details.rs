@@ -0,0 +1,268 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::taxonomy::Taxonomy;
+
+const MAX_NOTES_CHARS: usize = 1000;
+
+#[derive(Debug, PartialEq)]
+pub struct DetailsError;
+
+/// What household accounting wants to know about an expense beyond its amount: when it
+/// happened, what it was for, and anything worth remembering. Everything is optional.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Details {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+}
+
+/// Narrows down ledger queries to a date range, a category and tags. An empty filter matches
+/// everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    category: Option<String>,
+    tags: Vec<String>,
+}
+
+impl Details {
+    pub fn new() -> Self {
+        Details::default()
+    }
+
+    pub fn get_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.time
+    }
+
+    /// When the expense happened, with the offset of the time zone it happened in. Its date
+    /// is the date there, not where the ledger is read.
+    pub fn set_time(&mut self, time: DateTime<FixedOffset>) {
+        self.time = Some(time);
+    }
+
+    pub fn get_date(&self) -> Option<NaiveDate> {
+        self.time.map(|t| t.date_naive())
+    }
+
+    pub fn get_category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// Files the expense under a category of `taxonomy`, found as in `Taxonomy::find`.
+    pub fn set_category(
+        &mut self,
+        taxonomy: &Taxonomy,
+        reference: &str,
+    ) -> Result<(), DetailsError> {
+        let category: &str = taxonomy.find(reference).map_err(|_| DetailsError)?;
+        self.category = Some(category.to_string());
+        Ok(())
+    }
+
+    pub fn get_tags(&self) -> &BTreeSet<String> {
+        &self.tags
+    }
+
+    /// Tags are single words of letters, digits, `-` and `_`, and are lowercased. A leading
+    /// `#` is dropped.
+    pub fn add_tag(&mut self, tag: &str) -> Result<(), DetailsError> {
+        let tag: String = normalize_tag(tag).ok_or(DetailsError)?;
+        self.tags.insert(tag);
+        Ok(())
+    }
+
+    pub fn get_notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// Blank notes clear them. Notes are at most 1000 characters.
+    pub fn set_notes(&mut self, notes: &str) -> Result<(), DetailsError> {
+        let notes: &str = notes.trim();
+        if notes.chars().count() > MAX_NOTES_CHARS {
+            return Err(DetailsError);
+        }
+        self.notes = Some(notes.to_string()).filter(|n| !n.is_empty());
+        Ok(())
+    }
+
+    /// Combines the details of the expenses on one bill: the earliest time, the category if
+    /// they all share one, every tag and every note.
+    pub fn merge<'a>(details: impl IntoIterator<Item = &'a Details>) -> Details {
+        let details: Vec<&Details> = details.into_iter().collect();
+        let categories: BTreeSet<Option<&str>> = details.iter().map(|d| d.get_category()).collect();
+        let notes: Vec<&str> = details.iter().filter_map(|d| d.get_notes()).collect();
+        Details {
+            time: details.iter().filter_map(|d| d.time).min(),
+            category: match categories.into_iter().collect::<Vec<Option<&str>>>()[..] {
+                [Some(category)] => Some(category.to_string()),
+                _ => None,
+            },
+            tags: details.iter().flat_map(|d| d.tags.clone()).collect(),
+            notes: Some(notes.join("\n")).filter(|n| !n.is_empty()),
+        }
+    }
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Filter::default()
+    }
+
+    /// Only on or after `date`.
+    pub fn set_since(&mut self, date: NaiveDate) {
+        self.since = Some(date);
+    }
+
+    /// Only on or before `date`.
+    pub fn set_until(&mut self, date: NaiveDate) {
+        self.until = Some(date);
+    }
+
+    /// Only in `category` or its subcategories, ignoring case.
+    pub fn set_category(&mut self, category: &str) {
+        self.category = Some(category.trim().to_lowercase());
+    }
+
+    /// Only with every tag added.
+    pub fn add_tag(&mut self, tag: &str) -> Result<(), DetailsError> {
+        self.tags.push(normalize_tag(tag).ok_or(DetailsError)?);
+        Ok(())
+    }
+
+    /// Whether only dates are filtered on. Settlements have no category or tags, so they are
+    /// only counted when this holds.
+    pub fn is_dates_only(&self) -> bool {
+        self.category.is_none() && self.tags.is_empty()
+    }
+
+    pub fn matches_date(&self, date: NaiveDate) -> bool {
+        self.since.is_none_or(|since| since <= date) && self.until.is_none_or(|until| date <= until)
+    }
+
+    pub fn matches(&self, date: NaiveDate, details: &Details) -> bool {
+        let in_category = |filter: &String| {
+            details.get_category().is_some_and(|c| {
+                let c: String = c.to_lowercase();
+                c == *filter || c.starts_with(&format!("{}:", filter))
+            })
+        };
+        self.matches_date(date)
+            && self.category.as_ref().is_none_or(in_category)
+            && self.tags.iter().all(|t| details.tags.contains(t))
+    }
+}
+
+fn normalize_tag(tag: &str) -> Option<String> {
+    let tag: &str = tag.trim();
+    let tag: &str = tag.strip_prefix('#').unwrap_or(tag);
+    if tag.is_empty()
+        || !tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    Some(tag.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn time(value: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(value).unwrap()
+    }
+
+    fn details(category: &str, tags: &[&str]) -> Details {
+        let mut details: Details = Details::new();
+        details
+            .set_category(&Taxonomy::default(), category)
+            .unwrap();
+        for tag in tags {
+            details.add_tag(tag).unwrap();
+        }
+        details
+    }
+
+    #[test]
+    fn test_time_keeps_local_date() {
+        let mut details: Details = Details::new();
+        details.set_time(time("2026-01-31T23:30:00-08:00"));
+        assert_eq!(details.get_date(), NaiveDate::from_ymd_opt(2026, 1, 31));
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut details: Details = Details::new();
+        assert_eq!(
+            details.set_category(&Taxonomy::default(), "Pets"),
+            Err(DetailsError)
+        );
+        assert!(details.add_tag("#Weekend-Trip").is_ok());
+        assert_eq!(details.add_tag("two words"), Err(DetailsError));
+        assert_eq!(details.add_tag("#"), Err(DetailsError));
+        assert_eq!(
+            details.get_tags(),
+            &BTreeSet::from(["weekend-trip".to_string()])
+        );
+
+        assert!(details.set_notes("  paid with the joint card ").is_ok());
+        assert_eq!(details.get_notes(), Some("paid with the joint card"));
+        assert_eq!(details.set_notes(&"x".repeat(1001)), Err(DetailsError));
+        assert!(details.set_notes(" ").is_ok());
+        assert_eq!(details.get_notes(), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut first: Details = details("groceries", &["costco"]);
+        first.set_time(time("2026-01-05T18:00:00+01:00"));
+        first.set_notes("bulk").unwrap();
+        let mut second: Details = details("groceries", &["weekly"]);
+        second.set_time(time("2026-01-05T12:00:00+01:00"));
+
+        let merged: Details = Details::merge([&first, &second]);
+        assert_eq!(merged.get_time(), Some(time("2026-01-05T12:00:00+01:00")));
+        assert_eq!(merged.get_category(), Some("Food:Groceries"));
+        assert_eq!(merged.get_tags().len(), 2);
+        assert_eq!(merged.get_notes(), Some("bulk"));
+
+        let mixed: Details = Details::merge([&first, &details("dining", &[])]);
+        assert_eq!(mixed.get_category(), None);
+        let partly: Details = Details::merge([&first, &Details::new()]);
+        assert_eq!(partly.get_category(), None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 3, 15).unwrap();
+        let groceries: Details = details("groceries", &["costco", "weekly"]);
+
+        let mut filter: Filter = Filter::new();
+        assert!(filter.matches(date, &Details::new()));
+        filter.set_category("food");
+        assert!(filter.matches(date, &groceries));
+        assert!(!filter.matches(date, &details("rent", &[])));
+        assert!(!filter.matches(date, &Details::new()));
+
+        filter.add_tag("#Costco").unwrap();
+        assert!(filter.matches(date, &groceries));
+        assert!(!filter.matches(date, &details("groceries", &["weekly"])));
+
+        filter.set_since(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        filter.set_until(NaiveDate::from_ymd_opt(2026, 3, 31).unwrap());
+        assert!(filter.matches(date, &groceries));
+        assert!(!filter.matches(date.with_month(4).unwrap(), &groceries));
+        assert!(!filter.is_dates_only());
+    }
+}
@@ -1,6 +1,11 @@
 use std::collections::HashMap;
 
-use crate::rounding::Round;
+use chrono::NaiveDate;
+
+use crate::details::Details;
+use crate::period::Period;
+use crate::rounding::{allocate, Round};
+use crate::upserting::Upsertable;
 use crate::user::User;
 
 #[derive(Debug, PartialEq)]
@@ -10,21 +15,46 @@ pub struct PendingExpenseError;
 pub struct PendingExpense<'a> {
     name: String,
     amount: f32,
+    tax: f32,
+    tip: f32,
     participants: Vec<&'a User>,
+    payers: HashMap<&'a User, f32>,
+    details: Details,
+    billing_period: Option<Period>,
+    presence: HashMap<&'a User, Period>,
+    weights: HashMap<&'a User, f32>,
+    income_date: Option<NaiveDate>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Expense<'a> {
     name: String,
     amount: f32,
+    tax: f32,
+    tip: f32,
+    participants: Vec<&'a User>,
+    weights: Vec<f32>,
+    payers: HashMap<&'a User, f32>,
+    details: Details,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalculatedExpense<'a> {
+    name: String,
+    amount: f32,
+    tax: f32,
+    tip: f32,
     participants: Vec<&'a User>,
-    num_participants: u32,
     user_to_amount: HashMap<&'a User, f32>,
+    user_to_tax: HashMap<&'a User, f32>,
+    user_to_tip: HashMap<&'a User, f32>,
+    user_to_paid: HashMap<&'a User, f32>,
+    details: Details,
 }
 
 impl<'a> PendingExpense<'a> {
     pub fn new(name: &str, amount: f32) -> Result<Self, PendingExpenseError> {
-        if name.len() == 0 {
+        if name.is_empty() {
             return Err(PendingExpenseError);
         }
 
@@ -35,7 +65,15 @@ impl<'a> PendingExpense<'a> {
         Ok(PendingExpense {
             name: name.to_string(),
             amount,
+            tax: 0_f32,
+            tip: 0_f32,
             participants: Vec::new(),
+            payers: HashMap::new(),
+            details: Details::new(),
+            billing_period: None,
+            presence: HashMap::new(),
+            weights: HashMap::new(),
+            income_date: None,
         })
     }
 
@@ -43,46 +81,201 @@ impl<'a> PendingExpense<'a> {
         self.participants.push(user);
     }
 
+    pub fn set_tax(&mut self, tax: f32) -> Result<(), PendingExpenseError> {
+        if tax < 0_f32 {
+            return Err(PendingExpenseError);
+        }
+        self.tax = tax;
+        Ok(())
+    }
+
+    pub fn set_tip(&mut self, tip: f32) -> Result<(), PendingExpenseError> {
+        if tip < 0_f32 {
+            return Err(PendingExpenseError);
+        }
+        self.tip = tip;
+        Ok(())
+    }
+
+    /// Records that `user` paid `amount` towards this expense. Paying twice adds up.
+    pub fn add_payer(&mut self, user: &'a User, amount: f32) -> Result<(), PendingExpenseError> {
+        if amount <= 0_f32 {
+            return Err(PendingExpenseError);
+        }
+        self.payers.upsert_all(&HashMap::from([(user, amount)]));
+        Ok(())
+    }
+
+    /// Splits the amount by person-days over `period` instead of equally, such as a utility
+    /// bill for the month a roommate moved in. See `set_presence`.
+    pub fn set_billing_period(&mut self, period: Period) {
+        self.billing_period = Some(period);
+    }
+
+    /// The days `user` was present during the billing period. Participants without one were
+    /// present throughout it.
+    pub fn set_presence(&mut self, user: &'a User, period: Period) {
+        self.presence.insert(user, period);
+    }
+
+    /// Splits the amount in proportion to weights instead of equally, such as those of a
+    /// `RentPlan`. Participants without a weight weigh 1. With a billing period, each
+    /// participant's days are multiplied by their weight.
+    pub fn set_weight(&mut self, user: &'a User, weight: f32) -> Result<(), PendingExpenseError> {
+        if !weight.is_finite() || weight < 0_f32 {
+            return Err(PendingExpenseError);
+        }
+        self.weights.insert(user, weight);
+        Ok(())
+    }
+
+    /// Splits the amount in proportion to the participants' incomes on `date`, usually the
+    /// day of the expense, so expenses from before a raise keep the old ratio. See
+    /// `User::income_on`. Combines with weights and a billing period by multiplying.
+    pub fn split_by_income(&mut self, date: NaiveDate) {
+        self.income_date = Some(date);
+    }
+
+    /// Date, category, tags and notes, each validated as it is set.
+    pub fn get_details_mut(&mut self) -> &mut Details {
+        &mut self.details
+    }
+
+    /// Fails if there are no participants, or if payers were given and what they paid does not
+    /// match the amount plus tax and tip to the cent. An expense without payers is allowed so
+    /// that payments can be recorded for the whole split instead. With a billing period, also
+    /// fails if someone present or weighted is not a participant, or if every participant
+    /// weighs nothing, such as when nobody was present for a single day. When split by
+    /// income, also fails if a participant had no income recorded by then.
     pub fn finalize(self) -> Result<Expense<'a>, PendingExpenseError> {
-        if self.participants.len() == 0 {
+        if self.participants.is_empty() {
+            return Err(PendingExpenseError);
+        }
+
+        if self
+            .presence
+            .keys()
+            .chain(self.weights.keys())
+            .any(|user| !self.participants.contains(user))
+        {
+            return Err(PendingExpenseError);
+        }
+        if self.billing_period.is_none() && !self.presence.is_empty() {
             return Err(PendingExpenseError);
         }
+        let mut weights: Vec<f32> = Vec::with_capacity(self.participants.len());
+        for user in &self.participants {
+            let days: f32 = match (self.billing_period, self.presence.get(user)) {
+                (Some(period), Some(presence)) => period.overlap_days(presence) as f32,
+                (Some(period), None) => period.days() as f32,
+                (None, _) => 1_f32,
+            };
+            let income: f32 = match self.income_date {
+                Some(date) => user.income_on(date).ok_or(PendingExpenseError)?,
+                None => 1_f32,
+            };
+            weights.push(days * self.weights.get(user).copied().unwrap_or(1_f32) * income);
+        }
+        if weights.iter().all(|w| *w == 0_f32) {
+            return Err(PendingExpenseError);
+        }
+
+        if !self.payers.is_empty() {
+            let paid: i64 = self.payers.values().map(|v| v.to_cents()).sum();
+            let owed: i64 = self.amount.to_cents() + self.tax.to_cents() + self.tip.to_cents();
+            if paid != owed {
+                return Err(PendingExpenseError);
+            }
+        }
 
-        let num_participants: u32 = self.participants.len() as u32;
         Ok(Expense {
             name: self.name,
             amount: self.amount,
+            tax: self.tax,
+            tip: self.tip,
             participants: self.participants,
-            num_participants,
-            user_to_amount: HashMap::new(),
+            weights,
+            payers: self.payers,
+            details: self.details,
         })
     }
 }
 
 impl<'a> Expense<'a> {
-    pub fn calculate(&mut self) {
-        let per_user_amount_unrounded: f32 = self.amount / self.num_participants as f32;
-        let per_user_amount: f32 = per_user_amount_unrounded.to_two_decimals();
-
-        let mut remaining: f32 = self.amount;
+    pub fn calculate(self) -> CalculatedExpense<'a> {
+        let amounts: Vec<f32> = allocate(self.amount, &self.weights);
+        let taxes: Vec<f32> = allocate(self.tax, &amounts);
+        let tips: Vec<f32> = allocate(self.tip, &amounts);
+
+        let mut user_to_amount: HashMap<&'a User, f32> = HashMap::new();
+        let mut user_to_tax: HashMap<&'a User, f32> = HashMap::new();
+        let mut user_to_tip: HashMap<&'a User, f32> = HashMap::new();
+
+        for (index, participant) in self.participants.iter().enumerate() {
+            user_to_amount.upsert_all(&HashMap::from([(*participant, amounts[index])]));
+            user_to_tax.upsert_all(&HashMap::from([(*participant, taxes[index])]));
+            user_to_tip.upsert_all(&HashMap::from([(*participant, tips[index])]));
+        }
 
-        for participant in &self.participants {
-            if per_user_amount < remaining {
-                self.user_to_amount.insert(participant, per_user_amount);
-            } else {
-                self.user_to_amount.insert(participant, remaining);
-            }
-            remaining = (remaining - per_user_amount).to_two_decimals();
+        CalculatedExpense {
+            name: self.name,
+            amount: self.amount,
+            tax: self.tax,
+            tip: self.tip,
+            participants: self.participants,
+            user_to_amount,
+            user_to_tax,
+            user_to_tip,
+            user_to_paid: self.payers,
+            details: self.details,
         }
     }
 
-    pub fn get_user_to_amount(&self) -> HashMap<&User, f32> {
+    pub fn get_amount(&self) -> f32 {
+        self.amount
+    }
+}
+
+impl<'a> CalculatedExpense<'a> {
+    pub fn get_user_to_amount(&self) -> HashMap<&'a User, f32> {
         self.user_to_amount.clone()
     }
 
+    pub fn get_user_to_tax(&self) -> HashMap<&'a User, f32> {
+        self.user_to_tax.clone()
+    }
+
+    pub fn get_user_to_tip(&self) -> HashMap<&'a User, f32> {
+        self.user_to_tip.clone()
+    }
+
+    pub fn get_user_to_paid(&self) -> HashMap<&'a User, f32> {
+        self.user_to_paid.clone()
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
     pub fn get_amount(&self) -> f32 {
         self.amount
     }
+
+    pub fn get_tax(&self) -> f32 {
+        self.tax
+    }
+
+    pub fn get_tip(&self) -> f32 {
+        self.tip
+    }
+
+    pub fn get_participants(&self) -> &[&'a User] {
+        &self.participants
+    }
+
+    pub fn get_details(&self) -> &Details {
+        &self.details
+    }
 }
 
 #[cfg(test)]
@@ -158,14 +351,75 @@ mod tests {
             let expected_expense: Expense = Expense {
                 name: "random".to_string(),
                 amount: 12.34,
+                tax: 0_f32,
+                tip: 0_f32,
                 participants: vec![&user_one, &user_two],
-                num_participants: 2,
-                user_to_amount: HashMap::new(),
+                weights: vec![1_f32, 1_f32],
+                payers: HashMap::new(),
+                details: Details::new(),
             };
 
             assert_eq!(expense, expected_expense)
         }
 
+        #[test]
+        fn test_pending_expense_set_tax_and_tip_failure_negative() {
+            let mut pending_expense: PendingExpense = PendingExpense::new("random", 12.34).unwrap();
+
+            assert_eq!(pending_expense.set_tax(-1.00), Err(PendingExpenseError));
+            assert_eq!(pending_expense.set_tip(-1.00), Err(PendingExpenseError));
+            assert_eq!(pending_expense.set_tax(1.00), Ok(()));
+            assert_eq!(pending_expense.set_tip(2.00), Ok(()));
+        }
+
+        #[test]
+        fn test_pending_expense_add_payer_failure_not_positive() {
+            let mut pending_expense: PendingExpense = PendingExpense::new("random", 12.34).unwrap();
+            let user_one: User = User::new("user_one").unwrap();
+
+            assert_eq!(
+                pending_expense.add_payer(&user_one, 0.00),
+                Err(PendingExpenseError)
+            );
+            assert_eq!(
+                pending_expense.add_payer(&user_one, -5.00),
+                Err(PendingExpenseError)
+            );
+        }
+
+        #[test]
+        fn test_pending_expense_finalize_success_multiple_payers() {
+            let mut pending_expense: PendingExpense = PendingExpense::new("random", 40.00).unwrap();
+            let user_one: User = User::new("user_one").unwrap();
+            let user_two: User = User::new("user_two").unwrap();
+            pending_expense.add_participant(&user_one);
+            pending_expense.add_participant(&user_two);
+            pending_expense.set_tax(3.20).unwrap();
+            pending_expense.set_tip(6.80).unwrap();
+            pending_expense.add_payer(&user_one, 30.00).unwrap();
+            pending_expense.add_payer(&user_two, 15.00).unwrap();
+            pending_expense.add_payer(&user_two, 5.00).unwrap();
+
+            let expense: Expense = pending_expense.finalize().unwrap();
+
+            let mut expected: HashMap<&User, f32> = HashMap::new();
+            expected.insert(&user_one, 30.00);
+            expected.insert(&user_two, 20.00);
+            assert_eq!(expense.payers, expected);
+        }
+
+        #[test]
+        fn test_pending_expense_finalize_failure_payments_do_not_match() {
+            let mut pending_expense: PendingExpense = PendingExpense::new("random", 40.00).unwrap();
+            let user_one: User = User::new("user_one").unwrap();
+            pending_expense.add_participant(&user_one);
+            pending_expense.set_tip(6.80).unwrap();
+            pending_expense.add_payer(&user_one, 40.00).unwrap();
+
+            let maybe_expense: Result<Expense, PendingExpenseError> = pending_expense.finalize();
+            assert_eq!(maybe_expense.unwrap_err(), PendingExpenseError)
+        }
+
         #[test]
         fn test_pending_expense_finalize_failure_no_participants() {
             let pending_expense: PendingExpense = PendingExpense::new("random", 12.34).unwrap();
@@ -174,11 +428,118 @@ mod tests {
             assert!(maybe_expense.is_err());
             assert_eq!(maybe_expense.unwrap_err(), PendingExpenseError)
         }
+
+        #[test]
+        fn test_pending_expense_finalize_failure_presence_and_weights() {
+            let user_one: User = User::new("user_one").unwrap();
+            let user_two: User = User::new("user_two").unwrap();
+            let march: Period = period("2026-03-01", "2026-03-31");
+            let april: Period = period("2026-04-01", "2026-04-30");
+
+            let mut without_period: PendingExpense = PendingExpense::new("power", 90.00).unwrap();
+            without_period.add_participant(&user_one);
+            without_period.set_presence(&user_one, march);
+            assert_eq!(without_period.finalize(), Err(PendingExpenseError));
+
+            let mut not_participating: PendingExpense =
+                PendingExpense::new("power", 90.00).unwrap();
+            not_participating.add_participant(&user_one);
+            not_participating.set_billing_period(march);
+            not_participating.set_presence(&user_two, march);
+            assert_eq!(not_participating.finalize(), Err(PendingExpenseError));
+
+            let mut nobody_present: PendingExpense = PendingExpense::new("power", 90.00).unwrap();
+            nobody_present.add_participant(&user_one);
+            nobody_present.set_billing_period(march);
+            nobody_present.set_presence(&user_one, april);
+            assert_eq!(nobody_present.finalize(), Err(PendingExpenseError));
+
+            let mut weighted: PendingExpense = PendingExpense::new("rent", 900.00).unwrap();
+            weighted.add_participant(&user_one);
+            assert_eq!(
+                weighted.set_weight(&user_one, -1_f32),
+                Err(PendingExpenseError)
+            );
+            weighted.set_weight(&user_two, 2_f32).unwrap();
+            assert_eq!(weighted.finalize(), Err(PendingExpenseError));
+        }
+    }
+
+    fn period(start: &str, end: &str) -> Period {
+        let parse = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        Period::new(parse(start), parse(end)).unwrap()
     }
 
     mod test_expense {
         use super::*;
 
+        #[test]
+        fn test_calculate_split_by_income_on_date() {
+            let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+            let mut user_one: User = User::new("user_one").unwrap();
+            let mut user_two: User = User::new("user_two").unwrap();
+            user_one.set_income(6000.00, None).unwrap();
+            user_two.set_income(4000.00, None).unwrap();
+            user_two
+                .set_income(6000.00, Some(date("2026-06-01")))
+                .unwrap();
+
+            let split = |on: &str| {
+                let mut pending_expense: PendingExpense =
+                    PendingExpense::new("groceries", 100.00).unwrap();
+                pending_expense.add_participant(&user_one);
+                pending_expense.add_participant(&user_two);
+                pending_expense.split_by_income(date(on));
+                let amounts: HashMap<&User, f32> = pending_expense
+                    .finalize()
+                    .unwrap()
+                    .calculate()
+                    .get_user_to_amount();
+                (amounts[&user_one], amounts[&user_two])
+            };
+            assert_eq!(split("2026-05-31"), (60_f32, 40_f32));
+            assert_eq!(split("2026-06-01"), (50_f32, 50_f32));
+
+            let user_three: User = User::new("user_three").unwrap();
+            let mut pending_expense: PendingExpense = PendingExpense::new("power", 90.00).unwrap();
+            pending_expense.add_participant(&user_one);
+            pending_expense.add_participant(&user_three);
+            pending_expense.split_by_income(date("2026-05-31"));
+            assert_eq!(pending_expense.finalize(), Err(PendingExpenseError));
+        }
+
+        #[test]
+        fn test_calculate_prorated_by_person_days() {
+            let mut pending_expense: PendingExpense =
+                PendingExpense::new("electricity", 300.00).unwrap();
+            let user_one: User = User::new("user_one").unwrap();
+            let user_two: User = User::new("user_two").unwrap();
+            let user_three: User = User::new("user_three").unwrap();
+            pending_expense.add_participant(&user_one);
+            pending_expense.add_participant(&user_two);
+            pending_expense.add_participant(&user_three);
+            pending_expense.set_tax(5.10).unwrap();
+            pending_expense.set_billing_period(period("2026-03-01", "2026-03-31"));
+            // Moved in on the 17th, and moved out on the 5th.
+            pending_expense.set_presence(&user_two, period("2026-03-17", "2026-06-30"));
+            pending_expense.set_presence(&user_three, period("2026-02-20", "2026-03-05"));
+
+            let calculated_expense: CalculatedExpense =
+                pending_expense.finalize().unwrap().calculate();
+
+            let mut expected: HashMap<&User, f32> = HashMap::new();
+            expected.insert(&user_one, 182.35);
+            expected.insert(&user_two, 88.24);
+            expected.insert(&user_three, 29.41);
+            assert_eq!(calculated_expense.user_to_amount, expected);
+
+            let mut expected_tax: HashMap<&User, f32> = HashMap::new();
+            expected_tax.insert(&user_one, 3.10);
+            expected_tax.insert(&user_two, 1.50);
+            expected_tax.insert(&user_three, 0.50);
+            assert_eq!(calculated_expense.user_to_tax, expected_tax);
+        }
+
         #[test]
         fn test_calculate() {
             let mut pending_expense: PendingExpense = PendingExpense::new("random", 12.50).unwrap();
@@ -187,14 +548,60 @@ mod tests {
             pending_expense.add_participant(&user_one);
             pending_expense.add_participant(&user_two);
 
-            let mut expense: Expense = pending_expense.finalize().unwrap();
-            expense.calculate();
+            let expense: Expense = pending_expense.finalize().unwrap();
+            let calculated_expense: CalculatedExpense = expense.calculate();
 
             let mut expected: HashMap<&User, f32> = HashMap::new();
             expected.insert(&user_one, 6.25);
             expected.insert(&user_two, 6.25);
 
-            assert_eq!(expense.user_to_amount, expected);
+            assert_eq!(calculated_expense.user_to_amount, expected);
+            assert_eq!(calculated_expense.amount, 12.50);
+        }
+
+        #[test]
+        fn test_calculate_keeps_every_cent() {
+            let mut pending_expense: PendingExpense = PendingExpense::new("random", 10.00).unwrap();
+            let user_one: User = User::new("user_one").unwrap();
+            let user_two: User = User::new("user_two").unwrap();
+            let user_three: User = User::new("user_three").unwrap();
+            pending_expense.add_participant(&user_one);
+            pending_expense.add_participant(&user_two);
+            pending_expense.add_participant(&user_three);
+            pending_expense.set_tax(1.00).unwrap();
+
+            let calculated_expense: CalculatedExpense =
+                pending_expense.finalize().unwrap().calculate();
+
+            let amount_cents: i64 = calculated_expense
+                .user_to_amount
+                .values()
+                .map(|v| v.to_cents())
+                .sum();
+            let tax_cents: i64 = calculated_expense
+                .user_to_tax
+                .values()
+                .map(|v| v.to_cents())
+                .sum();
+            assert_eq!(amount_cents, 1000);
+            assert_eq!(tax_cents, 100);
+        }
+
+        #[test]
+        fn test_calculate_carries_payers() {
+            let mut pending_expense: PendingExpense = PendingExpense::new("random", 48.00).unwrap();
+            let user_one: User = User::new("user_one").unwrap();
+            let user_two: User = User::new("user_two").unwrap();
+            pending_expense.add_participant(&user_one);
+            pending_expense.add_participant(&user_two);
+            pending_expense.add_payer(&user_one, 48.00).unwrap();
+
+            let calculated_expense: CalculatedExpense =
+                pending_expense.finalize().unwrap().calculate();
+
+            let mut expected: HashMap<&User, f32> = HashMap::new();
+            expected.insert(&user_one, 48.00);
+            assert_eq!(calculated_expense.user_to_paid, expected);
         }
     }
 }
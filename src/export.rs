@@ -0,0 +1,493 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::group::Group;
+use crate::ledger::LedgerEntry;
+use crate::rounding::{allocate, from_cents, Round};
+
+#[derive(Debug, PartialEq)]
+pub struct ExportError;
+
+/// The plain-text accounting tools a ledger can be exported for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    Ledger,
+    Hledger,
+    Beancount,
+}
+
+/// Which accounts postings go to. Every field has a default, so a config file only needs the
+/// ones that differ.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Accounts {
+    /// Parent of the category accounts, e.g. `Expenses:Shared`.
+    pub expenses: String,
+    /// Category for entries with no category of their own and not listed in `categories`.
+    pub default_category: String,
+    /// Category per entry name or per group category, e.g. `{"groceries": "Food:Groceries"}`.
+    /// Names are matched ignoring case, and entry names are tried first.
+    pub categories: BTreeMap<String, String>,
+    /// Parent of what each member owes you, e.g. `Assets:Receivable:Bob`.
+    pub receivable: String,
+    /// Parent of what you owe each member, e.g. `Liabilities:Payable:Bob`.
+    pub payable: String,
+    /// Where your own payments come from and repayments go to.
+    pub funds: String,
+    pub currency: String,
+}
+
+/// One balanced transaction, amounts in cents.
+struct Transaction {
+    date: NaiveDate,
+    title: String,
+    tags: Vec<String>,
+    notes: Option<String>,
+    postings: Vec<(String, i64)>,
+}
+
+impl Dialect {
+    pub fn new(name: &str) -> Result<Self, ExportError> {
+        match name.to_lowercase().as_str() {
+            "ledger" => Ok(Dialect::Ledger),
+            "hledger" => Ok(Dialect::Hledger),
+            "beancount" => Ok(Dialect::Beancount),
+            _ => Err(ExportError),
+        }
+    }
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Accounts {
+            expenses: "Expenses".to_string(),
+            default_category: "Shared".to_string(),
+            categories: BTreeMap::new(),
+            receivable: "Assets:Receivable".to_string(),
+            payable: "Liabilities:Payable".to_string(),
+            funds: "Assets:Cash".to_string(),
+            currency: "USD".to_string(),
+        }
+    }
+}
+
+impl Accounts {
+    fn expense(&self, entry: &LedgerEntry) -> String {
+        let mapped = |key: &str| {
+            self.categories
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, c)| c.clone())
+        };
+        let category: String = mapped(entry.get_name())
+            .or_else(|| {
+                let category: &str = entry.get_details().get_category()?;
+                mapped(category).or_else(|| {
+                    Some(
+                        category
+                            .split(':')
+                            .map(account_name)
+                            .collect::<Vec<String>>()
+                            .join(":"),
+                    )
+                })
+            })
+            .unwrap_or_else(|| self.default_category.clone());
+        format!("{}:{}", self.expenses, category)
+    }
+
+    fn receivable(&self, member: &str) -> String {
+        format!("{}:{}", self.receivable, account_name(member))
+    }
+
+    fn payable(&self, member: &str) -> String {
+        format!("{}:{}", self.payable, account_name(member))
+    }
+}
+
+/// `member`'s side of the group's ledger as double-entry transactions. Their share of each
+/// expense goes to its category account, what they paid comes out of `funds`, and the
+/// difference is owed to or by the other members in proportion to what each of them is
+/// owed or owes for that expense. Settlements move money between `funds` and those accounts.
+/// Amounts are exact to the cent and every transaction balances.
+pub fn export(
+    group: &Group,
+    member: &str,
+    dialect: Dialect,
+    accounts: &Accounts,
+) -> Result<String, ExportError> {
+    let member: &str = group
+        .find_member(member)
+        .map_err(|_| ExportError)?
+        .get_name();
+    let mut transactions: Vec<Transaction> = Vec::new();
+
+    for entry in group.get_ledger().get_entries() {
+        let cents = |amounts: &BTreeMap<String, f32>, name: &str| {
+            amounts.get(name).map_or(0, |a| a.to_cents())
+        };
+        let share: i64 = cents(entry.get_consumed(), member);
+        let paid: i64 = cents(entry.get_paid(), member);
+        if share == 0 && paid == 0 {
+            continue;
+        }
+
+        let mut postings: Vec<(String, i64)> = vec![
+            (accounts.expense(entry), share),
+            (accounts.funds.clone(), -paid),
+        ];
+        let owed: i64 = paid - share;
+        let others: BTreeSet<&String> = entry
+            .get_paid()
+            .keys()
+            .chain(entry.get_consumed().keys())
+            .filter(|name| *name != member)
+            .collect();
+        // Whoever is on the other side of `owed`: debtors if the member is owed, creditors
+        // if the member owes.
+        let counterparts: Vec<(&String, i64)> = others
+            .into_iter()
+            .map(|name| {
+                (
+                    name,
+                    cents(entry.get_paid(), name) - cents(entry.get_consumed(), name),
+                )
+            })
+            .filter(|(_, net)| net.signum() == -owed.signum() && *net != 0)
+            .collect();
+        let weights: Vec<f32> = counterparts
+            .iter()
+            .map(|(_, net)| from_cents(net.abs()))
+            .collect();
+        let amounts: Vec<f32> = allocate(from_cents(owed.abs()), &weights);
+        for ((name, _), amount) in counterparts.iter().zip(amounts) {
+            if owed > 0 {
+                postings.push((accounts.receivable(name), amount.to_cents()));
+            } else {
+                postings.push((accounts.payable(name), -amount.to_cents()));
+            }
+        }
+
+        transactions.push(Transaction {
+            date: entry.get_date(),
+            title: entry.get_name().to_string(),
+            tags: entry.get_details().get_tags().iter().cloned().collect(),
+            notes: entry.get_details().get_notes().map(|n| n.to_string()),
+            postings,
+        });
+    }
+
+    for settlement in group.get_ledger().get_settlements() {
+        let amount: i64 = settlement.get_amount().to_cents();
+        let postings: Vec<(String, i64)> = if settlement.get_from() == member {
+            vec![
+                (accounts.payable(settlement.get_to()), amount),
+                (accounts.funds.clone(), -amount),
+            ]
+        } else if settlement.get_to() == member {
+            vec![
+                (accounts.funds.clone(), amount),
+                (accounts.receivable(settlement.get_from()), -amount),
+            ]
+        } else {
+            continue;
+        };
+        transactions.push(Transaction {
+            date: settlement.get_date(),
+            title: format!("{} paid {}", settlement.get_from(), settlement.get_to()),
+            tags: Vec::new(),
+            notes: None,
+            postings,
+        });
+    }
+
+    for transaction in &mut transactions {
+        transaction.postings.retain(|(_, cents)| *cents != 0);
+    }
+    transactions.sort_by_key(|t| t.date);
+    Ok(render(&transactions, dialect, &accounts.currency))
+}
+
+fn render(transactions: &[Transaction], dialect: Dialect, currency: &str) -> String {
+    let used: BTreeSet<&String> = transactions
+        .iter()
+        .flat_map(|t| t.postings.iter().map(|(account, _)| account))
+        .collect();
+    let mut output: String = String::new();
+
+    match (dialect, transactions.first()) {
+        (Dialect::Beancount, first) => {
+            output.push_str(&format!(
+                "option \"operating_currency\" \"{}\"\n\n",
+                currency
+            ));
+            if let Some(first) = first {
+                for account in &used {
+                    output.push_str(&format!("{} open {} {}\n", first.date, account, currency));
+                }
+            }
+        }
+        (Dialect::Ledger | Dialect::Hledger, _) => {
+            output.push_str(&format!("commodity {}\n", currency));
+            for account in &used {
+                output.push_str(&format!("account {}\n", account));
+            }
+        }
+    }
+
+    for transaction in transactions {
+        output.push('\n');
+        match dialect {
+            Dialect::Ledger => output.push_str(&format!(
+                "{} * {}\n",
+                transaction.date.format("%Y/%m/%d"),
+                description(&transaction.title)
+            )),
+            Dialect::Hledger => output.push_str(&format!(
+                "{} * {}\n",
+                transaction.date,
+                description(&transaction.title)
+            )),
+            Dialect::Beancount => output.push_str(&format!(
+                "{} * {}{}\n",
+                transaction.date,
+                quote(&transaction.title),
+                transaction
+                    .tags
+                    .iter()
+                    .map(|t| format!(" #{}", t))
+                    .collect::<String>()
+            )),
+        }
+        render_details(&mut output, transaction, dialect);
+        for (account, cents) in &transaction.postings {
+            output.push_str(&format!(
+                "    {:<40}  {:>10} {}\n",
+                account,
+                amount(*cents),
+                currency
+            ));
+        }
+    }
+    output
+}
+
+/// Tags and notes go in comments for ledger and hledger, which read `:tag:` and `tag:` in
+/// comments as tags. Beancount tags are on the transaction line and notes are metadata.
+fn render_details(output: &mut String, transaction: &Transaction, dialect: Dialect) {
+    let notes: Vec<&str> = transaction
+        .notes
+        .iter()
+        .flat_map(|n| n.lines())
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    match dialect {
+        Dialect::Ledger | Dialect::Hledger => {
+            if !transaction.tags.is_empty() {
+                let tags: String = match dialect {
+                    Dialect::Ledger => format!(":{}:", transaction.tags.join(":")),
+                    _ => transaction
+                        .tags
+                        .iter()
+                        .map(|t| format!("{}:", t))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                };
+                output.push_str(&format!("    ; {}\n", tags));
+            }
+            // Otherwise a note such as `Note: ...` would be read as a tag.
+            for line in notes {
+                output.push_str(&format!("    ; {}\n", line.replace(": ", " - ")));
+            }
+        }
+        Dialect::Beancount if !notes.is_empty() => {
+            output.push_str(&format!("  note: {}\n", quote(&notes.join(" "))));
+        }
+        Dialect::Beancount => {}
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// ledger and hledger end the description at a `;`, which starts a comment.
+fn description(title: &str) -> String {
+    title.replace(';', ",").replace(['\n', '\r'], " ")
+}
+
+fn amount(cents: i64) -> String {
+    let sign: &str = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+/// Beancount account components start with a capital letter or digit and hold only letters,
+/// digits and hyphens, so `mary jane` becomes `Mary-Jane`.
+fn account_name(member: &str) -> String {
+    let words: Vec<String> = member
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if words.is_empty() {
+        return "Member".to_string();
+    }
+    words.join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::process::{Command, Output};
+
+    use crate::details::Details;
+    use crate::settlement::Settlement;
+    use crate::user::User;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, day).unwrap()
+    }
+
+    fn entry(name: &str, day: u32, paid: &[(&str, f32)], consumed: &[(&str, f32)]) -> LedgerEntry {
+        let amounts = |amounts: &[(&str, f32)]| -> BTreeMap<String, f32> {
+            amounts.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+        };
+        LedgerEntry::new(name, date(day), amounts(paid), amounts(consumed)).unwrap()
+    }
+
+    fn trip() -> Group {
+        let mut group: Group = Group::new("Trip").unwrap();
+        for name in ["alice", "bob", "carol"] {
+            group.add_member(User::new(name).unwrap()).unwrap();
+        }
+        let entries: [LedgerEntry; 3] = [
+            entry(
+                "Dinner",
+                5,
+                &[("alice", 60.00)],
+                &[("alice", 20.00), ("bob", 20.00), ("carol", 20.00)],
+            ),
+            entry(
+                "Groceries",
+                6,
+                &[("bob", 31.00)],
+                &[("alice", 15.50), ("bob", 15.50)],
+            ),
+            entry(
+                "Taxi; airport",
+                7,
+                &[("alice", 10.00), ("carol", 20.00)],
+                &[("alice", 10.00), ("bob", 10.00), ("carol", 10.00)],
+            ),
+        ];
+        for mut entry in entries {
+            if entry.get_name().starts_with("Taxi") {
+                let mut details: Details = Details::new();
+                details
+                    .set_category(group.get_taxonomy(), "transport")
+                    .unwrap();
+                details.add_tag("work").unwrap();
+                details.add_tag("berlin").unwrap();
+                details.set_notes("Receipt: in the shared folder").unwrap();
+                entry.set_details(details);
+            }
+            group.get_ledger_mut().record_entry(entry);
+        }
+        let roster: Vec<User> = group.get_roster().to_vec();
+        for (from, to, amount) in [(1, 0, 4.50), (2, 1, 5.00)] {
+            let settlement: Settlement =
+                Settlement::new(&roster[from], &roster[to], amount, date(8)).unwrap();
+            group.get_ledger_mut().record_settlement(settlement);
+        }
+        group
+    }
+
+    fn accounts() -> Accounts {
+        serde_json::from_str(r#"{"categories": {"groceries": "Food:Groceries"}}"#).unwrap()
+    }
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn compare(dialect: Dialect, fixture_name: &str) {
+        let exported: String = export(&trip(), "alice", dialect, &accounts()).unwrap();
+        assert_eq!(
+            exported,
+            std::fs::read_to_string(fixture(fixture_name)).unwrap()
+        );
+    }
+
+    /// Runs `checker` on the fixture. These tests are ignored by default since they need the
+    /// tool installed; run them with `cargo test -- --ignored`.
+    fn check(fixture_name: &str, checker: &[&str]) {
+        let output: Output = Command::new(checker[0])
+            .args(&checker[1..])
+            .arg(Path::new(&fixture(fixture_name)))
+            .output()
+            .unwrap_or_else(|_| panic!("{} is not installed", checker[0]));
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn test_export_ledger() {
+        compare(Dialect::Ledger, "alice.ledger");
+    }
+
+    #[test]
+    fn test_export_hledger() {
+        compare(Dialect::Hledger, "alice.journal");
+    }
+
+    #[test]
+    fn test_export_beancount() {
+        compare(Dialect::Beancount, "alice.beancount");
+    }
+
+    #[test]
+    #[ignore = "needs ledger installed"]
+    fn test_ledger_accepts_export() {
+        check("alice.ledger", &["ledger", "--pedantic", "balance", "-f"]);
+    }
+
+    #[test]
+    #[ignore = "needs hledger installed"]
+    fn test_hledger_accepts_export() {
+        check("alice.journal", &["hledger", "check", "-s", "-f"]);
+    }
+
+    #[test]
+    #[ignore = "needs bean-check installed"]
+    fn test_bean_check_accepts_export() {
+        check("alice.beancount", &["bean-check"]);
+    }
+
+    #[test]
+    fn test_export_failure_unknown_member() {
+        assert_eq!(
+            export(&trip(), "dave", Dialect::Ledger, &Accounts::default()),
+            Err(ExportError)
+        );
+    }
+
+    #[test]
+    fn test_account_name() {
+        assert_eq!(account_name("mary jane"), "Mary-Jane");
+        assert_eq!(account_name("o'brien"), "O-Brien");
+        assert_eq!(account_name("??"), "Member");
+    }
+}
@@ -0,0 +1,281 @@
+//! A C ABI over groups and bills, declared in `include/heesab.h`. Groups and bills are opaque
+//! handles that must be freed with their `_free` function, and results are JSON strings that
+//! must be freed with `heesab_string_free`. Functions that return an `int` return 0 on
+//! success and -1 on failure, and functions that return a pointer return null on failure.
+//! `heesab_last_error` then says why.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+use crate::bindings::{self, Bill, BindingsError};
+use crate::group::Group;
+use crate::user::User;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Why the last call on this thread failed, or null. Owned by heesab, do not free it.
+#[no_mangle]
+pub extern "C" fn heesab_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// # Safety
+///
+/// `name` must be a valid, NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_group_new(name: *const c_char) -> *mut Group {
+    let created: Result<Group, BindingsError> = read(name)
+        .and_then(|name| Group::new(name).map_err(|_| error("group names must not be empty")));
+    match created {
+        Ok(group) => Box::into_raw(Box::new(group)),
+        Err(e) => fail(e, ptr::null_mut()),
+    }
+}
+
+/// # Safety
+///
+/// `group` must come from `heesab_group_new` and `name` must be a valid, NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_group_add_member(group: *mut Group, name: *const c_char) -> c_int {
+    let added: Result<(), BindingsError> = handle_mut(group).and_then(|group| {
+        let user: User = User::new(read(name)?).map_err(|_| error("names must not be empty"))?;
+        group
+            .add_member(user)
+            .map_err(|_| error("a member with that name already exists"))
+    });
+    status(added)
+}
+
+/// Names a set of members, such as `kids`, for use in selections. See `Group::add_alias`.
+///
+/// # Safety
+///
+/// `group` must come from `heesab_group_new`, and `alias` and `members` must be valid,
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_group_add_alias(
+    group: *mut Group,
+    alias: *const c_char,
+    members: *const c_char,
+) -> c_int {
+    let added: Result<(), BindingsError> = handle_mut(group).and_then(|group| {
+        group
+            .add_alias(read(alias)?, read(members)?)
+            .map_err(|_| error("the alias must select members and not be a member's name"))
+    });
+    status(added)
+}
+
+/// # Safety
+///
+/// `group` must come from `heesab_group_new` or be null, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_group_free(group: *mut Group) {
+    if !group.is_null() {
+        drop(Box::from_raw(group));
+    }
+}
+
+/// Starts an empty bill among the group's current members. The bill does not refer back to
+/// the group, so either can be freed first.
+///
+/// # Safety
+///
+/// `group` must come from `heesab_group_new`.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_new(group: *const Group) -> *mut Bill {
+    let group: Option<&Group> = group.as_ref();
+    match group
+        .ok_or_else(|| error("the group is null"))
+        .and_then(Bill::new)
+    {
+        Ok(bill) => Box::into_raw(Box::new(bill)),
+        Err(e) => fail(e, ptr::null_mut()),
+    }
+}
+
+/// `participants` is a selection such as `all except bob`.
+///
+/// # Safety
+///
+/// `split` must come from `heesab_split_new`, and `name` and `participants` must be valid,
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_add_expense(
+    split: *mut Bill,
+    name: *const c_char,
+    amount: f32,
+    participants: *const c_char,
+) -> c_int {
+    let added: Result<(), BindingsError> = handle_mut(split)
+        .and_then(|bill| bill.add_expense(read(name)?, amount, read(participants)?));
+    status(added)
+}
+
+/// # Safety
+///
+/// `split` must come from `heesab_split_new`.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_set_tax_and_tip(
+    split: *mut Bill,
+    tax: f32,
+    tip: f32,
+) -> c_int {
+    status(handle_mut(split).and_then(|bill| bill.set_tax_and_tip(tax, tip)))
+}
+
+/// # Safety
+///
+/// `split` must come from `heesab_split_new` and `member` must be a valid, NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_add_payer(
+    split: *mut Bill,
+    member: *const c_char,
+    amount: f32,
+) -> c_int {
+    status(handle_mut(split).and_then(|bill| bill.add_payer(read(member)?, amount)))
+}
+
+/// The breakdown and suggested settlements as JSON, see `bindings::split_json`.
+///
+/// # Safety
+///
+/// `split` must come from `heesab_split_new`.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_result(split: *const Bill) -> *mut c_char {
+    let bill: Option<&Bill> = split.as_ref();
+    json(
+        bill.ok_or_else(|| error("the split is null"))
+            .and_then(|bill| bill.to_json()),
+    )
+}
+
+/// # Safety
+///
+/// `split` must come from `heesab_split_new` or be null, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_free(split: *mut Bill) {
+    if !split.is_null() {
+        drop(Box::from_raw(split));
+    }
+}
+
+/// Splits a whole bill given as JSON in one call, see `bindings::split_json`.
+///
+/// # Safety
+///
+/// `input` must be a valid, NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_split_json(input: *const c_char) -> *mut c_char {
+    json(read(input).and_then(bindings::split_json))
+}
+
+/// # Safety
+///
+/// `string` must have been returned by heesab or be null, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn heesab_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+fn error(message: &str) -> BindingsError {
+    BindingsError::new(message)
+}
+
+fn fail<T>(e: BindingsError, failed: T) -> T {
+    let message: CString = CString::new(e.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    failed
+}
+
+fn status(result: Result<(), BindingsError>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(e) => fail(e, -1),
+    }
+}
+
+fn json(result: Result<String, BindingsError>) -> *mut c_char {
+    match result.and_then(|json| CString::new(json).map_err(|_| error("invalid output"))) {
+        Ok(json) => json.into_raw(),
+        Err(e) => fail(e, ptr::null_mut()),
+    }
+}
+
+unsafe fn read<'a>(string: *const c_char) -> Result<&'a str, BindingsError> {
+    if string.is_null() {
+        return Err(error("a string argument is null"));
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| error("strings must be UTF-8"))
+}
+
+unsafe fn handle_mut<'a, T>(handle: *mut T) -> Result<&'a mut T, BindingsError> {
+    handle.as_mut().ok_or_else(|| error("the handle is null"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(heesab_last_error()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_group_and_split() {
+        unsafe {
+            let group: *mut Group = heesab_group_new(c"Dinner".as_ptr());
+            assert_eq!(heesab_group_add_member(group, c"alice".as_ptr()), 0);
+            assert_eq!(heesab_group_add_member(group, c"bob".as_ptr()), 0);
+            assert_eq!(heesab_group_add_member(group, c"Bob".as_ptr()), -1);
+            assert_eq!(last_error(), "a member with that name already exists");
+
+            let split: *mut Bill = heesab_split_new(group);
+            heesab_group_free(group);
+            assert_eq!(
+                heesab_split_add_expense(split, c"pizza".as_ptr(), 10.00, c"all".as_ptr()),
+                0
+            );
+            assert_eq!(heesab_split_set_tax_and_tip(split, 0.00, 2.00), 0);
+            assert_eq!(heesab_split_add_payer(split, c"alice".as_ptr(), 12.00), 0);
+
+            let result: *mut c_char = heesab_split_result(split);
+            let json: &str = CStr::from_ptr(result).to_str().unwrap();
+            assert!(json.contains(r#""settlements":[{"from":"bob","to":"alice","amount":6.0}]"#));
+            heesab_string_free(result);
+            heesab_split_free(split);
+        }
+    }
+
+    #[test]
+    fn test_split_json_with_weights() {
+        let input: &CStr = cr#"{"users": [{"name": "alice"}, {"name": "bob"}],
+            "expenses": [{"name": "rent", "amount": 30.0, "participants": ["alice", "bob"],
+                "weights": {"alice": 2.0}}],
+            "payers": {"bob": 30.0}}"#;
+        unsafe {
+            let result: *mut c_char = heesab_split_json(input.as_ptr());
+            let json: &str = CStr::from_ptr(result).to_str().unwrap();
+            assert!(json.contains(r#""settlements":[{"from":"alice","to":"bob","amount":20.0}]"#));
+            heesab_string_free(result);
+        }
+    }
+
+    #[test]
+    fn test_split_json_failure() {
+        let result: *mut c_char = unsafe { heesab_split_json(c"{}".as_ptr()) };
+        assert!(result.is_null());
+        assert!(last_error().starts_with("invalid input"));
+    }
+}
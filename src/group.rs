@@ -0,0 +1,413 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::recurring::RecurringExpense;
+use crate::rent::RentPlan;
+use crate::selection::{self, SelectionError};
+use crate::taxonomy::Taxonomy;
+use crate::user::User;
+
+#[derive(Debug, PartialEq)]
+pub struct GroupError;
+
+/// A named set of people who split bills together, such as "Roommates", along with the
+/// ledger of everything they have recorded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    name: String,
+    roster: Vec<User>,
+    #[serde(default)]
+    aliases: BTreeMap<String, Vec<String>>,
+    ledger: Ledger,
+    #[serde(default)]
+    taxonomy: Taxonomy,
+    #[serde(default)]
+    recurring: Vec<RecurringExpense>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rent: Option<RentPlan>,
+    #[serde(default)]
+    version: u64,
+}
+
+impl Group {
+    pub fn new(name: &str) -> Result<Self, GroupError> {
+        if name.is_empty() {
+            return Err(GroupError);
+        }
+
+        Ok(Group {
+            name: name.to_string(),
+            roster: Vec::new(),
+            aliases: BTreeMap::new(),
+            ledger: Ledger::new(),
+            taxonomy: Taxonomy::default(),
+            recurring: Vec::new(),
+            rent: None,
+            version: 0,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) -> Result<(), GroupError> {
+        if name.is_empty() {
+            return Err(GroupError);
+        }
+        self.name = name.to_string();
+        Ok(())
+    }
+
+    /// Counts changes to the group, so that a change based on an outdated copy can be
+    /// detected and refused.
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn bump_version(&mut self) {
+        self.version += 1;
+    }
+
+    /// Keeps the version from going back from `saved`'s, a saved copy of the group, and moves
+    /// it past if the group differs, so changes made without bumping the version still
+    /// invalidate outdated copies.
+    pub(crate) fn advance_version(&mut self, saved: &Group) {
+        let changed: bool = Group {
+            version: saved.version,
+            ..self.clone()
+        } != *saved;
+        let least: u64 = if changed {
+            saved.version + 1
+        } else {
+            saved.version
+        };
+        self.version = self.version.max(least);
+    }
+
+    /// Names are unique within a group, ignoring case.
+    pub fn add_member(&mut self, user: User) -> Result<(), GroupError> {
+        if self
+            .roster
+            .iter()
+            .any(|u| u.get_name().eq_ignore_ascii_case(user.get_name()))
+        {
+            return Err(GroupError);
+        }
+        self.roster.push(user);
+        Ok(())
+    }
+
+    /// Also drops the member from any aliases, and drops aliases left with nobody in them.
+    /// Fails if the member pays or shares in a recurring expense or lives in a room of the
+    /// rent plan, which would otherwise keep charging them.
+    pub fn remove_member(&mut self, reference: &str) -> Result<User, GroupError> {
+        let index: usize =
+            selection::resolve_one(&self.roster, reference).map_err(|_| GroupError)?;
+        let name: &str = self.roster[index].get_name();
+        if self.in_recurring(name) || self.in_rent_plan(name) {
+            return Err(GroupError);
+        }
+        let user: User = self.roster.remove(index);
+        for members in self.aliases.values_mut() {
+            members.retain(|name| name != user.get_name());
+        }
+        self.aliases.retain(|_, members| !members.is_empty());
+        Ok(user)
+    }
+
+    pub fn get_roster(&self) -> &[User] {
+        &self.roster
+    }
+
+    /// Looks up a member by name, number, initials or unique prefix. See `selection::select`.
+    pub fn find_member(&self, reference: &str) -> Result<&User, GroupError> {
+        selection::select_one(&self.roster, reference).map_err(|_| GroupError)
+    }
+
+    pub fn find_member_mut(&mut self, reference: &str) -> Result<&mut User, GroupError> {
+        let index: usize =
+            selection::resolve_one(&self.roster, reference).map_err(|_| GroupError)?;
+        Ok(&mut self.roster[index])
+    }
+
+    /// Names a set of members, such as "kids", so they can be selected together. The alias
+    /// cannot shadow a member's name or `all`.
+    pub fn add_alias(&mut self, alias: &str, members: &str) -> Result<(), GroupError> {
+        let alias: &str = alias.trim();
+        if alias.is_empty()
+            || alias.eq_ignore_ascii_case("all")
+            || self
+                .roster
+                .iter()
+                .any(|u| u.get_name().eq_ignore_ascii_case(alias))
+        {
+            return Err(GroupError);
+        }
+
+        let selected: Vec<&User> =
+            selection::select(&self.roster, &BTreeMap::new(), members).map_err(|_| GroupError)?;
+        let names: Vec<String> = selected.iter().map(|u| u.get_name().to_string()).collect();
+        self.aliases.insert(alias.to_string(), names);
+        Ok(())
+    }
+
+    pub fn get_aliases(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.aliases
+    }
+
+    /// Resolves a selection such as `all except bob` against this group's roster and aliases.
+    pub fn select(&self, input: &str) -> Result<Vec<&User>, SelectionError> {
+        selection::select(&self.roster, &self.aliases, input)
+    }
+
+    pub fn get_ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn get_ledger_mut(&mut self) -> &mut Ledger {
+        &mut self.ledger
+    }
+
+    /// The categories expenses are filed under. Groups start with `Taxonomy::default`.
+    pub fn get_taxonomy(&self) -> &Taxonomy {
+        &self.taxonomy
+    }
+
+    /// Entries already filed under a category keep it, even if it is no longer listed.
+    pub fn set_taxonomy(&mut self, taxonomy: Taxonomy) {
+        self.taxonomy = taxonomy;
+    }
+
+    pub fn get_recurring(&self) -> &[RecurringExpense] {
+        &self.recurring
+    }
+
+    /// Names are unique within a group, ignoring case. Fails if the payer or a participant is
+    /// not a member.
+    pub fn add_recurring(&mut self, recurring: RecurringExpense) -> Result<(), GroupError> {
+        let is_member = |name: &str| self.roster.iter().any(|u| u.get_name() == name);
+        if self
+            .recurring
+            .iter()
+            .any(|r| r.get_name().eq_ignore_ascii_case(recurring.get_name()))
+            || !is_member(recurring.get_payer())
+            || !recurring.get_weights().keys().all(|name| is_member(name))
+        {
+            return Err(GroupError);
+        }
+        self.recurring.push(recurring);
+        Ok(())
+    }
+
+    /// Looks up a recurring expense by name, ignoring case, or by its 1-based number.
+    pub fn find_recurring_mut(
+        &mut self,
+        reference: &str,
+    ) -> Result<&mut RecurringExpense, GroupError> {
+        let index: usize = self.find_recurring_index(reference)?;
+        Ok(&mut self.recurring[index])
+    }
+
+    /// Instances already recorded stay in the ledger.
+    pub fn remove_recurring(&mut self, reference: &str) -> Result<RecurringExpense, GroupError> {
+        let index: usize = self.find_recurring_index(reference)?;
+        Ok(self.recurring.remove(index))
+    }
+
+    /// Records every recurring expense's instances due on or before `until`, and returns
+    /// them. Running again for the same day records nothing.
+    pub fn run_recurring(&mut self, until: NaiveDate) -> Vec<LedgerEntry> {
+        let mut recorded: Vec<LedgerEntry> = Vec::new();
+        for recurring in self.recurring.iter_mut() {
+            recorded.extend(recurring.materialize(until));
+        }
+        recorded.sort_by_key(|e| e.get_date());
+        for entry in &recorded {
+            self.ledger.record_entry(entry.clone());
+        }
+        recorded
+    }
+
+    /// Kept with the group so the rent is split the same way every month.
+    pub fn get_rent_plan(&self) -> Option<&RentPlan> {
+        self.rent.as_ref()
+    }
+
+    /// Fails if an occupant is not a member.
+    pub fn set_rent_plan(&mut self, plan: RentPlan) -> Result<(), GroupError> {
+        if !plan
+            .get_occupants()
+            .iter()
+            .all(|name| self.roster.iter().any(|u| u.get_name() == *name))
+        {
+            return Err(GroupError);
+        }
+        self.rent = Some(plan);
+        Ok(())
+    }
+
+    /// Whether `name` pays or shares in any recurring expense.
+    pub fn in_recurring(&self, name: &str) -> bool {
+        self.recurring
+            .iter()
+            .any(|r| r.get_payer() == name || r.get_weights().contains_key(name))
+    }
+
+    /// Whether `name` lives in a room of the rent plan.
+    pub fn in_rent_plan(&self, name: &str) -> bool {
+        self.rent
+            .as_ref()
+            .is_some_and(|plan| plan.get_occupants().contains(&name))
+    }
+
+    fn find_recurring_index(&self, reference: &str) -> Result<usize, GroupError> {
+        let reference: &str = reference.trim();
+        if let Some(index) = self
+            .recurring
+            .iter()
+            .position(|r| r.get_name().eq_ignore_ascii_case(reference))
+        {
+            return Ok(index);
+        }
+        match reference.parse::<usize>() {
+            Ok(position) if position >= 1 && position <= self.recurring.len() => Ok(position - 1),
+            _ => Err(GroupError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recurring::Schedule;
+
+    fn roommates() -> Group {
+        let mut group: Group = Group::new("Roommates").unwrap();
+        group.add_member(User::new("Alice Smith").unwrap()).unwrap();
+        group.add_member(User::new("Bob").unwrap()).unwrap();
+        group.add_member(User::new("Ben").unwrap()).unwrap();
+        group
+    }
+
+    #[test]
+    fn test_new_failure_name_is_empty() {
+        assert_eq!(Group::new("").unwrap_err(), GroupError);
+    }
+
+    #[test]
+    fn test_add_member_failure_duplicate_name() {
+        let mut group: Group = roommates();
+        let maybe_added: Result<(), GroupError> = group.add_member(User::new("bob").unwrap());
+        assert_eq!(maybe_added, Err(GroupError));
+        assert_eq!(group.get_roster().len(), 3);
+    }
+
+    #[test]
+    fn test_find_member_by_name_initials_and_index() {
+        let group: Group = roommates();
+
+        assert_eq!(
+            group.find_member("alice smith").unwrap().get_name(),
+            "Alice Smith"
+        );
+        assert_eq!(group.find_member("AS").unwrap().get_name(), "Alice Smith");
+        assert_eq!(group.find_member("2").unwrap().get_name(), "Bob");
+    }
+
+    #[test]
+    fn test_find_member_failure() {
+        let group: Group = roommates();
+
+        assert_eq!(group.find_member("b").unwrap_err(), GroupError);
+        assert_eq!(group.find_member("all").unwrap_err(), GroupError);
+        assert_eq!(group.find_member("4").unwrap_err(), GroupError);
+        assert_eq!(group.find_member("carol").unwrap_err(), GroupError);
+    }
+
+    #[test]
+    fn test_add_alias_and_select() {
+        let mut group: Group = roommates();
+        group.add_alias("boys", "bob:ben").unwrap();
+
+        let selected: Vec<&str> = group
+            .select("as, boys")
+            .unwrap()
+            .iter()
+            .map(|u| u.get_name())
+            .collect();
+        assert_eq!(selected, vec!["Alice Smith", "Bob", "Ben"]);
+    }
+
+    #[test]
+    fn test_add_alias_failure_shadows_member() {
+        let mut group: Group = roommates();
+        assert_eq!(group.add_alias("Bob", "1:2"), Err(GroupError));
+        assert_eq!(group.add_alias("all", "1:2"), Err(GroupError));
+    }
+
+    #[test]
+    fn test_remove_member_updates_aliases() {
+        let mut group: Group = roommates();
+        group.add_alias("boys", "bob:ben").unwrap();
+        group.add_alias("just bob", "bob").unwrap();
+        group.remove_member("bob").unwrap();
+
+        let mut expected: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        expected.insert("boys".to_string(), vec!["Ben".to_string()]);
+        assert_eq!(group.get_aliases(), &expected);
+    }
+
+    #[test]
+    fn test_remove_member() {
+        let mut group: Group = roommates();
+        let removed: User = group.remove_member("bob").unwrap();
+
+        assert_eq!(removed.get_name(), "Bob");
+        assert_eq!(group.get_roster().len(), 2);
+    }
+
+    #[test]
+    fn test_remove_member_failure_in_recurring() {
+        let mut group: Group = roommates();
+        let start: NaiveDate = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let alice: User = group.find_member("alice").unwrap().clone();
+        let ben: User = group.find_member("ben").unwrap().clone();
+        let recurring: RecurringExpense = RecurringExpense::new(
+            "Internet",
+            60.00,
+            Schedule::new("monthly", start).unwrap(),
+            start,
+            &alice,
+            &[(&alice, 1_f32), (&ben, 1_f32)],
+        )
+        .unwrap();
+        group.add_recurring(recurring).unwrap();
+
+        assert_eq!(group.remove_member("alice"), Err(GroupError));
+        assert_eq!(group.remove_member("ben"), Err(GroupError));
+        assert_eq!(group.get_roster().len(), 3);
+
+        group.remove_recurring("internet").unwrap();
+        assert_eq!(group.remove_member("ben").unwrap().get_name(), "Ben");
+    }
+
+    #[test]
+    fn test_remove_member_failure_in_rent_plan() {
+        let mut group: Group = roommates();
+        let ben: User = group.find_member("ben").unwrap().clone();
+        let mut plan: RentPlan = RentPlan::new(0.5, &[("area", 1_f32)]).unwrap();
+        plan.add_room("Big", &[("area", 20_f32)], &[&ben]).unwrap();
+        group.set_rent_plan(plan.clone()).unwrap();
+
+        assert_eq!(group.remove_member("ben"), Err(GroupError));
+        assert!(group.in_rent_plan("Ben"));
+
+        plan.assign("Big", &[]).unwrap();
+        group.set_rent_plan(plan).unwrap();
+        assert_eq!(group.remove_member("ben").unwrap().get_name(), "Ben");
+    }
+}
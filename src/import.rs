@@ -0,0 +1,624 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use csv::{ReaderBuilder, StringRecord};
+
+use crate::group::Group;
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::rounding::{self, allocate, from_cents, Round};
+use crate::settlement::Settlement;
+use crate::user::User;
+
+/// Why an export could not be read at all. Rows that cannot be mapped are listed in the
+/// `ImportReport` instead.
+#[derive(Debug, PartialEq)]
+pub struct ImportError(String);
+
+impl ImportError {
+    fn new(message: &str) -> Self {
+        ImportError(message.to_string())
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The expense apps whose CSV exports can be imported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Splitwise's "Export as spreadsheet": one column per person holding what each row did
+    /// to their balance, ending in a `Total balance` row.
+    Splitwise,
+    /// Tricount's export: `Paid by`, `Transaction type` and one `Impacted to <name>` column
+    /// per person holding their share.
+    Tricount,
+}
+
+/// What an import did and did not bring across, for checking before it is saved.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportReport {
+    members: Vec<String>,
+    entries: usize,
+    settlements: usize,
+    unmapped: Vec<String>,
+    notes: Vec<String>,
+    mismatches: Vec<String>,
+    balance_checked: bool,
+}
+
+impl Format {
+    pub fn new(name: &str) -> Result<Self, ImportError> {
+        match name.to_lowercase().as_str() {
+            "splitwise" => Ok(Format::Splitwise),
+            "tricount" => Ok(Format::Tricount),
+            _ => Err(ImportError(format!(
+                "{} is not a supported app, use splitwise or tricount",
+                name
+            ))),
+        }
+    }
+}
+
+impl ImportReport {
+    pub fn get_members(&self) -> &[String] {
+        &self.members
+    }
+
+    pub fn get_entries(&self) -> usize {
+        self.entries
+    }
+
+    pub fn get_settlements(&self) -> usize {
+        self.settlements
+    }
+
+    /// Rows that were left out, and why.
+    pub fn get_unmapped(&self) -> &[String] {
+        &self.unmapped
+    }
+
+    /// Rows that were brought across with an assumption worth checking.
+    pub fn get_notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    /// Members whose imported balance differs from the final balance the export states.
+    pub fn get_mismatches(&self) -> &[String] {
+        &self.mismatches
+    }
+
+    /// Whether the export stated final balances to compare against. Tricount exports and
+    /// Splitwise exports without a `Total balance` row do not.
+    pub fn is_balance_checked(&self) -> bool {
+        self.balance_checked
+    }
+}
+
+/// Reads a CSV export into a new group named `name`, with the export's people as members,
+/// its expenses as ledger entries and its payments as settlements. Amounts are carried over
+/// in cents, so balances match the app's own to the cent when every row could be mapped.
+pub fn import(format: Format, name: &str, csv: &str) -> Result<(Group, ImportReport), ImportError> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+    let headers: StringRecord = reader
+        .headers()
+        .map_err(|e| ImportError(format!("invalid CSV: {}", e)))?
+        .clone();
+    let headers: Vec<&str> = headers
+        .iter()
+        .map(|h| h.trim_start_matches('\u{feff}'))
+        .collect();
+    let mut rows: Vec<StringRecord> = Vec::new();
+    for row in reader.records() {
+        rows.push(row.map_err(|e| ImportError(format!("invalid CSV: {}", e)))?);
+    }
+
+    let mut importer: Importer = match format {
+        Format::Splitwise => Importer::splitwise(&headers)?,
+        Format::Tricount => Importer::tricount(&headers)?,
+    };
+    for (index, row) in rows.iter().enumerate() {
+        // Line 1 is the header.
+        let line: usize = index + 2;
+        if row.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        let imported: Result<(), String> = match format {
+            Format::Splitwise => importer.splitwise_row(row),
+            Format::Tricount => importer.tricount_row(row),
+        };
+        if let Err(reason) = imported {
+            importer
+                .report
+                .unmapped
+                .push(format!("line {}: {}", line, reason));
+        }
+    }
+    importer.finish(name)
+}
+
+/// Collects members, entries and settlements while rows are read, before the group exists.
+struct Importer {
+    columns: Columns,
+    users: Vec<User>,
+    ledger: Ledger,
+    currency: Option<String>,
+    expected_balances: Option<BTreeMap<String, i64>>,
+    report: ImportReport,
+}
+
+enum Columns {
+    Splitwise,
+    Tricount {
+        title: usize,
+        amount: usize,
+        date: usize,
+        paid_by: usize,
+        kind: usize,
+        /// `Impacted to <name>` columns, in the same order as the members.
+        impacted: Vec<usize>,
+        /// `Paid by <name>` columns and their names, in exports with several payers per row.
+        payers: Vec<(usize, String)>,
+    },
+}
+
+impl Importer {
+    fn new(names: Vec<&str>, columns: Columns) -> Result<Self, ImportError> {
+        let mut users: Vec<User> = Vec::new();
+        for name in names {
+            let user: User =
+                User::new(name).map_err(|_| ImportError::new("a person has no name"))?;
+            if users.contains(&user) {
+                return Err(ImportError(format!("{} is listed twice", name)));
+            }
+            users.push(user);
+        }
+        if users.is_empty() {
+            return Err(ImportError::new("the export lists nobody"));
+        }
+        Ok(Importer {
+            columns,
+            report: ImportReport {
+                members: users.iter().map(|u| u.get_name().to_string()).collect(),
+                ..ImportReport::default()
+            },
+            users,
+            ledger: Ledger::new(),
+            currency: None,
+            expected_balances: None,
+        })
+    }
+
+    /// `Date,Description,Category,Cost,Currency,<person>,<person>,...`
+    fn splitwise(headers: &[&str]) -> Result<Self, ImportError> {
+        if headers.len() < 6
+            || headers[..5] != ["Date", "Description", "Category", "Cost", "Currency"]
+        {
+            return Err(ImportError::new(
+                "this is not a Splitwise export, expected Date,Description,Category,Cost,Currency,...",
+            ));
+        }
+        Importer::new(headers[5..].to_vec(), Columns::Splitwise)
+    }
+
+    fn tricount(headers: &[&str]) -> Result<Self, ImportError> {
+        let find = |names: &[&str]| headers.iter().position(|h| names.contains(h));
+        let missing = || {
+            ImportError::new("this is not a Tricount export, expected Title, Amount, Date & time, Paid by, Transaction type and Impacted to columns")
+        };
+        let mut names: Vec<&str> = Vec::new();
+        let mut impacted: Vec<usize> = Vec::new();
+        let mut payers: Vec<(usize, String)> = Vec::new();
+        for (index, header) in headers.iter().enumerate() {
+            if let Some(name) = header.strip_prefix("Impacted to ") {
+                names.push(name);
+                impacted.push(index);
+            } else if let Some(name) = header.strip_prefix("Paid by ") {
+                payers.push((index, name.to_string()));
+            }
+        }
+        let columns: Columns = Columns::Tricount {
+            title: find(&["Title"]).ok_or_else(missing)?,
+            amount: find(&["Amount in default currency", "Amount"]).ok_or_else(missing)?,
+            date: find(&["Date & time", "Date"]).ok_or_else(missing)?,
+            paid_by: find(&["Paid by"]).ok_or_else(missing)?,
+            kind: find(&["Transaction type"]).ok_or_else(missing)?,
+            impacted,
+            payers,
+        };
+        Importer::new(names, columns)
+    }
+
+    fn splitwise_row(&mut self, row: &StringRecord) -> Result<(), String> {
+        let field = |index: usize| row.get(index).unwrap_or_default();
+        let description: &str = field(1);
+        let mut nets: BTreeMap<String, i64> = BTreeMap::new();
+        for (offset, user) in self.users.iter().enumerate() {
+            let net: i64 = parse_cents(field(5 + offset))
+                .ok_or_else(|| format!("{} has an unreadable amount for {}", description, user))?;
+            nets.insert(user.get_name().to_string(), net);
+        }
+
+        if description == "Total balance" {
+            self.expected_balances = Some(nets);
+            return Ok(());
+        }
+
+        let date: NaiveDate = parse_date(field(0))
+            .ok_or_else(|| format!("{} has an unreadable date {}", description, field(0)))?;
+        self.check_currency(description, field(4))?;
+        if nets.values().sum::<i64>() != 0 {
+            return Err(format!("{}'s amounts do not add up to zero", description));
+        }
+
+        let gains: Vec<(&String, i64)> = nets
+            .iter()
+            .filter(|(_, n)| **n > 0)
+            .map(|(k, v)| (k, *v))
+            .collect();
+        let losses: Vec<(&String, i64)> = nets
+            .iter()
+            .filter(|(_, n)| **n < 0)
+            .map(|(k, v)| (k, *v))
+            .collect();
+        if gains.is_empty() {
+            self.report.notes.push(format!(
+                "{} on {} changes nobody's balance, so it was left out",
+                description, date
+            ));
+            return Ok(());
+        }
+
+        if field(2) == "Payment" {
+            return match (gains.as_slice(), losses.as_slice()) {
+                ([(from, cents)], [(to, _)]) => self.settle(from, to, *cents, date),
+                _ => Err(format!(
+                    "{} is a payment between more than two people",
+                    description
+                )),
+            };
+        }
+
+        // The export only says what each row did to each balance. Whoever lost money had
+        // that as their share. Whoever gained money paid, and the rest of the cost is their
+        // share. With several payers, how that rest divides between them is not in the
+        // export, so it is divided equally, which leaves every balance the same.
+        let cost: i64 = parse_cents(field(3))
+            .ok_or_else(|| format!("{} has an unreadable cost", description))?;
+        let consumed_by_others: i64 = losses.iter().map(|(_, n)| -n).sum();
+        let rest: i64 = cost - consumed_by_others;
+        if rest < 0 {
+            return Err(format!("{}'s shares are more than its cost", description));
+        }
+        let payer_shares: Vec<f32> = allocate(from_cents(rest), &vec![1_f32; gains.len()]);
+        if gains.len() > 1 {
+            self.report.notes.push(format!(
+                "{} on {} had several payers, their own shares were assumed equal",
+                description, date
+            ));
+        }
+
+        let mut paid: BTreeMap<String, f32> = BTreeMap::new();
+        let mut consumed: BTreeMap<String, f32> = BTreeMap::new();
+        for ((name, net), share) in gains.iter().zip(payer_shares) {
+            paid.insert(name.to_string(), from_cents(net + share.to_cents()));
+            if share.to_cents() > 0 {
+                consumed.insert(name.to_string(), share);
+            }
+        }
+        for (name, net) in &losses {
+            consumed.insert(name.to_string(), from_cents(-net));
+        }
+        self.record(description, date, paid, consumed)
+    }
+
+    fn tricount_row(&mut self, row: &StringRecord) -> Result<(), String> {
+        let Columns::Tricount {
+            title,
+            amount,
+            date,
+            paid_by,
+            kind,
+            ref impacted,
+            ref payers,
+        } = self.columns
+        else {
+            unreachable!("Tricount rows are only read with Tricount columns");
+        };
+        let field = |index: usize| row.get(index).unwrap_or_default();
+        let title: &str = field(title);
+        let amount: i64 = parse_cents(field(amount))
+            .ok_or_else(|| format!("{} has an unreadable amount", title))?
+            .abs();
+        let date: NaiveDate = parse_date(field(date))
+            .ok_or_else(|| format!("{} has an unreadable date {}", title, field(date)))?;
+        let kind: String = field(kind).to_lowercase();
+
+        let mut shares: BTreeMap<String, i64> = BTreeMap::new();
+        for (column, user) in impacted.iter().zip(&self.users) {
+            let share: i64 = parse_cents(field(*column))
+                .ok_or_else(|| format!("{} has an unreadable share for {}", title, user))?
+                .abs();
+            if share != 0 {
+                shares.insert(user.get_name().to_string(), share);
+            }
+        }
+        if shares.values().sum::<i64>() != amount {
+            return Err(format!("{}'s shares do not add up to its amount", title));
+        }
+
+        let mut paid: BTreeMap<String, i64> = BTreeMap::new();
+        for (column, payer) in payers {
+            let cents: i64 = parse_cents(field(*column))
+                .ok_or_else(|| format!("{} has an unreadable payment by {}", title, payer))?
+                .abs();
+            if cents != 0 {
+                paid.insert(payer.to_string(), cents);
+            }
+        }
+        if paid.is_empty() {
+            paid.insert(field(paid_by).to_string(), amount);
+        } else if paid.values().sum::<i64>() != amount {
+            return Err(format!("{}'s payments do not add up to its amount", title));
+        }
+        for payer in paid.keys() {
+            if !self.users.iter().any(|u| u.get_name() == payer) {
+                return Err(format!(
+                    "{} was paid by {}, who has no column",
+                    title, payer
+                ));
+            }
+        }
+
+        if kind.contains("transfer") || kind.contains("balance") {
+            return match (paid.iter().next(), shares.iter().next()) {
+                (Some((from, _)), Some((to, _))) if paid.len() == 1 && shares.len() == 1 => {
+                    self.settle(from, to, amount, date)
+                }
+                _ => Err(format!(
+                    "{} is a transfer between more than two people",
+                    title
+                )),
+            };
+        }
+
+        let (paid, consumed): (BTreeMap<String, i64>, BTreeMap<String, i64>) =
+            if kind.contains("income") {
+                // Money received on everyone's behalf: the receiver now owes each their share.
+                (shares, paid)
+            } else {
+                (paid, shares)
+            };
+        self.record(
+            title,
+            date,
+            paid.into_iter().map(|(k, v)| (k, from_cents(v))).collect(),
+            consumed
+                .into_iter()
+                .map(|(k, v)| (k, from_cents(v)))
+                .collect(),
+        )
+    }
+
+    fn check_currency(&mut self, description: &str, currency: &str) -> Result<(), String> {
+        match &self.currency {
+            None => {
+                self.currency = Some(currency.to_string());
+                Ok(())
+            }
+            Some(first) if first == currency => Ok(()),
+            Some(first) => Err(format!(
+                "{} is in {}, but the group is in {}",
+                description, currency, first
+            )),
+        }
+    }
+
+    fn settle(&mut self, from: &str, to: &str, cents: i64, date: NaiveDate) -> Result<(), String> {
+        let find = |name: &str| self.users.iter().find(|u| u.get_name() == name);
+        let settlement: Settlement = match (find(from), find(to)) {
+            (Some(from), Some(to)) => Settlement::new(from, to, from_cents(cents), date)
+                .map_err(|_| format!("{} paying {} is not a valid payment", from, to))?,
+            _ => return Err(format!("{} or {} is not in the export", from, to)),
+        };
+        self.ledger.record_settlement(settlement);
+        self.report.settlements += 1;
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        name: &str,
+        date: NaiveDate,
+        paid: BTreeMap<String, f32>,
+        consumed: BTreeMap<String, f32>,
+    ) -> Result<(), String> {
+        let entry: LedgerEntry = LedgerEntry::new(name, date, paid, consumed)
+            .map_err(|_| format!("{} is not a valid expense", name))?;
+        self.ledger.record_entry(entry);
+        self.report.entries += 1;
+        Ok(())
+    }
+
+    fn finish(mut self, name: &str) -> Result<(Group, ImportReport), ImportError> {
+        if let Some(expected) = &self.expected_balances {
+            self.report.balance_checked = true;
+            let imported: BTreeMap<String, f32> = self.ledger.balances();
+            for (member, cents) in expected {
+                let actual: i64 = imported.get(member).map_or(0, |b| b.to_cents());
+                if actual != *cents {
+                    self.report.mismatches.push(format!(
+                        "{}: the export says {:.2}, the import has {:.2}",
+                        member,
+                        from_cents(*cents),
+                        from_cents(actual)
+                    ));
+                }
+            }
+        }
+
+        let mut group: Group =
+            Group::new(name).map_err(|_| ImportError::new("the group needs a name"))?;
+        for user in self.users {
+            group
+                .add_member(user)
+                .map_err(|_| ImportError::new("two people in the export have the same name"))?;
+        }
+        *group.get_ledger_mut() = self.ledger;
+        Ok((group, self.report))
+    }
+}
+
+/// Like `rounding::parse_cents`, but an empty field counts as nothing.
+fn parse_cents(field: &str) -> Option<i64> {
+    if field.trim().is_empty() {
+        return Some(0);
+    }
+    rounding::parse_cents(field)
+}
+
+fn parse_date(field: &str) -> Option<NaiveDate> {
+    const DATE_TIMES: [&str; 4] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%d/%m/%Y %H:%M:%S",
+        "%d/%m/%Y %H:%M",
+    ];
+    const DATES: [&str; 2] = ["%Y-%m-%d", "%d/%m/%Y"];
+    DATE_TIMES
+        .iter()
+        .find_map(|f| {
+            NaiveDateTime::parse_from_str(field, f)
+                .ok()
+                .map(|d| d.date())
+        })
+        .or_else(|| {
+            DATES
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(field, f).ok())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPLITWISE: &str = "\
+Date,Description,Category,Cost,Currency,Alice,Bob,Carol
+2026-01-05,Dinner,Dining out,60.00,USD,40.00,-20.00,-20.00
+2026-01-06,Groceries,Groceries,31.00,USD,-10.33,20.67,-10.34
+2026-01-07,Taxi,Taxi,30.00,USD,10.00,10.00,-20.00
+2026-01-08,Bob paid Alice,Payment,10.00,USD,-10.00,10.00,0.00
+2026-01-09,Souvenir,General,5.00,GBP,-5.00,5.00,0.00
+
+2026-01-10,Total balance, , ,USD,29.67,20.67,-50.34
+";
+
+    const TRICOUNT: &str = "\
+Title,Amount,Currency,Exchange rate,Amount in default currency,Default currency,Date & time,Paid by,Transaction type,Category,Impacted to Alice,Impacted to Bob
+Hotel,-100.00,EUR,1,-100.00,EUR,2026-01-05 10:00:00,Alice,Normal,Accommodation,-50.00,-50.00
+Refund,20.00,EUR,1,20.00,EUR,2026-01-06 10:00:00,Bob,Income,,10.00,10.00
+Bob to Alice,-30.00,EUR,1,-30.00,EUR,2026-01-07 10:00:00,Bob,Money transfer,,-30.00,0.00
+Museum,-12.00,EUR,1,-12.00,EUR,2026-01-08 10:00:00,Alice,Normal,,-5.00,-5.00
+";
+
+    fn balances(group: &Group) -> Vec<(String, i64)> {
+        group
+            .get_ledger()
+            .balances()
+            .into_iter()
+            .map(|(k, v)| (k, v.to_cents()))
+            .collect()
+    }
+
+    #[test]
+    fn test_import_splitwise() {
+        let (group, report) = import(Format::Splitwise, "Trip", SPLITWISE).unwrap();
+
+        assert_eq!(report.get_members(), &["Alice", "Bob", "Carol"]);
+        assert_eq!(report.get_entries(), 3);
+        assert_eq!(report.get_settlements(), 1);
+        assert_eq!(
+            report.get_unmapped(),
+            &["line 6: Souvenir is in GBP, but the group is in USD".to_string()]
+        );
+        assert_eq!(report.get_notes().len(), 1);
+        assert!(report.is_balance_checked());
+        assert!(report.get_mismatches().is_empty());
+        assert_eq!(
+            balances(&group),
+            vec![
+                ("Alice".to_string(), 2967),
+                ("Bob".to_string(), 2067),
+                ("Carol".to_string(), -5034)
+            ]
+        );
+
+        let taxi: &LedgerEntry = &group.get_ledger().get_entries()[2];
+        assert_eq!(taxi.get_paid().get("Alice"), Some(&15.00));
+        assert_eq!(taxi.get_consumed().get("Carol"), Some(&20.00));
+    }
+
+    #[test]
+    fn test_import_splitwise_mismatch() {
+        let csv: String = SPLITWISE.replace("29.67,20.67,-50.34", "34.67,15.67,-50.34");
+        let (_, report) = import(Format::Splitwise, "Trip", &csv).unwrap();
+
+        assert_eq!(
+            report.get_mismatches(),
+            &[
+                "Alice: the export says 34.67, the import has 29.67".to_string(),
+                "Bob: the export says 15.67, the import has 20.67".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_tricount() {
+        let (group, report) = import(Format::Tricount, "Trip", TRICOUNT).unwrap();
+
+        assert_eq!(report.get_entries(), 2);
+        assert_eq!(report.get_settlements(), 1);
+        assert_eq!(
+            report.get_unmapped(),
+            &["line 5: Museum's shares do not add up to its amount".to_string()]
+        );
+        assert!(!report.is_balance_checked());
+        assert_eq!(
+            balances(&group),
+            vec![("Alice".to_string(), 3000), ("Bob".to_string(), -3000)]
+        );
+    }
+
+    #[test]
+    fn test_import_tricount_several_payers() {
+        let csv: &str = "\
+Title,Amount,Date & time,Paid by,Transaction type,Impacted to Alice,Impacted to Bob,Paid by Alice,Paid by Bob
+Hotel,-100.00,2026-01-05 10:00:00,Alice,Normal,-50.00,-50.00,-60.00,-40.00
+Dinner,-40.00,2026-01-06 20:00:00,Alice,Normal,-20.00,-20.00,-30.00,-20.00
+";
+        let (group, report) = import(Format::Tricount, "Trip", csv).unwrap();
+
+        assert_eq!(report.get_entries(), 1);
+        assert_eq!(
+            report.get_unmapped(),
+            &["line 3: Dinner's payments do not add up to its amount".to_string()]
+        );
+        assert_eq!(
+            balances(&group),
+            vec![("Alice".to_string(), 1000), ("Bob".to_string(), -1000)]
+        );
+    }
+
+    #[test]
+    fn test_import_failure_wrong_format() {
+        assert!(import(Format::Splitwise, "Trip", TRICOUNT).is_err());
+        assert!(import(Format::Tricount, "Trip", SPLITWISE).is_err());
+    }
+}
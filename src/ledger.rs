@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::details::{Details, Filter};
+use crate::rounding::{from_cents, Round};
+use crate::settlement::Settlement;
+use crate::split::{Breakdown, ProcessedSplit};
+
+#[derive(Debug, PartialEq)]
+pub struct LedgerError;
+
+/// What a processed split left behind: who paid how much and who consumed how much, keyed by
+/// user name so the entry outlives the users it was calculated from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    name: String,
+    date: NaiveDate,
+    paid: BTreeMap<String, f32>,
+    consumed: BTreeMap<String, f32>,
+    #[serde(flatten)]
+    details: Details,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+    settlements: Vec<Settlement>,
+}
+
+impl LedgerEntry {
+    /// An entry recorded elsewhere, such as in another app. Fails if the name is empty, nobody
+    /// paid, an amount is negative, or what was paid and consumed differ by a cent or more.
+    pub fn new(
+        name: &str,
+        date: NaiveDate,
+        paid: BTreeMap<String, f32>,
+        consumed: BTreeMap<String, f32>,
+    ) -> Result<Self, LedgerError> {
+        if name.is_empty() || paid.is_empty() {
+            return Err(LedgerError);
+        }
+
+        if paid.values().chain(consumed.values()).any(|a| *a < 0_f32) {
+            return Err(LedgerError);
+        }
+
+        let paid_cents: i64 = paid.values().map(|a| a.to_cents()).sum();
+        let consumed_cents: i64 = consumed.values().map(|a| a.to_cents()).sum();
+        if paid_cents != consumed_cents {
+            return Err(LedgerError);
+        }
+
+        Ok(LedgerEntry {
+            name: name.to_string(),
+            date,
+            paid,
+            consumed,
+            details: Details::new(),
+        })
+    }
+
+    /// Fails if the name is empty or the split has no payments, since without payments
+    /// nobody is owed anything. The entry keeps the expenses' details, see `Details::merge`.
+    pub fn from_split(
+        name: &str,
+        date: NaiveDate,
+        split: &ProcessedSplit,
+    ) -> Result<Self, LedgerError> {
+        let mut entry: LedgerEntry = LedgerEntry::from_breakdown(name, date, &split.breakdown())?;
+        entry.set_details(Details::merge(
+            split.get_expenses().iter().map(|e| e.get_details()),
+        ));
+        Ok(entry)
+    }
+
+    pub fn from_breakdown(
+        name: &str,
+        date: NaiveDate,
+        breakdown: &Breakdown,
+    ) -> Result<Self, LedgerError> {
+        if name.is_empty() {
+            return Err(LedgerError);
+        }
+
+        if breakdown.rows.iter().all(|r| r.balance.is_none()) {
+            return Err(LedgerError);
+        }
+
+        Ok(LedgerEntry {
+            name: name.to_string(),
+            date,
+            paid: breakdown
+                .rows
+                .iter()
+                .filter_map(|r| r.paid.filter(|p| *p != 0_f32).map(|p| (r.name.clone(), p)))
+                .collect(),
+            consumed: breakdown
+                .rows
+                .iter()
+                .filter(|r| r.total != 0_f32)
+                .map(|r| (r.name.clone(), r.total))
+                .collect(),
+            details: Details::new(),
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn get_paid(&self) -> &BTreeMap<String, f32> {
+        &self.paid
+    }
+
+    pub fn get_consumed(&self) -> &BTreeMap<String, f32> {
+        &self.consumed
+    }
+
+    pub fn get_details(&self) -> &Details {
+        &self.details
+    }
+
+    /// If the details have a time, the entry is dated on its day.
+    pub fn set_details(&mut self, details: Details) {
+        if let Some(date) = details.get_date() {
+            self.date = date;
+        }
+        self.details = details;
+    }
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger::default()
+    }
+
+    pub fn record_entry(&mut self, entry: LedgerEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn replace_entry(&mut self, index: usize, entry: LedgerEntry) -> Result<(), LedgerError> {
+        let replaced: &mut LedgerEntry = self.entries.get_mut(index).ok_or(LedgerError)?;
+        *replaced = entry;
+        Ok(())
+    }
+
+    pub fn remove_entry(&mut self, index: usize) -> Result<LedgerEntry, LedgerError> {
+        if index >= self.entries.len() {
+            return Err(LedgerError);
+        }
+        Ok(self.entries.remove(index))
+    }
+
+    pub fn record_settlement(&mut self, settlement: Settlement) {
+        self.settlements.push(settlement);
+    }
+
+    pub fn get_entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn get_settlements(&self) -> &[Settlement] {
+        &self.settlements
+    }
+
+    pub fn reconcile_settlement(&mut self, index: usize) -> Result<(), LedgerError> {
+        self.settlements
+            .get_mut(index)
+            .ok_or(LedgerError)?
+            .reconcile();
+        Ok(())
+    }
+
+    /// Outstanding balance per user across every entry and settlement. Positive means the
+    /// user is owed money, negative means they owe.
+    pub fn balances(&self) -> BTreeMap<String, f32> {
+        self.balances_until(NaiveDate::MAX)
+    }
+
+    /// Like `balances`, but only counts entries and settlements dated on or before `date`.
+    pub fn balances_until(&self, date: NaiveDate) -> BTreeMap<String, f32> {
+        let mut filter: Filter = Filter::new();
+        filter.set_until(date);
+        self.balances_matching(&filter)
+    }
+
+    pub fn entries_matching(&self, filter: &Filter) -> Vec<&LedgerEntry> {
+        self.entries
+            .iter()
+            .filter(|e| filter.matches(e.date, &e.details))
+            .collect()
+    }
+
+    /// Like `balances`, but only counts entries matching `filter`. Settlements are counted
+    /// when the filter is on dates only, see `Filter::is_dates_only`.
+    pub fn balances_matching(&self, filter: &Filter) -> BTreeMap<String, f32> {
+        let mut cents: BTreeMap<String, i64> = BTreeMap::new();
+
+        for entry in self.entries_matching(filter) {
+            for (name, amount) in &entry.paid {
+                *cents.entry(name.clone()).or_insert(0) += amount.to_cents();
+            }
+            for (name, amount) in &entry.consumed {
+                *cents.entry(name.clone()).or_insert(0) -= amount.to_cents();
+            }
+        }
+
+        let settlements = self
+            .settlements
+            .iter()
+            .filter(|s| filter.is_dates_only() && filter.matches_date(s.get_date()));
+        for settlement in settlements {
+            let amount: i64 = settlement.get_amount().to_cents();
+            *cents.entry(settlement.get_from().to_string()).or_insert(0) += amount;
+            *cents.entry(settlement.get_to().to_string()).or_insert(0) -= amount;
+        }
+
+        cents.into_iter().map(|(k, v)| (k, from_cents(v))).collect()
+    }
+
+    /// What each user consumed across the entries matching `filter`, such as everyone's
+    /// share of groceries in March.
+    pub fn shares_matching(&self, filter: &Filter) -> BTreeMap<String, f32> {
+        let mut cents: BTreeMap<String, i64> = BTreeMap::new();
+        for entry in self.entries_matching(filter) {
+            for (name, amount) in &entry.consumed {
+                *cents.entry(name.clone()).or_insert(0) += amount.to_cents();
+            }
+        }
+        cents.into_iter().map(|(k, v)| (k, from_cents(v))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expense::{CalculatedExpense, PendingExpense};
+    use crate::split::Split;
+    use crate::taxonomy::Taxonomy;
+    use crate::user::User;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn dinner_entry(alice: &User, bob: &User, day: u32) -> LedgerEntry {
+        let mut pending_expense: PendingExpense = PendingExpense::new("dinner", 40.00).unwrap();
+        pending_expense.add_participant(alice);
+        pending_expense.add_participant(bob);
+        pending_expense.add_payer(alice, 40.00).unwrap();
+        let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+        let split: ProcessedSplit = Split::new(vec![&expense], 0.00, 0.00)
+            .unwrap()
+            .process()
+            .unwrap();
+
+        LedgerEntry::from_split("dinner", date(day), &split).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let paid: BTreeMap<String, f32> = BTreeMap::from([("alice".to_string(), 40.00)]);
+        let consumed: BTreeMap<String, f32> =
+            BTreeMap::from([("alice".to_string(), 20.00), ("bob".to_string(), 20.00)]);
+        assert!(LedgerEntry::new("dinner", date(1), paid.clone(), consumed.clone()).is_ok());
+
+        let short: BTreeMap<String, f32> = BTreeMap::from([("bob".to_string(), 39.99)]);
+        assert_eq!(
+            LedgerEntry::new("dinner", date(1), paid, short),
+            Err(LedgerError)
+        );
+        assert_eq!(
+            LedgerEntry::new("dinner", date(1), BTreeMap::new(), BTreeMap::new()),
+            Err(LedgerError)
+        );
+    }
+
+    #[test]
+    fn test_from_split_failure_no_payments() {
+        let alice: User = User::new("alice").unwrap();
+        let mut pending_expense: PendingExpense = PendingExpense::new("dinner", 40.00).unwrap();
+        pending_expense.add_participant(&alice);
+        let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+        let split: ProcessedSplit = Split::new(vec![&expense], 0.00, 0.00)
+            .unwrap()
+            .process()
+            .unwrap();
+
+        let maybe_entry: Result<LedgerEntry, LedgerError> =
+            LedgerEntry::from_split("dinner", date(1), &split);
+        assert_eq!(maybe_entry.unwrap_err(), LedgerError);
+    }
+
+    #[test]
+    fn test_balances_across_entries_and_settlements() {
+        let alice: User = User::new("alice").unwrap();
+        let bob: User = User::new("bob").unwrap();
+
+        let mut ledger: Ledger = Ledger::new();
+        ledger.record_entry(dinner_entry(&alice, &bob, 1));
+        ledger.record_entry(dinner_entry(&alice, &bob, 5));
+        ledger.record_settlement(Settlement::new(&bob, &alice, 15.00, date(10)).unwrap());
+
+        let mut expected: BTreeMap<String, f32> = BTreeMap::new();
+        expected.insert("alice".to_string(), 25.00);
+        expected.insert("bob".to_string(), -25.00);
+        assert_eq!(ledger.balances(), expected);
+
+        let mut expected_before_settlement: BTreeMap<String, f32> = BTreeMap::new();
+        expected_before_settlement.insert("alice".to_string(), 40.00);
+        expected_before_settlement.insert("bob".to_string(), -40.00);
+        assert_eq!(ledger.balances_until(date(9)), expected_before_settlement);
+    }
+
+    #[test]
+    fn test_from_split_keeps_details() {
+        let alice: User = User::new("alice").unwrap();
+        let mut pending_expense: PendingExpense = PendingExpense::new("dinner", 40.00).unwrap();
+        pending_expense.add_participant(&alice);
+        pending_expense.add_payer(&alice, 40.00).unwrap();
+        let details: &mut Details = pending_expense.get_details_mut();
+        details.set_time("2026-10-03T22:15:00+09:00".parse().unwrap());
+        details
+            .set_category(&Taxonomy::default(), "dining")
+            .unwrap();
+        details.add_tag("tokyo").unwrap();
+        let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+        let split: ProcessedSplit = Split::new(vec![&expense], 0.00, 0.00)
+            .unwrap()
+            .process()
+            .unwrap();
+
+        let entry: LedgerEntry = LedgerEntry::from_split("dinner", date(1), &split).unwrap();
+        assert_eq!(entry.get_date(), date(3));
+        assert_eq!(entry.get_details(), expense.get_details());
+    }
+
+    #[test]
+    fn test_queries_matching() {
+        let alice: User = User::new("alice").unwrap();
+        let bob: User = User::new("bob").unwrap();
+        let mut groceries: LedgerEntry = dinner_entry(&alice, &bob, 1);
+        let mut details: Details = Details::new();
+        details
+            .set_category(&Taxonomy::default(), "groceries")
+            .unwrap();
+        groceries.set_details(details);
+
+        let mut ledger: Ledger = Ledger::new();
+        ledger.record_entry(groceries);
+        ledger.record_entry(dinner_entry(&alice, &bob, 5));
+        ledger.record_settlement(Settlement::new(&bob, &alice, 15.00, date(10)).unwrap());
+
+        let mut filter: Filter = Filter::new();
+        filter.set_category("food");
+        assert_eq!(ledger.entries_matching(&filter).len(), 1);
+        assert_eq!(
+            ledger.balances_matching(&filter),
+            BTreeMap::from([("alice".to_string(), 20.00), ("bob".to_string(), -20.00)])
+        );
+
+        let mut filter: Filter = Filter::new();
+        filter.set_since(date(2));
+        assert_eq!(ledger.entries_matching(&filter).len(), 1);
+        assert_eq!(
+            ledger.balances_matching(&filter),
+            BTreeMap::from([("alice".to_string(), 5.00), ("bob".to_string(), -5.00)])
+        );
+        assert_eq!(
+            ledger.shares_matching(&filter),
+            BTreeMap::from([("alice".to_string(), 20.00), ("bob".to_string(), 20.00)])
+        );
+    }
+}
@@ -1,5 +1,32 @@
+pub mod api;
+pub mod bindings;
+pub mod chat;
+pub mod details;
 pub mod expense;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod group;
+pub mod import;
+pub mod ledger;
+pub mod payment;
+pub mod period;
+#[cfg(feature = "python")]
+mod python;
+pub mod receipt;
+pub mod reconcile;
+pub mod recurring;
+pub mod rent;
+pub mod report;
 mod rounding;
+pub mod selection;
+pub mod session;
+pub mod settlement;
 pub mod split;
+pub mod statement;
+pub mod store;
+pub mod taxonomy;
 mod upserting;
 pub mod user;
+#[cfg(feature = "wasm")]
+pub mod wasm;
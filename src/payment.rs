@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::Ledger;
+
+#[derive(Debug, PartialEq)]
+pub struct PaymentError;
+
+/// Where someone can be paid. Each kind turns a repayment into a link or QR payload that
+/// opens the payer's app with the amount and a memo filled in.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentHandle {
+    /// A UPI virtual payment address, e.g. `alice@okbank`.
+    Upi(String),
+    /// A SEPA bank account, stored without spaces.
+    Iban(String),
+    /// A PayPal.me username.
+    PayPal(String),
+    /// A Venmo username, without the `@`.
+    Venmo(String),
+}
+
+impl PaymentHandle {
+    /// Parses a handle of the given kind: `upi`, `iban`, `paypal` or `venmo`. IBANs must pass
+    /// their check digits.
+    pub fn new(kind: &str, handle: &str) -> Result<Self, PaymentError> {
+        let handle: &str = handle.trim();
+        match kind.to_lowercase().as_str() {
+            "upi" => match handle.split_once('@') {
+                Some((name, provider))
+                    if !name.is_empty()
+                        && !provider.is_empty()
+                        && !provider.contains('@')
+                        && !handle.contains(char::is_whitespace) =>
+                {
+                    Ok(PaymentHandle::Upi(handle.to_string()))
+                }
+                _ => Err(PaymentError),
+            },
+            "iban" => {
+                let iban: String = handle
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect::<String>()
+                    .to_uppercase();
+                if !is_valid_iban(&iban) {
+                    return Err(PaymentError);
+                }
+                Ok(PaymentHandle::Iban(iban))
+            }
+            "paypal" => {
+                let username: &str = handle
+                    .trim_start_matches("https://")
+                    .trim_start_matches("www.")
+                    .trim_start_matches("paypal.me/");
+                if username.is_empty() || !username.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(PaymentError);
+                }
+                Ok(PaymentHandle::PayPal(username.to_string()))
+            }
+            "venmo" => {
+                let username: &str = handle.trim_start_matches('@');
+                if username.is_empty()
+                    || !username
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(PaymentError);
+                }
+                Ok(PaymentHandle::Venmo(username.to_string()))
+            }
+            _ => Err(PaymentError),
+        }
+    }
+
+    pub fn get_kind(&self) -> &str {
+        match self {
+            PaymentHandle::Upi(_) => "upi",
+            PaymentHandle::Iban(_) => "iban",
+            PaymentHandle::PayPal(_) => "paypal",
+            PaymentHandle::Venmo(_) => "venmo",
+        }
+    }
+
+    pub fn get_handle(&self) -> &str {
+        match self {
+            PaymentHandle::Upi(handle)
+            | PaymentHandle::Iban(handle)
+            | PaymentHandle::PayPal(handle)
+            | PaymentHandle::Venmo(handle) => handle,
+        }
+    }
+
+    /// What to open or encode in a QR code to pay `payee` `amount`: a `upi://pay` URI, a SEPA
+    /// EPC QR payload, or a PayPal.me or Venmo link. PayPal.me links cannot carry a memo.
+    pub fn request(&self, payee: &str, amount: f32, memo: &str) -> String {
+        let amount: String = format!("{:.2}", amount);
+        match self {
+            PaymentHandle::Upi(vpa) => format!(
+                "upi://pay?pa={}&pn={}&am={}&cu=INR&tn={}",
+                encode(vpa),
+                encode(payee),
+                amount,
+                encode(&truncate(memo, 80))
+            ),
+            // See the European Payments Council's "Quick Response Code" guidelines, version 002
+            // with UTF-8 text and no BIC.
+            PaymentHandle::Iban(iban) => [
+                "BCD",
+                "002",
+                "1",
+                "SCT",
+                "",
+                &truncate(payee, 70),
+                iban,
+                &format!("EUR{}", amount),
+                "",
+                "",
+                &truncate(memo, 140),
+            ]
+            .join("\n"),
+            PaymentHandle::PayPal(username) => {
+                format!("https://paypal.me/{}/{}", encode(username), amount)
+            }
+            PaymentHandle::Venmo(username) => format!(
+                "https://venmo.com/?txn=pay&recipients={}&amount={}&note={}",
+                encode(username),
+                amount,
+                encode(memo)
+            ),
+        }
+    }
+}
+
+/// A memo for `from` repaying `to`, listing the expenses `to` paid for and `from` shared.
+pub fn memo(ledger: &Ledger, from: &str, to: &str) -> String {
+    let mut names: Vec<&str> = Vec::new();
+    for entry in ledger.get_entries() {
+        if entry.get_paid().contains_key(to)
+            && entry.get_consumed().contains_key(from)
+            && !names.contains(&entry.get_name())
+        {
+            names.push(entry.get_name());
+        }
+    }
+    if names.is_empty() {
+        return "heesab settlement".to_string();
+    }
+    format!("heesab: {}", names.join(", "))
+}
+
+/// ISO 13616: move the country code and check digits to the end, turn letters into numbers
+/// and the result must leave 1 when divided by 97.
+fn is_valid_iban(iban: &str) -> bool {
+    if iban.len() < 15
+        || iban.len() > 34
+        || !iban.chars().all(|c| c.is_ascii_alphanumeric())
+        || !iban[..2].chars().all(|c| c.is_ascii_alphabetic())
+        || !iban[2..4].chars().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+
+    let rearranged: String = format!("{}{}", &iban[4..], &iban[..4]);
+    let mut remainder: u32 = 0;
+    for c in rearranged.chars() {
+        let value: u32 = c.to_digit(36).unwrap_or_default();
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+fn truncate(text: &str, length: usize) -> String {
+    text.chars().take(length).collect()
+}
+
+/// Percent-encodes everything but unreserved characters, for use in URI query values.
+fn encode(text: &str) -> String {
+    let mut encoded: String = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            PaymentHandle::new("UPI", "alice@okbank"),
+            Ok(PaymentHandle::Upi("alice@okbank".to_string()))
+        );
+        assert_eq!(PaymentHandle::new("upi", "alice"), Err(PaymentError));
+        assert_eq!(
+            PaymentHandle::new("iban", "de89 3704 0044 0532 0130 00"),
+            Ok(PaymentHandle::Iban("DE89370400440532013000".to_string()))
+        );
+        assert_eq!(
+            PaymentHandle::new("iban", "DE88370400440532013000"),
+            Err(PaymentError)
+        );
+        assert_eq!(
+            PaymentHandle::new("paypal", "https://paypal.me/alice"),
+            Ok(PaymentHandle::PayPal("alice".to_string()))
+        );
+        assert_eq!(
+            PaymentHandle::new("venmo", "@alice-smith"),
+            Ok(PaymentHandle::Venmo("alice-smith".to_string()))
+        );
+        assert_eq!(PaymentHandle::new("cash", "alice"), Err(PaymentError));
+    }
+
+    #[test]
+    fn test_request() {
+        let memo: &str = "heesab: pizza, rent";
+        assert_eq!(
+            PaymentHandle::Upi("alice@okbank".to_string()).request("Alice Smith", 16.5, memo),
+            "upi://pay?pa=alice%40okbank&pn=Alice%20Smith&am=16.50&cu=INR&tn=heesab%3A%20pizza%2C%20rent"
+        );
+        assert_eq!(
+            PaymentHandle::Iban("DE89370400440532013000".to_string()).request(
+                "Alice Smith",
+                16.5,
+                memo
+            ),
+            "BCD\n002\n1\nSCT\n\nAlice Smith\nDE89370400440532013000\nEUR16.50\n\n\nheesab: pizza, rent"
+        );
+        assert_eq!(
+            PaymentHandle::PayPal("alice".to_string()).request("Alice Smith", 16.5, memo),
+            "https://paypal.me/alice/16.50"
+        );
+        assert_eq!(
+            PaymentHandle::Venmo("alice".to_string()).request("Alice Smith", 16.5, memo),
+            "https://venmo.com/?txn=pay&recipients=alice&amount=16.50&note=heesab%3A%20pizza%2C%20rent"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::NaiveDate;
+
+#[derive(Debug, PartialEq)]
+pub struct PeriodError;
+
+/// A range of days, both ends included, such as a billing period or the nights someone
+/// stayed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Period {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl Period {
+    /// Fails if `end` is before `start`. A period of one day starts and ends on it.
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self, PeriodError> {
+        if end < start {
+            return Err(PeriodError);
+        }
+        Ok(Period { start, end })
+    }
+
+    pub fn get_start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn get_end(&self) -> NaiveDate {
+        self.end
+    }
+
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+
+    /// How many days fall in both periods.
+    pub fn overlap_days(&self, other: &Period) -> i64 {
+        let start: NaiveDate = self.start.max(other.start);
+        let end: NaiveDate = self.end.min(other.end);
+        ((end - start).num_days() + 1).max(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(start: &str, end: &str) -> Period {
+        let parse = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        Period::new(parse(start), parse(end)).unwrap()
+    }
+
+    #[test]
+    fn test_new_failure_end_before_start() {
+        let start: NaiveDate = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let end: NaiveDate = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert_eq!(Period::new(start, end), Err(PeriodError));
+    }
+
+    #[test]
+    fn test_days_and_overlap() {
+        let march: Period = period("2026-03-01", "2026-03-31");
+        assert_eq!(march.days(), 31);
+        assert_eq!(period("2026-03-01", "2026-03-01").days(), 1);
+
+        assert_eq!(march.overlap_days(&period("2026-03-16", "2026-04-15")), 16);
+        assert_eq!(march.overlap_days(&period("2026-02-01", "2026-05-01")), 31);
+        assert_eq!(march.overlap_days(&period("2026-04-01", "2026-04-30")), 0);
+    }
+}
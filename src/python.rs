@@ -0,0 +1,96 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::bindings::{self, Bill, BindingsError};
+use crate::group::Group;
+use crate::user::User;
+
+/// A group of people who split bills, as `heesab.Group(name)` in Python.
+#[pyclass(name = "Group")]
+struct PyGroup {
+    group: Group,
+}
+
+/// A bill among a group's members, as returned by `Group.split()` in Python.
+#[pyclass(name = "Split")]
+struct PySplit {
+    bill: Bill,
+}
+
+#[pymethods]
+impl PyGroup {
+    #[new]
+    fn new(name: &str) -> PyResult<Self> {
+        let group: Group =
+            Group::new(name).map_err(|_| PyValueError::new_err("group names must not be empty"))?;
+        Ok(PyGroup { group })
+    }
+
+    fn add_member(&mut self, name: &str) -> PyResult<()> {
+        let user: User =
+            User::new(name).map_err(|_| PyValueError::new_err("names must not be empty"))?;
+        self.group
+            .add_member(user)
+            .map_err(|_| PyValueError::new_err("a member with that name already exists"))
+    }
+
+    fn add_alias(&mut self, alias: &str, members: &str) -> PyResult<()> {
+        self.group.add_alias(alias, members).map_err(|_| {
+            PyValueError::new_err("the alias must select members and not be a member's name")
+        })
+    }
+
+    fn members(&self) -> Vec<String> {
+        self.group
+            .get_roster()
+            .iter()
+            .map(|u| u.get_name().to_string())
+            .collect()
+    }
+
+    fn split(&self) -> PyResult<PySplit> {
+        let bill: Bill = Bill::new(&self.group).map_err(value_error)?;
+        Ok(PySplit { bill })
+    }
+}
+
+#[pymethods]
+impl PySplit {
+    fn add_expense(&mut self, name: &str, amount: f32, participants: &str) -> PyResult<()> {
+        self.bill
+            .add_expense(name, amount, participants)
+            .map_err(value_error)
+    }
+
+    fn set_tax_and_tip(&mut self, tax: f32, tip: f32) -> PyResult<()> {
+        self.bill.set_tax_and_tip(tax, tip).map_err(value_error)
+    }
+
+    fn add_payer(&mut self, member: &str, amount: f32) -> PyResult<()> {
+        self.bill.add_payer(member, amount).map_err(value_error)
+    }
+
+    /// The breakdown and suggested settlements as a JSON string.
+    fn result(&self) -> PyResult<String> {
+        self.bill.to_json().map_err(value_error)
+    }
+}
+
+/// Splits a whole bill given as a JSON string, see `bindings::split_json`.
+#[pyfunction]
+fn split_json(input: &str) -> PyResult<String> {
+    bindings::split_json(input).map_err(value_error)
+}
+
+fn value_error(e: BindingsError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// The `heesab` Python module, built with `maturin build --features python`.
+#[pymodule]
+fn heesab(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGroup>()?;
+    module.add_class::<PySplit>()?;
+    module.add_function(wrap_pyfunction!(split_json, module)?)?;
+    Ok(())
+}
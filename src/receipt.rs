@@ -0,0 +1,423 @@
+use crate::expense::{PendingExpense, PendingExpenseError};
+use crate::rounding::{allocate, from_cents, Round};
+
+#[derive(Debug, PartialEq)]
+pub struct ReceiptError;
+
+/// One purchased line. `price` is what the line costs in total, not per unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceiptItem {
+    name: String,
+    quantity: u32,
+    price: f32,
+}
+
+/// What could be read from a plain-text receipt, such as one pasted from an email.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Receipt {
+    items: Vec<ReceiptItem>,
+    subtotal: Option<f32>,
+    tax: f32,
+    tip: f32,
+    total: Option<f32>,
+    warnings: Vec<String>,
+}
+
+enum Line {
+    Item(ReceiptItem),
+    Subtotal(i64),
+    Tax(i64),
+    Tip(i64),
+    Total(i64),
+    Ignored,
+}
+
+/// Words that mark a line as something other than an item. Checked in order, so `subtotal`
+/// is found before `total`.
+const SUBTOTAL_WORDS: [&str; 4] = ["subtotal", "sub-total", "zwischensumme", "sous-total"];
+const TAX_WORDS: [&str; 9] = [
+    "tax", "taxes", "vat", "gst", "hst", "mwst", "ust", "tva", "iva",
+];
+const TIP_WORDS: [&str; 6] = [
+    "tip",
+    "tips",
+    "gratuity",
+    "service",
+    "trinkgeld",
+    "pourboire",
+];
+const TOTAL_WORDS: [&str; 7] = [
+    "total", "totale", "summe", "gesamt", "due", "importe", "montant",
+];
+const PAYMENT_WORDS: [&str; 9] = [
+    "cash",
+    "change",
+    "card",
+    "visa",
+    "mastercard",
+    "amex",
+    "paid",
+    "tendered",
+    "rückgeld",
+];
+
+const CURRENCY_SYMBOLS: [char; 6] = ['$', '€', '£', '¥', '₹', '₩'];
+const CURRENCY_CODES: [&str; 7] = ["usd", "eur", "gbp", "inr", "chf", "cad", "aud"];
+
+impl ReceiptItem {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_quantity(&self) -> u32 {
+        self.quantity
+    }
+
+    pub fn get_price(&self) -> f32 {
+        self.price
+    }
+}
+
+impl Receipt {
+    /// Reads item lines (name, optional quantity, price) and subtotal, tax, tip or gratuity
+    /// and total lines. Lines without an amount, such as the shop's address, are skipped, as
+    /// are payment lines such as `Cash` or `Change`. Fails if no items were found.
+    ///
+    /// Prices may use a comma or a point for decimals and carry a currency symbol or code on
+    /// either side, e.g. `12,50 €`, `€12.50`, `1.234,50` or `USD 1,234.50`. Quantities are
+    /// read from `2 x Beer`, `2x Beer`, `Beer x2` or `2 Beer`, and a unit price from
+    /// `@ 4.50`.
+    pub fn parse(text: &str) -> Result<Self, ReceiptError> {
+        let mut items: Vec<ReceiptItem> = Vec::new();
+        let mut subtotal: Option<i64> = None;
+        let mut tax: i64 = 0;
+        let mut tip: i64 = 0;
+        let mut total: Option<i64> = None;
+        let mut warnings: Vec<String> = Vec::new();
+
+        for line in text.lines() {
+            match parse_line(line) {
+                Some(Line::Item(item)) if item.price < 0_f32 => warnings.push(format!(
+                    "Skipped \"{}\": discounts are not items, take them off an item instead.",
+                    line.trim()
+                )),
+                Some(Line::Item(item)) => items.push(item),
+                Some(Line::Subtotal(cents)) => subtotal = Some(cents),
+                // Receipts often list several tax rates separately.
+                Some(Line::Tax(cents)) => tax += cents,
+                Some(Line::Tip(cents)) => tip += cents,
+                // The last total wins, since some receipts repeat it after the tip.
+                Some(Line::Total(cents)) => total = Some(cents),
+                Some(Line::Ignored) | None => {}
+            }
+        }
+        if items.is_empty() {
+            return Err(ReceiptError);
+        }
+
+        let items_cents: i64 = items.iter().map(|i| i.price.to_cents()).sum();
+        if let Some(subtotal) = subtotal {
+            if subtotal != items_cents {
+                warnings.push(format!(
+                    "The items add up to ${:.2}, but the subtotal says ${:.2}.",
+                    from_cents(items_cents),
+                    from_cents(subtotal)
+                ));
+            }
+        }
+        if let Some(total) = total {
+            let expected: i64 = subtotal.unwrap_or(items_cents) + tax + tip;
+            // A total printed before the tip was added is fine too.
+            if total != expected && total != expected - tip {
+                warnings.push(format!(
+                    "The subtotal, tax and tip add up to ${:.2}, but the total says ${:.2}.",
+                    from_cents(expected),
+                    from_cents(total)
+                ));
+            }
+        }
+
+        Ok(Receipt {
+            items,
+            subtotal: subtotal.map(from_cents),
+            tax: from_cents(tax),
+            tip: from_cents(tip),
+            total: total.map(from_cents),
+            warnings,
+        })
+    }
+
+    pub fn get_items(&self) -> &[ReceiptItem] {
+        &self.items
+    }
+
+    pub fn get_subtotal(&self) -> Option<f32> {
+        self.subtotal
+    }
+
+    pub fn get_tax(&self) -> f32 {
+        self.tax
+    }
+
+    pub fn get_tip(&self) -> f32 {
+        self.tip
+    }
+
+    pub fn get_total(&self) -> Option<f32> {
+        self.total
+    }
+
+    /// Anything that did not add up or could not be used, worth showing before splitting.
+    pub fn get_warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// A draft bill with one expense per item. The receipt's tax and tip are shared across
+    /// the items in proportion to their prices, to the cent. Participants and payers are left
+    /// for the caller to add.
+    pub fn draft<'a>(&self) -> Result<Vec<PendingExpense<'a>>, PendingExpenseError> {
+        let prices: Vec<f32> = self.items.iter().map(|i| i.price).collect();
+        let taxes: Vec<f32> = allocate(self.tax, &prices);
+        let tips: Vec<f32> = allocate(self.tip, &prices);
+
+        let mut pending_expenses: Vec<PendingExpense<'a>> = Vec::new();
+        for (index, item) in self.items.iter().enumerate() {
+            let mut pending_expense: PendingExpense = PendingExpense::new(&item.name, item.price)?;
+            pending_expense.set_tax(taxes[index])?;
+            pending_expense.set_tip(tips[index])?;
+            pending_expenses.push(pending_expense);
+        }
+        Ok(pending_expenses)
+    }
+}
+
+/// Splits a line into its label and the amount at its end. `None` if it does not end in an
+/// amount.
+fn parse_line(line: &str) -> Option<Line> {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    while words.last().is_some_and(|w| is_currency(w)) {
+        words.pop();
+    }
+    let cents: i64 = parse_amount(words.pop()?)?;
+    while words.last().is_some_and(|w| is_currency(w)) {
+        words.pop();
+    }
+
+    let label: String = words.join(" ");
+    let keywords: Vec<String> = label
+        .split(|c: char| !c.is_alphabetic() && c != '-')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    let has = |list: &[&str]| keywords.iter().any(|k| list.contains(&k.as_str()));
+    let has_subtotal: bool = has(&SUBTOTAL_WORDS) || label.to_lowercase().contains("sub total");
+
+    if has_subtotal {
+        Some(Line::Subtotal(cents))
+    } else if has(&TAX_WORDS) {
+        Some(Line::Tax(cents))
+    } else if has(&TIP_WORDS) {
+        Some(Line::Tip(cents))
+    } else if has(&PAYMENT_WORDS) || keywords.first().is_some_and(|k| k == "bar") {
+        // "Bar" is cash in German, but "Chocolate bar" is an item.
+        Some(Line::Ignored)
+    } else if has(&TOTAL_WORDS) {
+        Some(Line::Total(cents))
+    } else if keywords.is_empty() {
+        Some(Line::Ignored)
+    } else {
+        Some(Line::Item(parse_item(words, cents)?))
+    }
+}
+
+/// Reads the quantity and unit price out of an item's label. `cents` is the line's price.
+fn parse_item(mut words: Vec<&str>, cents: i64) -> Option<ReceiptItem> {
+    let mut quantity: Option<u32> = None;
+    let mut cents: i64 = cents;
+
+    // "Beer 2 @ 4.50 9.00", or "Beer 2 @ 4.50" where the last amount is the unit price.
+    if let Some(at) = words.iter().position(|w| *w == "@") {
+        if let Some(count) = at.checked_sub(1).and_then(|i| words[i].parse::<u32>().ok()) {
+            quantity = Some(count);
+            if at + 1 == words.len() {
+                cents *= count as i64;
+            }
+            words.truncate(at - 1);
+        }
+    }
+
+    if quantity.is_none() && words.len() > 1 {
+        let first: &str = words[0];
+        let last: &str = words[words.len() - 1];
+        if let Some(count) = parse_count(first) {
+            // "2 x Beer", "2x Beer", "2 Beer".
+            quantity = Some(count);
+            words.remove(0);
+            if words.len() > 1 && (words[0] == "x" || words[0] == "×") {
+                words.remove(0);
+            }
+        } else if let Some(count) = last
+            .strip_prefix(['x', '×'])
+            .and_then(|c| c.parse::<u32>().ok())
+        {
+            // "Beer x2".
+            quantity = Some(count);
+            words.pop();
+        }
+    }
+
+    let name: String = words.join(" ");
+    if name.is_empty() || quantity == Some(0) {
+        return None;
+    }
+    Some(ReceiptItem {
+        name,
+        quantity: quantity.unwrap_or(1),
+        price: from_cents(cents),
+    })
+}
+
+/// `2`, `2x` or `2×`, as a quantity at the start of an item.
+fn parse_count(word: &str) -> Option<u32> {
+    word.trim_end_matches(['x', '×'])
+        .parse::<u32>()
+        .ok()
+        .filter(|c| *c < 1000)
+}
+
+fn is_currency(word: &str) -> bool {
+    CURRENCY_CODES.contains(&word.to_lowercase().as_str())
+        || (!word.is_empty() && word.chars().all(|c| CURRENCY_SYMBOLS.contains(&c)))
+}
+
+/// Reads an amount such as `12.50`, `12,50`, `1,234.50`, `1.234,50`, `-2.00` or `€12.50`
+/// into cents. The last separator is the decimal point and any others group thousands.
+/// Amounts without one or two decimals, such as `Table 12`, are not prices.
+fn parse_amount(word: &str) -> Option<i64> {
+    let mut word: &str = word.trim_matches(|c: char| CURRENCY_SYMBOLS.contains(&c));
+    for code in CURRENCY_CODES {
+        if word
+            .get(..code.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(code))
+        {
+            word = &word[code.len()..];
+        }
+    }
+    let (negative, word): (bool, &str) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, word.strip_suffix('-').map_or(word, |w| w)),
+    };
+    let word: &str = word.trim_matches(|c: char| CURRENCY_SYMBOLS.contains(&c));
+
+    if word.is_empty()
+        || !word.starts_with(|c: char| c.is_ascii_digit())
+        || !word
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    {
+        return None;
+    }
+
+    let (whole, fraction): (&str, &str) = match word.rfind(['.', ',']) {
+        Some(index) if (1..=2).contains(&(word.len() - index - 1)) => {
+            (&word[..index], &word[index + 1..])
+        }
+        _ => return None,
+    };
+    let whole: String = whole.chars().filter(|c| c.is_ascii_digit()).collect();
+    let whole: i64 = whole.parse::<i64>().ok()?;
+    let fraction: i64 = match fraction.len() {
+        1 => fraction.parse::<i64>().ok()? * 10,
+        _ => fraction.parse::<i64>().ok()?,
+    };
+
+    let cents: i64 = whole.checked_mul(100)? + fraction;
+    Some(if negative { -cents } else { cents })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expense::CalculatedExpense;
+    use crate::user::User;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("12.50"), Some(1250));
+        assert_eq!(parse_amount("12,50"), Some(1250));
+        assert_eq!(parse_amount("12,5"), Some(1250));
+        assert_eq!(parse_amount("€12.50"), Some(1250));
+        assert_eq!(parse_amount("1.234,50"), Some(123450));
+        assert_eq!(parse_amount("1,234.50"), Some(123450));
+        assert_eq!(parse_amount("USD12.00"), Some(1200));
+        assert_eq!(parse_amount("1,234"), None);
+        assert_eq!(parse_amount("12"), None);
+        assert_eq!(parse_amount("-2.00"), Some(-200));
+        assert_eq!(parse_amount("14:32"), None);
+        assert_eq!(parse_amount("8%"), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let text: &str = "
+            Trattoria Roma
+            12/05/2026 19:42
+
+            2 x Pizza Margherita     18,00 €
+            Tiramisu                  6,50 €
+            Espresso x3               7,50 €
+            Wine 2 @ 5,00
+            Zwischensumme            41,50 €
+            MwSt 7%                   2,91 €
+            Trinkgeld                 4,00 €
+            Summe                    48,41 €
+            Bar                      50,00 €
+            Rückgeld                  1,59 €
+        ";
+
+        let receipt: Receipt = Receipt::parse(text).unwrap();
+        let items: Vec<(&str, u32, f32)> = receipt
+            .get_items()
+            .iter()
+            .map(|i| (i.get_name(), i.get_quantity(), i.get_price()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("Pizza Margherita", 2, 18.00),
+                ("Tiramisu", 1, 6.50),
+                ("Espresso", 3, 7.50),
+                ("Wine", 2, 10.00),
+            ]
+        );
+        assert_eq!(receipt.get_subtotal(), Some(41.50));
+        assert_eq!(receipt.get_tax(), 2.91);
+        assert_eq!(receipt.get_tip(), 4.00);
+        assert_eq!(receipt.get_total(), Some(48.41));
+        assert_eq!(
+            receipt.get_warnings(),
+            &["The items add up to $42.00, but the subtotal says $41.50.".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_failure() {
+        assert_eq!(Receipt::parse("Thank you!\nTotal 0.00"), Err(ReceiptError));
+    }
+
+    #[test]
+    fn test_draft() {
+        let text: &str = "Burger $12.00\nFries $4.00\nSubtotal $16.00\nSales tax $1.01\nGratuity $3.00\nTotal $20.01";
+        let receipt: Receipt = Receipt::parse(text).unwrap();
+        assert!(receipt.get_warnings().is_empty());
+
+        let user: User = User::new("random name").unwrap();
+        let mut taxes_and_tips: Vec<(f32, f32)> = Vec::new();
+        for mut pending_expense in receipt.draft().unwrap() {
+            pending_expense.add_participant(&user);
+            let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+            taxes_and_tips.push((expense.get_tax(), expense.get_tip()));
+        }
+        assert_eq!(taxes_and_tips, vec![(0.76, 2.25), (0.25, 0.75)]);
+    }
+}
@@ -0,0 +1,427 @@
+use chrono::NaiveDate;
+
+use crate::group::Group;
+use crate::rounding::Round;
+use crate::settlement::Settlement;
+use crate::statement::Transaction;
+use crate::user::User;
+
+/// How many days apart a recorded repayment and a bank transfer can be and still be taken
+/// for the same payment, since transfers can take a few working days to clear.
+const DATE_WINDOW_DAYS: i64 = 5;
+
+#[derive(Debug, PartialEq)]
+pub struct ReconcileError;
+
+/// When a transfer has to be dated to match a repayment.
+enum Window {
+    /// Within `DATE_WINDOW_DAYS` of a recorded repayment's date.
+    Around(NaiveDate),
+    /// No earlier than `DATE_WINDOW_DAYS` before the date, since a suggested repayment cannot
+    /// be paid before the debt it settles.
+    Since(NaiveDate),
+}
+
+/// What reconciling a member's statement against their group found.
+#[derive(Debug, Default, PartialEq)]
+pub struct Reconciliation {
+    confirmed: Vec<(Transaction, Settlement)>,
+    settled: Vec<(Transaction, Settlement)>,
+    unmatched_transactions: Vec<Transaction>,
+    unmatched_settlements: Vec<Settlement>,
+    open: Vec<Settlement>,
+}
+
+impl Reconciliation {
+    /// Recorded repayments found on the statement, including ones reconciled before.
+    pub fn get_confirmed(&self) -> &[(Transaction, Settlement)] {
+        &self.confirmed
+    }
+
+    /// Suggested repayments found on the statement, which are now recorded.
+    pub fn get_settled(&self) -> &[(Transaction, Settlement)] {
+        &self.settled
+    }
+
+    /// Transfers to or from other members that match no repayment, such as partial payments.
+    pub fn get_unmatched_transactions(&self) -> &[Transaction] {
+        &self.unmatched_transactions
+    }
+
+    /// Recorded repayments dated within the statement that are missing from it.
+    pub fn get_unmatched_settlements(&self) -> &[Settlement] {
+        &self.unmatched_settlements
+    }
+
+    /// Suggested repayments involving the member that are still outstanding.
+    pub fn get_open(&self) -> &[Settlement] {
+        &self.open
+    }
+}
+
+/// Matches the transfers on `member`'s statement to the group's repayments by amount, date and
+/// the other party's name. Recorded repayments that match are marked reconciled, and suggested
+/// repayments that match are recorded on the transfer's date and marked reconciled.
+///
+/// Outgoing transfers are repayments `member` made, and incoming ones repayments made to them.
+/// A recorded repayment must be within a few days of its transfer, while a suggested one
+/// matches a transfer from a few days before the last expense or repayment between the two
+/// members onwards. Reconciling the same statement again changes nothing.
+pub fn reconcile(
+    group: &mut Group,
+    member: &str,
+    transactions: &[Transaction],
+) -> Result<Reconciliation, ReconcileError> {
+    let member: User = group
+        .find_member(member)
+        .map_err(|_| ReconcileError)?
+        .clone();
+    let others: Vec<User> = group
+        .get_roster()
+        .iter()
+        .filter(|u| **u != member)
+        .cloned()
+        .collect();
+    let mut used: Vec<bool> = vec![false; transactions.len()];
+    let mut reconciliation: Reconciliation = Reconciliation::default();
+
+    let recorded: Vec<(usize, Settlement)> = group
+        .get_ledger()
+        .get_settlements()
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|(_, s)| involves(s, &member))
+        .collect();
+    for (index, settlement) in recorded {
+        let found: Option<usize> = find_transfer(
+            transactions,
+            &used,
+            &member,
+            &settlement,
+            Window::Around(settlement.get_date()),
+        );
+        match found {
+            Some(found) => {
+                used[found] = true;
+                group
+                    .get_ledger_mut()
+                    .reconcile_settlement(index)
+                    .map_err(|_| ReconcileError)?;
+                let mut settlement: Settlement = settlement;
+                settlement.reconcile();
+                reconciliation
+                    .confirmed
+                    .push((transactions[found].clone(), settlement));
+            }
+            None if within(transactions, settlement.get_date()) => {
+                reconciliation.unmatched_settlements.push(settlement);
+            }
+            None => {}
+        }
+    }
+
+    let suggested: Vec<Settlement> =
+        Settlement::suggest(&group.get_ledger().balances(), NaiveDate::MIN)
+            .into_iter()
+            .filter(|s| involves(s, &member))
+            .collect();
+    for suggestion in suggested {
+        let since: NaiveDate = last_activity(group, suggestion.get_from(), suggestion.get_to());
+        let window: Window = Window::Since(since);
+        let Some(found) = find_transfer(transactions, &used, &member, &suggestion, window) else {
+            reconciliation.open.push(suggestion);
+            continue;
+        };
+        used[found] = true;
+        let transaction: &Transaction = &transactions[found];
+        let (from, to) = match (
+            group.find_member(suggestion.get_from()),
+            group.find_member(suggestion.get_to()),
+        ) {
+            (Ok(from), Ok(to)) => (from, to),
+            _ => return Err(ReconcileError),
+        };
+        let mut settlement: Settlement =
+            Settlement::new(from, to, suggestion.get_amount(), transaction.get_date())
+                .map_err(|_| ReconcileError)?;
+        settlement.reconcile();
+        group.get_ledger_mut().record_settlement(settlement.clone());
+        reconciliation
+            .settled
+            .push((transaction.clone(), settlement));
+    }
+
+    reconciliation.unmatched_transactions = transactions
+        .iter()
+        .zip(&used)
+        .filter(|(t, used)| !**used && others.iter().any(|u| names(u.get_name(), t.get_merchant())))
+        .map(|(t, _)| t.clone())
+        .collect();
+
+    Ok(reconciliation)
+}
+
+fn involves(settlement: &Settlement, member: &User) -> bool {
+    settlement.get_from() == member.get_name() || settlement.get_to() == member.get_name()
+}
+
+/// The date of the latest expense or repayment involving both `first` and `second`.
+fn last_activity(group: &Group, first: &str, second: &str) -> NaiveDate {
+    let ledger = group.get_ledger();
+    let entries = ledger.get_entries().iter().filter(|e| {
+        let takes_part =
+            |name: &str| e.get_paid().contains_key(name) || e.get_consumed().contains_key(name);
+        takes_part(first) && takes_part(second)
+    });
+    let settlements = ledger.get_settlements().iter().filter(|s| {
+        (s.get_from() == first && s.get_to() == second)
+            || (s.get_from() == second && s.get_to() == first)
+    });
+    entries
+        .map(|e| e.get_date())
+        .chain(settlements.map(|s| s.get_date()))
+        .max()
+        .unwrap_or(NaiveDate::MIN)
+}
+
+/// Whether `date` falls between the first and last transaction on the statement.
+fn within(transactions: &[Transaction], date: NaiveDate) -> bool {
+    let dates = || transactions.iter().map(|t| t.get_date());
+    match (dates().min(), dates().max()) {
+        (Some(first), Some(last)) => first <= date && date <= last,
+        _ => false,
+    }
+}
+
+/// Whether a statement's name for the other party names a member: any word of their name
+/// appears as a word in it, ignoring case and punctuation.
+fn names(name: &str, counterparty: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.chars().count() > 1)
+            .map(|w| w.to_lowercase())
+            .collect()
+    };
+    let counterparty: Vec<String> = words(counterparty);
+    words(name).iter().any(|w| counterparty.contains(w))
+}
+
+/// The unused transfer going the same way for the same amount with the other party named. Around
+/// a date, the closest transfer within the window; since a date, the latest.
+fn find_transfer(
+    transactions: &[Transaction],
+    used: &[bool],
+    member: &User,
+    settlement: &Settlement,
+    window: Window,
+) -> Option<usize> {
+    let outgoing: bool = settlement.get_from() == member.get_name();
+    let other: &str = if outgoing {
+        settlement.get_to()
+    } else {
+        settlement.get_from()
+    };
+    let cents: i64 = settlement.get_amount().to_cents();
+    let signed_cents: i64 = if outgoing { cents } else { -cents };
+
+    let candidates = transactions
+        .iter()
+        .enumerate()
+        .filter(|(index, t)| {
+            !used[*index]
+                && t.get_amount().to_cents() == signed_cents
+                && names(other, t.get_merchant())
+        })
+        .map(|(index, t)| (index, t.get_date()));
+    match window {
+        Window::Around(date) => candidates
+            .map(|(index, d)| (index, (d - date).num_days().abs()))
+            .filter(|(_, days)| *days <= DATE_WINDOW_DAYS)
+            .min_by_key(|(_, days)| *days)
+            .map(|(index, _)| index),
+        Window::Since(date) => candidates
+            .filter(|(_, d)| (date - *d).num_days() <= DATE_WINDOW_DAYS)
+            .max_by_key(|(_, d)| *d)
+            .map(|(index, _)| index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use crate::ledger::LedgerEntry;
+    use crate::statement::{self, StatementFormat};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, day).unwrap()
+    }
+
+    fn group() -> Group {
+        let mut group: Group = Group::new("flat").unwrap();
+        for name in ["alice", "bob", "carol"] {
+            group.add_member(User::new(name).unwrap()).unwrap();
+        }
+        let alice: User = group.find_member("alice").unwrap().clone();
+        let bob: User = group.find_member("bob").unwrap().clone();
+        let ledger = group.get_ledger_mut();
+        ledger.record_entry(
+            LedgerEntry::new(
+                "groceries",
+                date(1),
+                BTreeMap::from([("alice".to_string(), 90.00)]),
+                BTreeMap::from([
+                    ("alice".to_string(), 30.00),
+                    ("bob".to_string(), 30.00),
+                    ("carol".to_string(), 30.00),
+                ]),
+            )
+            .unwrap(),
+        );
+        ledger.record_settlement(Settlement::new(&bob, &alice, 30.00, date(10)).unwrap());
+        ledger.record_settlement(Settlement::new(&alice, &bob, 5.00, date(13)).unwrap());
+        group
+    }
+
+    fn statement() -> Vec<Transaction> {
+        let qif: &str = "!Type:Bank
+D01/11/2026
+T-50.00
+PGROCERY
+^
+D01/12/2026
+T30.00
+PBOB SMITH
+^
+D01/15/2026
+T30.00
+PCarol (Venmo)
+^
+D01/16/2026
+T7.00
+PBob Smith
+^
+";
+        statement::parse_all(StatementFormat::Qif, qif).unwrap()
+    }
+
+    fn summary(matches: &[(Transaction, Settlement)]) -> Vec<(&str, &str, NaiveDate)> {
+        matches
+            .iter()
+            .map(|(t, s)| (t.get_merchant(), s.get_from(), s.get_date()))
+            .collect()
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let mut group: Group = group();
+        let reconciliation: Reconciliation = reconcile(&mut group, "alice", &statement()).unwrap();
+
+        assert_eq!(
+            summary(reconciliation.get_confirmed()),
+            vec![("BOB SMITH", "bob", date(10))]
+        );
+        assert_eq!(
+            summary(reconciliation.get_settled()),
+            vec![("Carol (Venmo)", "carol", date(15))]
+        );
+        assert_eq!(reconciliation.get_unmatched_settlements().len(), 1);
+        assert_eq!(
+            reconciliation.get_unmatched_settlements()[0].get_to(),
+            "bob"
+        );
+        assert_eq!(reconciliation.get_open().len(), 1);
+        assert_eq!(reconciliation.get_open()[0].get_from(), "bob");
+        assert_eq!(reconciliation.get_open()[0].get_amount(), 5.00);
+        assert_eq!(reconciliation.get_unmatched_transactions().len(), 1);
+        assert_eq!(
+            reconciliation.get_unmatched_transactions()[0].get_amount(),
+            -7.00
+        );
+
+        let settlements: &[Settlement] = group.get_ledger().get_settlements();
+        assert_eq!(
+            settlements
+                .iter()
+                .map(|s| s.is_reconciled())
+                .collect::<Vec<bool>>(),
+            vec![true, false, true]
+        );
+        assert_eq!(group.get_ledger().balances().get("carol"), Some(&0.00));
+    }
+
+    #[test]
+    fn test_reconcile_again() {
+        let mut group: Group = group();
+        reconcile(&mut group, "alice", &statement()).unwrap();
+        let reconciliation: Reconciliation = reconcile(&mut group, "alice", &statement()).unwrap();
+
+        assert_eq!(reconciliation.get_confirmed().len(), 2);
+        assert!(reconciliation.get_settled().is_empty());
+        assert_eq!(reconciliation.get_unmatched_transactions().len(), 1);
+        assert_eq!(group.get_ledger().get_settlements().len(), 3);
+    }
+
+    #[test]
+    fn test_reconcile_outside_window() {
+        let mut group: Group = group();
+        let late: Vec<Transaction> = statement::parse_all(
+            StatementFormat::Qif,
+            "!Type:Bank\nD01/25/2026\nT30.00\nPBob\n^\n",
+        )
+        .unwrap();
+        let reconciliation: Reconciliation = reconcile(&mut group, "alice", &late).unwrap();
+
+        assert!(reconciliation.get_confirmed().is_empty());
+        assert!(reconciliation.get_unmatched_settlements().is_empty());
+        assert_eq!(reconciliation.get_unmatched_transactions(), late.as_slice());
+    }
+
+    #[test]
+    fn test_reconcile_ignores_transfers_before_the_debt() {
+        let mut group: Group = group();
+        group.get_ledger_mut().record_entry(
+            LedgerEntry::new(
+                "dinner",
+                date(20),
+                BTreeMap::from([("alice".to_string(), 60.00)]),
+                BTreeMap::from([("alice".to_string(), 30.00), ("carol".to_string(), 30.00)]),
+            )
+            .unwrap(),
+        );
+        let old: Vec<Transaction> = statement::parse_all(
+            StatementFormat::Qif,
+            "!Type:Bank\nD01/02/2026\nT60.00\nPCarol\n^\n",
+        )
+        .unwrap();
+        let reconciliation: Reconciliation = reconcile(&mut group, "alice", &old).unwrap();
+
+        assert!(reconciliation.get_settled().is_empty());
+        assert!(reconciliation
+            .get_open()
+            .iter()
+            .any(|s| s.get_from() == "carol" && s.get_amount() == 60.00));
+        assert_eq!(group.get_ledger().balances().get("carol"), Some(&-60.00));
+
+        let recent: Vec<Transaction> = statement::parse_all(
+            StatementFormat::Qif,
+            "!Type:Bank\nD01/17/2026\nT60.00\nPCarol\n^\n",
+        )
+        .unwrap();
+        let reconciliation: Reconciliation = reconcile(&mut group, "alice", &recent).unwrap();
+        assert_eq!(
+            summary(reconciliation.get_settled()),
+            vec![("Carol", "carol", date(17))]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_failure_not_a_member() {
+        assert_eq!(
+            reconcile(&mut group(), "dave", &statement()),
+            Err(ReconcileError)
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::details::Details;
+use crate::ledger::LedgerEntry;
+use crate::rounding::{allocate, Round};
+use crate::user::User;
+
+#[derive(Debug, PartialEq)]
+pub struct RecurringError;
+
+/// How often a recurring expense comes due.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Schedule {
+    /// On this day of every month, or on the last day of shorter months.
+    Monthly(u32),
+    /// Every this many weeks from the start date.
+    Weekly(u32),
+    /// Every year on the start date, or on February 28 when it falls on a leap day.
+    Yearly,
+}
+
+/// An expense that repeats with the same payer, participants and weights, such as rent or a
+/// streaming subscription. Instances are recorded by `materialize`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecurringExpense {
+    name: String,
+    amount: f32,
+    schedule: Schedule,
+    start: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    changes: BTreeMap<NaiveDate, f32>,
+    payer: String,
+    weights: BTreeMap<String, f32>,
+    #[serde(default)]
+    details: Details,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    through: Option<NaiveDate>,
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schedule::Monthly(day) => write!(f, "monthly on day {}", day),
+            Schedule::Weekly(1) => write!(f, "weekly"),
+            Schedule::Weekly(weeks) => write!(f, "every {} weeks", weeks),
+            Schedule::Yearly => write!(f, "yearly"),
+        }
+    }
+}
+
+impl Schedule {
+    /// Reads `monthly` or `monthly:<day>`, `weekly` or `weekly:<weeks>`, and `yearly`.
+    /// Without a day, monthly expenses come due on the day of `start`.
+    pub fn new(value: &str, start: NaiveDate) -> Result<Self, RecurringError> {
+        let value: String = value.trim().to_lowercase();
+        let (kind, count): (&str, Option<&str>) = match value.split_once(':') {
+            Some((kind, count)) => (kind.trim(), Some(count.trim())),
+            None => (value.as_str(), None),
+        };
+        let count: Option<u32> = match count {
+            Some(count) => Some(count.parse::<u32>().map_err(|_| RecurringError)?),
+            None => None,
+        };
+        let schedule: Schedule = match (kind, count) {
+            ("monthly", count) => Schedule::Monthly(count.unwrap_or(start.day())),
+            ("weekly", count) => Schedule::Weekly(count.unwrap_or(1)),
+            ("yearly", None) => Schedule::Yearly,
+            _ => return Err(RecurringError),
+        };
+        match schedule {
+            Schedule::Monthly(day) if !(1..=31).contains(&day) => Err(RecurringError),
+            Schedule::Weekly(0) => Err(RecurringError),
+            _ => Ok(schedule),
+        }
+    }
+
+    /// The `index`th date on or after `start`'s month, which may fall before `start`.
+    fn nth(&self, start: NaiveDate, index: u32) -> Option<NaiveDate> {
+        match self {
+            Schedule::Monthly(day) => {
+                let month: NaiveDate = start.with_day(1)?.checked_add_months(Months::new(index))?;
+                let last: u32 = month.checked_add_months(Months::new(1))?.pred_opt()?.day();
+                month.with_day((*day).min(last))
+            }
+            Schedule::Weekly(weeks) => {
+                start.checked_add_days(Days::new(7 * *weeks as u64 * index as u64))
+            }
+            Schedule::Yearly => start.checked_add_months(Months::new(12 * index)),
+        }
+    }
+}
+
+impl RecurringExpense {
+    /// Fails if the name is empty, the amount is not positive, nobody takes part, a weight is
+    /// negative or not a number, or every weight is zero. Participants with a weight of zero
+    /// owe nothing.
+    pub fn new(
+        name: &str,
+        amount: f32,
+        schedule: Schedule,
+        start: NaiveDate,
+        payer: &User,
+        participants: &[(&User, f32)],
+    ) -> Result<Self, RecurringError> {
+        let name: &str = name.trim();
+        if name.is_empty() || !amount.is_finite() || amount.to_cents() <= 0 {
+            return Err(RecurringError);
+        }
+        if participants.is_empty()
+            || participants
+                .iter()
+                .any(|(_, w)| !w.is_finite() || *w < 0_f32)
+            || participants.iter().all(|(_, w)| *w == 0_f32)
+        {
+            return Err(RecurringError);
+        }
+
+        Ok(RecurringExpense {
+            name: name.to_string(),
+            amount: amount.to_two_decimals(),
+            schedule,
+            start,
+            end: None,
+            changes: BTreeMap::new(),
+            payer: payer.get_name().to_string(),
+            weights: participants
+                .iter()
+                .map(|(u, w)| (u.get_name().to_string(), *w))
+                .collect(),
+            details: Details::new(),
+            through: None,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// The amount before any change. See `amount_on`.
+    pub fn get_amount(&self) -> f32 {
+        self.amount
+    }
+
+    pub fn get_schedule(&self) -> Schedule {
+        self.schedule
+    }
+
+    pub fn get_start(&self) -> NaiveDate {
+        self.start
+    }
+
+    pub fn get_end(&self) -> Option<NaiveDate> {
+        self.end
+    }
+
+    /// No instance comes due after `end`. Fails if it is before the start.
+    pub fn set_end(&mut self, end: Option<NaiveDate>) -> Result<(), RecurringError> {
+        if end.is_some_and(|end| end < self.start) {
+            return Err(RecurringError);
+        }
+        self.end = end;
+        Ok(())
+    }
+
+    pub fn get_changes(&self) -> &BTreeMap<NaiveDate, f32> {
+        &self.changes
+    }
+
+    /// Instances due on or after `from` cost `amount` instead, until a later change. An
+    /// amount of zero pauses the expense. Instances already recorded are not changed.
+    pub fn change_amount(&mut self, from: NaiveDate, amount: f32) -> Result<(), RecurringError> {
+        if !amount.is_finite() || amount < 0_f32 {
+            return Err(RecurringError);
+        }
+        self.changes.insert(from, amount.to_two_decimals());
+        Ok(())
+    }
+
+    /// What the instance due on `date` costs.
+    pub fn amount_on(&self, date: NaiveDate) -> f32 {
+        self.changes
+            .range(..=date)
+            .next_back()
+            .map(|(_, amount)| *amount)
+            .unwrap_or(self.amount)
+    }
+
+    pub fn get_payer(&self) -> &str {
+        &self.payer
+    }
+
+    pub fn get_weights(&self) -> &BTreeMap<String, f32> {
+        &self.weights
+    }
+
+    pub fn get_details(&self) -> &Details {
+        &self.details
+    }
+
+    /// Every instance is recorded with these details. Fails if they have a time, since each
+    /// instance is dated by the schedule.
+    pub fn set_details(&mut self, details: Details) -> Result<(), RecurringError> {
+        if details.get_time().is_some() {
+            return Err(RecurringError);
+        }
+        self.details = details;
+        Ok(())
+    }
+
+    /// The last day instances have been recorded through, if any.
+    pub fn get_through(&self) -> Option<NaiveDate> {
+        self.through
+    }
+
+    /// The dates of instances due on or before `until` that have not been recorded yet.
+    pub fn due_dates(&self, until: NaiveDate) -> Vec<NaiveDate> {
+        let last: NaiveDate = self.end.map_or(until, |end| end.min(until));
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        for index in 0.. {
+            let date: NaiveDate = match self.schedule.nth(self.start, index) {
+                Some(date) if date <= last => date,
+                _ => break,
+            };
+            if date >= self.start && self.through.is_none_or(|through| date > through) {
+                dates.push(date);
+            }
+        }
+        dates
+    }
+
+    /// Builds ledger entries for the instances due on or before `until` and remembers they
+    /// have been recorded, so materializing again never repeats one. Paused instances are
+    /// skipped.
+    pub fn materialize(&mut self, until: NaiveDate) -> Vec<LedgerEntry> {
+        let names: Vec<&String> = self.weights.keys().collect();
+        let weights: Vec<f32> = self.weights.values().copied().collect();
+        let mut entries: Vec<LedgerEntry> = Vec::new();
+        for date in self.due_dates(until) {
+            let amount: f32 = self.amount_on(date);
+            if amount.to_cents() == 0 {
+                continue;
+            }
+            let consumed: BTreeMap<String, f32> = names
+                .iter()
+                .map(|n| n.to_string())
+                .zip(allocate(amount, &weights))
+                .collect();
+            let paid: BTreeMap<String, f32> = BTreeMap::from([(self.payer.clone(), amount)]);
+            let mut entry: LedgerEntry = LedgerEntry::new(&self.name, date, paid, consumed)
+                .expect("allocated shares add up to the amount");
+            entry.set_details(self.details.clone());
+            entries.push(entry);
+        }
+        if self.through.is_none_or(|through| through < until) {
+            self.through = Some(until);
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::group::Group;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn rent(schedule: Schedule, start: &str) -> RecurringExpense {
+        let alice: User = User::new("Alice").unwrap();
+        let bob: User = User::new("Bob").unwrap();
+        RecurringExpense::new(
+            "Rent",
+            1000_f32,
+            schedule,
+            date(start),
+            &alice,
+            &[(&alice, 2_f32), (&bob, 1_f32)],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_schedule_new() {
+        let start: NaiveDate = date("2026-01-15");
+        assert_eq!(Schedule::new("monthly", start), Ok(Schedule::Monthly(15)));
+        assert_eq!(Schedule::new("Monthly:1", start), Ok(Schedule::Monthly(1)));
+        assert_eq!(Schedule::new("weekly:2", start), Ok(Schedule::Weekly(2)));
+        assert_eq!(Schedule::new("yearly", start), Ok(Schedule::Yearly));
+        assert_eq!(Schedule::new("monthly:32", start), Err(RecurringError));
+        assert_eq!(Schedule::new("weekly:0", start), Err(RecurringError));
+        assert_eq!(Schedule::new("daily", start), Err(RecurringError));
+    }
+
+    #[test]
+    fn test_new_failure() {
+        let alice: User = User::new("Alice").unwrap();
+        let start: NaiveDate = date("2026-01-01");
+        let new = |name: &str, amount: f32, participants: &[(&User, f32)]| {
+            RecurringExpense::new(name, amount, Schedule::Yearly, start, &alice, participants)
+        };
+        assert_eq!(new(" ", 10_f32, &[(&alice, 1_f32)]), Err(RecurringError));
+        assert_eq!(new("Rent", 0_f32, &[(&alice, 1_f32)]), Err(RecurringError));
+        assert_eq!(new("Rent", 10_f32, &[]), Err(RecurringError));
+        assert_eq!(
+            new("Rent", 10_f32, &[(&alice, -1_f32)]),
+            Err(RecurringError)
+        );
+        assert_eq!(new("Rent", 10_f32, &[(&alice, 0_f32)]), Err(RecurringError));
+
+        let mut rent: RecurringExpense = rent(Schedule::Yearly, "2026-01-01");
+        assert_eq!(rent.set_end(Some(date("2025-12-31"))), Err(RecurringError));
+    }
+
+    #[test]
+    fn test_due_dates_monthly_uses_last_day_of_short_months() {
+        let rent: RecurringExpense = rent(Schedule::Monthly(31), "2026-01-31");
+        assert_eq!(
+            rent.due_dates(date("2026-04-30")),
+            vec![
+                date("2026-01-31"),
+                date("2026-02-28"),
+                date("2026-03-31"),
+                date("2026-04-30")
+            ]
+        );
+
+        let rent: RecurringExpense = self::rent(Schedule::Monthly(1), "2026-01-15");
+        assert_eq!(
+            rent.due_dates(date("2026-03-01")),
+            vec![date("2026-02-01"), date("2026-03-01")]
+        );
+    }
+
+    #[test]
+    fn test_due_dates_weekly_and_yearly() {
+        let mut cleaning: RecurringExpense = rent(Schedule::Weekly(2), "2026-01-01");
+        cleaning.set_end(Some(date("2026-01-30"))).unwrap();
+        assert_eq!(
+            cleaning.due_dates(date("2026-12-31")),
+            vec![date("2026-01-01"), date("2026-01-15"), date("2026-01-29")]
+        );
+
+        let insurance: RecurringExpense = rent(Schedule::Yearly, "2024-02-29");
+        assert_eq!(
+            insurance.due_dates(date("2028-03-01")),
+            vec![
+                date("2024-02-29"),
+                date("2025-02-28"),
+                date("2026-02-28"),
+                date("2027-02-28"),
+                date("2028-02-29")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_recurring_is_idempotent() {
+        let mut group: Group = Group::new("Flat").unwrap();
+        group.add_member(User::new("Alice").unwrap()).unwrap();
+        group.add_member(User::new("Bob").unwrap()).unwrap();
+
+        let mut rent: RecurringExpense = rent(Schedule::Monthly(1), "2026-01-01");
+        rent.change_amount(date("2026-03-01"), 1200_f32).unwrap();
+        rent.change_amount(date("2026-04-01"), 0_f32).unwrap();
+        rent.change_amount(date("2026-05-01"), 1200_f32).unwrap();
+        rent.set_end(Some(date("2026-05-15"))).unwrap();
+        group.add_recurring(rent).unwrap();
+
+        assert_eq!(group.run_recurring(date("2026-02-15")).len(), 2);
+        assert!(group.run_recurring(date("2026-02-15")).is_empty());
+        assert!(group.run_recurring(date("2026-01-31")).is_empty());
+
+        let recorded: Vec<LedgerEntry> = group.run_recurring(date("2026-12-31"));
+        let dates: Vec<NaiveDate> = recorded.iter().map(|e| e.get_date()).collect();
+        assert_eq!(dates, vec![date("2026-03-01"), date("2026-05-01")]);
+        assert_eq!(recorded[0].get_paid()["Alice"], 1200_f32);
+        assert_eq!(recorded[0].get_consumed()["Alice"], 800_f32);
+        assert_eq!(recorded[0].get_consumed()["Bob"], 400_f32);
+
+        let entries: &[LedgerEntry] = group.get_ledger().get_entries();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].get_consumed()["Alice"], 666.67_f32);
+        assert_eq!(entries[0].get_consumed()["Bob"], 333.33_f32);
+        assert!(group.run_recurring(date("2027-12-31")).is_empty());
+    }
+}
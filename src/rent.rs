@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::rounding::{allocate, from_cents, Round};
+use crate::user::User;
+
+#[derive(Debug, PartialEq)]
+pub struct RentError;
+
+/// How a shared home's rent is divided: a `common` fraction for the shared areas, split
+/// equally between everyone, and the rest by room. Each room scores points for its
+/// attributes, such as its size or a private bathroom, and pays in proportion to them. The
+/// occupants of a room split its part equally.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RentPlan {
+    common: f32,
+    attributes: BTreeMap<String, f32>,
+    rooms: Vec<Room>,
+}
+
+/// A room with a value for some of the plan's attributes, such as `area: 14` or
+/// `bathroom: 1`, and the members living in it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Room {
+    name: String,
+    attributes: BTreeMap<String, f32>,
+    occupants: Vec<String>,
+}
+
+/// One occupant's part of the rent, and how it came about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RentShare {
+    pub occupant: String,
+    pub room: String,
+    /// The room's points, shared by everyone in it.
+    pub points: f32,
+    pub common: f32,
+    pub private: f32,
+    pub total: f32,
+}
+
+impl RentPlan {
+    /// `common` is between 0 and 1. Each attribute is worth `weight` points per unit, so an
+    /// area of 14 at 1 point a unit scores 14 and a private bathroom at 10 points scores 10.
+    pub fn new(common: f32, attributes: &[(&str, f32)]) -> Result<Self, RentError> {
+        if !(0_f32..=1_f32).contains(&common) {
+            return Err(RentError);
+        }
+        let mut weights: BTreeMap<String, f32> = BTreeMap::new();
+        for (name, weight) in attributes {
+            let name: String = name.trim().to_lowercase();
+            if name.is_empty() || !weight.is_finite() || *weight < 0_f32 {
+                return Err(RentError);
+            }
+            if weights.insert(name, *weight).is_some() {
+                return Err(RentError);
+            }
+        }
+        Ok(RentPlan {
+            common,
+            attributes: weights,
+            rooms: Vec::new(),
+        })
+    }
+
+    pub fn get_common(&self) -> f32 {
+        self.common
+    }
+
+    pub fn get_attributes(&self) -> &BTreeMap<String, f32> {
+        &self.attributes
+    }
+
+    pub fn get_rooms(&self) -> &[Room] {
+        &self.rooms
+    }
+
+    /// Room names are unique, ignoring case. Fails if an attribute is not one of the plan's
+    /// or its value is negative, or if an occupant already has a room.
+    pub fn add_room(
+        &mut self,
+        name: &str,
+        attributes: &[(&str, f32)],
+        occupants: &[&User],
+    ) -> Result<(), RentError> {
+        let name: &str = name.trim();
+        if name.is_empty() || self.rooms.iter().any(|r| r.name.eq_ignore_ascii_case(name)) {
+            return Err(RentError);
+        }
+        let mut values: BTreeMap<String, f32> = BTreeMap::new();
+        for (attribute, value) in attributes {
+            let attribute: String = attribute.trim().to_lowercase();
+            if !self.attributes.contains_key(&attribute) || !value.is_finite() || *value < 0_f32 {
+                return Err(RentError);
+            }
+            values.insert(attribute, *value);
+        }
+        self.rooms.push(Room {
+            name: name.to_string(),
+            attributes: values,
+            occupants: Vec::new(),
+        });
+        self.assign(name, occupants)
+    }
+
+    /// Moves `occupants` into the room named `room`, replacing whoever lived there. An empty
+    /// room pays nothing, so the others cover it. Fails if an occupant lives in another room.
+    pub fn assign(&mut self, room: &str, occupants: &[&User]) -> Result<(), RentError> {
+        let index: usize = self
+            .rooms
+            .iter()
+            .position(|r| r.name.eq_ignore_ascii_case(room.trim()))
+            .ok_or(RentError)?;
+        let names: Vec<String> = occupants.iter().map(|u| u.get_name().to_string()).collect();
+        let taken = |name: &String| {
+            self.rooms
+                .iter()
+                .enumerate()
+                .any(|(i, r)| i != index && r.occupants.contains(name))
+        };
+        if names
+            .iter()
+            .enumerate()
+            .any(|(i, n)| names[..i].contains(n) || taken(n))
+        {
+            return Err(RentError);
+        }
+        self.rooms[index].occupants = names;
+        Ok(())
+    }
+
+    /// Everyone living in a room, in the order of the rooms.
+    pub fn get_occupants(&self) -> Vec<&str> {
+        self.rooms
+            .iter()
+            .flat_map(|r| r.occupants.iter().map(|o| o.as_str()))
+            .collect()
+    }
+
+    /// The points `room` scores for its attributes.
+    pub fn points(&self, room: &Room) -> f32 {
+        room.attributes
+            .iter()
+            .map(|(attribute, value)| value * self.attributes.get(attribute).unwrap_or(&0_f32))
+            .sum()
+    }
+
+    /// The fraction of the rent each occupant pays, adding up to 1. These can weigh a split,
+    /// see `PendingExpense::set_weight`, and split any amount about the way `split` does. If
+    /// no occupied room scores points, occupied rooms pay equal parts.
+    pub fn weights(&self) -> BTreeMap<String, f32> {
+        let parts: Vec<(&Room, &String, f32)> = self.room_parts();
+        let people: usize = parts.len();
+        parts
+            .into_iter()
+            .map(|(_, occupant, part)| {
+                let weight: f32 = self.common / people as f32 + (1_f32 - self.common) * part;
+                (occupant.clone(), weight)
+            })
+            .collect()
+    }
+
+    /// Divides `rent` between the occupants to the cent, in the order of the rooms. The
+    /// common part is split equally first, then the rest by room, and each share's total is
+    /// the two added up. The same plan and rent always give the same shares. Fails if nobody
+    /// lives in any room or the rent is negative.
+    pub fn split(&self, rent: f32) -> Result<Vec<RentShare>, RentError> {
+        let parts: Vec<(&Room, &String, f32)> = self.room_parts();
+        if parts.is_empty() || !rent.is_finite() || rent < 0_f32 {
+            return Err(RentError);
+        }
+        let common_total: f32 = from_cents((rent * self.common).to_cents());
+        let commons: Vec<f32> = allocate(common_total, &vec![1_f32; parts.len()]);
+        let privates: Vec<f32> = allocate(
+            from_cents(rent.to_cents() - common_total.to_cents()),
+            &parts.iter().map(|(_, _, part)| *part).collect::<Vec<f32>>(),
+        );
+
+        let shares: Vec<RentShare> = parts
+            .iter()
+            .zip(commons.iter().zip(privates.iter()))
+            .map(|((room, occupant, _), (common, private))| RentShare {
+                occupant: occupant.to_string(),
+                room: room.name.clone(),
+                points: self.points(room),
+                common: *common,
+                private: *private,
+                total: from_cents(common.to_cents() + private.to_cents()),
+            })
+            .collect();
+        Ok(shares)
+    }
+
+    /// Each occupant with the fraction of the rooms' part of the rent they pay, in the order
+    /// of the rooms.
+    fn room_parts(&self) -> Vec<(&Room, &String, f32)> {
+        let occupied: Vec<&Room> = self
+            .rooms
+            .iter()
+            .filter(|r| !r.occupants.is_empty())
+            .collect();
+        let total_points: f32 = occupied.iter().map(|r| self.points(r)).sum();
+        let mut parts: Vec<(&Room, &String, f32)> = Vec::new();
+        for room in occupied.iter().copied() {
+            let room_part: f32 = match total_points > 0_f32 {
+                true => self.points(room) / total_points,
+                false => 1_f32 / occupied.len() as f32,
+            };
+            for occupant in &room.occupants {
+                parts.push((room, occupant, room_part / room.occupants.len() as f32));
+            }
+        }
+        parts
+    }
+}
+
+impl Room {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_attributes(&self) -> &BTreeMap<String, f32> {
+        &self.attributes
+    }
+
+    pub fn get_occupants(&self) -> &[String] {
+        &self.occupants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expense::{CalculatedExpense, PendingExpense};
+
+    fn users() -> Vec<User> {
+        ["Alice", "Bob", "Carol", "Dave"]
+            .iter()
+            .map(|n| User::new(n).unwrap())
+            .collect()
+    }
+
+    fn flat(users: &[User]) -> RentPlan {
+        let mut plan: RentPlan = RentPlan::new(
+            0.25,
+            &[("area", 1_f32), ("bathroom", 10_f32), ("parking", 5_f32)],
+        )
+        .unwrap();
+        plan.add_room(
+            "Big",
+            &[("area", 20_f32), ("bathroom", 1_f32), ("parking", 1_f32)],
+            &[&users[0]],
+        )
+        .unwrap();
+        plan.add_room("Small", &[("area", 12_f32)], &[&users[1]])
+            .unwrap();
+        plan.add_room("Shared", &[("Area", 18_f32)], &[&users[2], &users[3]])
+            .unwrap();
+        plan
+    }
+
+    #[test]
+    fn test_new_and_add_room_failure() {
+        let users: Vec<User> = users();
+        assert_eq!(RentPlan::new(1.5, &[]), Err(RentError));
+        assert_eq!(
+            RentPlan::new(0.2, &[("area", 1_f32), ("Area", 2_f32)]),
+            Err(RentError)
+        );
+
+        let mut plan: RentPlan = flat(&users);
+        assert_eq!(
+            plan.add_room("big", &[("area", 9_f32)], &[]),
+            Err(RentError)
+        );
+        assert_eq!(
+            plan.add_room("Attic", &[("balcony", 1_f32)], &[]),
+            Err(RentError)
+        );
+        assert_eq!(
+            plan.add_room("Attic", &[("area", 9_f32)], &[&users[1]]),
+            Err(RentError)
+        );
+    }
+
+    #[test]
+    fn test_split_explains_each_share() {
+        let users: Vec<User> = users();
+        let plan: RentPlan = flat(&users);
+        let shares: Vec<RentShare> = plan.split(2000_f32).unwrap();
+
+        let summary: Vec<(&str, &str, f32, f32, f32, f32)> = shares
+            .iter()
+            .map(|s| {
+                (
+                    s.occupant.as_str(),
+                    s.room.as_str(),
+                    s.points,
+                    s.common,
+                    s.private,
+                    s.total,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Alice", "Big", 35_f32, 125_f32, 807.69, 932.69),
+                ("Bob", "Small", 12_f32, 125_f32, 276.93, 401.93),
+                ("Carol", "Shared", 18_f32, 125_f32, 207.69, 332.69),
+                ("Dave", "Shared", 18_f32, 125_f32, 207.69, 332.69),
+            ]
+        );
+        let cents: i64 = shares.iter().map(|s| s.total.to_cents()).sum();
+        assert_eq!(cents, 200000);
+        assert_eq!(plan.split(2000_f32).unwrap(), shares);
+    }
+
+    #[test]
+    fn test_split_adds_common_and_private() {
+        let users: Vec<User> = users();
+        let mut plan: RentPlan = RentPlan::new(1_f32, &[("area", 1_f32)]).unwrap();
+        plan.add_room("First", &[("area", 10_f32)], &[&users[2]])
+            .unwrap();
+        plan.add_room("Second", &[("area", 10_f32)], &[&users[0]])
+            .unwrap();
+        plan.add_room("Third", &[("area", 10_f32)], &[&users[1]])
+            .unwrap();
+
+        for rent in [100_f32, 100.01, 100.02, 2000_f32] {
+            let shares: Vec<RentShare> = plan.split(rent).unwrap();
+            for share in &shares {
+                assert!(share.private >= 0_f32);
+                assert_eq!(
+                    share.total.to_cents(),
+                    share.common.to_cents() + share.private.to_cents()
+                );
+            }
+            let cents: i64 = shares.iter().map(|s| s.total.to_cents()).sum();
+            assert_eq!(cents, rent.to_cents());
+        }
+        assert!(flat(&users)
+            .split(2000.03)
+            .unwrap()
+            .iter()
+            .all(|s| s.private >= 0_f32));
+    }
+
+    #[test]
+    fn test_weights_feed_an_expense() {
+        let users: Vec<User> = users();
+        let plan: RentPlan = flat(&users);
+        let weights: BTreeMap<String, f32> = plan.weights();
+
+        let mut pending_expense: PendingExpense = PendingExpense::new("rent", 2000.00).unwrap();
+        for user in &users {
+            pending_expense.add_participant(user);
+            pending_expense
+                .set_weight(user, weights[user.get_name()])
+                .unwrap();
+        }
+        let calculated_expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+
+        for share in plan.split(2000_f32).unwrap() {
+            let user: &User = users
+                .iter()
+                .find(|u| u.get_name() == share.occupant)
+                .unwrap();
+            assert_eq!(calculated_expense.get_user_to_amount()[user], share.total);
+        }
+    }
+
+    #[test]
+    fn test_assign_moves_occupants() {
+        let users: Vec<User> = users();
+        let mut plan: RentPlan = flat(&users);
+        assert_eq!(plan.assign("small", &[&users[0]]), Err(RentError));
+
+        plan.assign("shared", &[&users[2]]).unwrap();
+        plan.assign("small", &[&users[1], &users[3]]).unwrap();
+        assert_eq!(plan.get_occupants(), vec!["Alice", "Bob", "Dave", "Carol"]);
+
+        plan.assign("big", &[]).unwrap();
+        let shares: Vec<RentShare> = plan.split(900_f32).unwrap();
+        assert_eq!(shares.len(), 3);
+        assert!(!plan.weights().contains_key("Alice"));
+    }
+}
@@ -0,0 +1,655 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::details::Filter;
+use crate::ledger::{Ledger, LedgerEntry};
+use crate::rounding::{from_cents, Round};
+
+const TOP_MERCHANTS: usize = 10;
+const BAR_WIDTH: usize = 30;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, PartialEq)]
+pub struct ReportError;
+
+/// The reports a `Report` can be shown as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportKind {
+    /// Spend per category.
+    Categories,
+    /// What each person paid and consumed.
+    People,
+    /// Spend per calendar month, with a sparkline.
+    Months,
+    /// The names spent on the most, such as a shop or "rent".
+    Merchants,
+    /// Each person's share of the group's spend.
+    Shares,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    /// Aligned columns with a bar chart, for the terminal.
+    Table,
+    Csv,
+    Json,
+}
+
+/// Spending over the entries of a ledger, usually narrowed down to a date range with a
+/// `Filter`. An entry's spend is what its members consumed, which is each user's total from
+/// the split it was recorded from, so tax and tip are included. Settlements move money
+/// without spending any and are left out.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Report {
+    total: f32,
+    categories: Vec<CategoryRow>,
+    people: Vec<PersonRow>,
+    months: Vec<MonthRow>,
+    merchants: Vec<MerchantRow>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CategoryRow {
+    category: String,
+    amount: f32,
+    count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PersonRow {
+    name: String,
+    paid: f32,
+    consumed: f32,
+    /// Percent of the total consumed by this person.
+    share: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MonthRow {
+    /// `YYYY-MM`.
+    month: String,
+    amount: f32,
+    count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MerchantRow {
+    merchant: String,
+    amount: f32,
+    count: usize,
+}
+
+/// Rows of text with the values their bars are drawn from.
+struct Table {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+    bars: Vec<f32>,
+}
+
+impl ReportKind {
+    pub const ALL: [ReportKind; 5] = [
+        ReportKind::Categories,
+        ReportKind::People,
+        ReportKind::Months,
+        ReportKind::Merchants,
+        ReportKind::Shares,
+    ];
+
+    pub fn new(name: &str) -> Result<Self, ReportError> {
+        match name.to_lowercase().as_str() {
+            "categories" => Ok(ReportKind::Categories),
+            "people" => Ok(ReportKind::People),
+            "months" => Ok(ReportKind::Months),
+            "merchants" => Ok(ReportKind::Merchants),
+            "shares" => Ok(ReportKind::Shares),
+            _ => Err(ReportError),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ReportKind::Categories => "Spend per category",
+            ReportKind::People => "Paid and consumed per person",
+            ReportKind::Months => "Spend per month",
+            ReportKind::Merchants => "Top merchants",
+            ReportKind::Shares => "Share of the total",
+        }
+    }
+}
+
+impl ReportFormat {
+    pub fn new(name: &str) -> Result<Self, ReportError> {
+        match name.to_lowercase().as_str() {
+            "table" => Ok(ReportFormat::Table),
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(ReportError),
+        }
+    }
+}
+
+impl Report {
+    pub fn new(ledger: &Ledger, filter: &Filter) -> Self {
+        let entries: Vec<&LedgerEntry> = ledger.entries_matching(filter);
+        let spend = |entry: &LedgerEntry| -> i64 {
+            entry.get_consumed().values().map(|a| a.to_cents()).sum()
+        };
+
+        let mut categories: BTreeMap<String, (i64, usize)> = BTreeMap::new();
+        let mut people: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+        let mut months: BTreeMap<NaiveDate, (i64, usize)> = BTreeMap::new();
+        // Keyed by the lowercased name, keeping the first spelling seen.
+        let mut merchants: BTreeMap<String, (String, i64, usize)> = BTreeMap::new();
+        for entry in &entries {
+            let cents: i64 = spend(entry);
+            let category: String = entry
+                .get_details()
+                .get_category()
+                .unwrap_or("Uncategorized")
+                .to_string();
+            let row: &mut (i64, usize) = categories.entry(category).or_default();
+            *row = (row.0 + cents, row.1 + 1);
+
+            for (name, amount) in entry.get_paid() {
+                people.entry(name.clone()).or_default().0 += amount.to_cents();
+            }
+            for (name, amount) in entry.get_consumed() {
+                people.entry(name.clone()).or_default().1 += amount.to_cents();
+            }
+
+            let row: &mut (i64, usize) = months.entry(month(entry.get_date())).or_default();
+            *row = (row.0 + cents, row.1 + 1);
+
+            let row: &mut (String, i64, usize) = merchants
+                .entry(entry.get_name().trim().to_lowercase())
+                .or_insert_with(|| (entry.get_name().trim().to_string(), 0, 0));
+            row.1 += cents;
+            row.2 += 1;
+        }
+
+        // Months without spending still belong on a trend.
+        if let (Some(first), Some(last)) = (
+            months.keys().next().copied(),
+            months.keys().next_back().copied(),
+        ) {
+            let mut current: NaiveDate = first;
+            while current < last {
+                current = current
+                    .checked_add_months(chrono::Months::new(1))
+                    .unwrap_or(last);
+                months.entry(current).or_default();
+            }
+        }
+
+        let total: i64 = entries.iter().map(|e| spend(e)).sum();
+        let mut categories: Vec<CategoryRow> = categories
+            .into_iter()
+            .map(|(category, (cents, count))| CategoryRow {
+                category,
+                amount: from_cents(cents),
+                count,
+            })
+            .collect();
+        categories.sort_by_key(|r| std::cmp::Reverse(r.amount.to_cents()));
+        let mut merchants: Vec<MerchantRow> = merchants
+            .into_values()
+            .map(|(merchant, cents, count)| MerchantRow {
+                merchant,
+                amount: from_cents(cents),
+                count,
+            })
+            .collect();
+        merchants.sort_by_key(|r| std::cmp::Reverse(r.amount.to_cents()));
+        merchants.truncate(TOP_MERCHANTS);
+
+        Report {
+            total: from_cents(total),
+            categories,
+            people: people
+                .into_iter()
+                .map(|(name, (paid, consumed))| PersonRow {
+                    name,
+                    paid: from_cents(paid),
+                    consumed: from_cents(consumed),
+                    share: match total {
+                        0 => 0_f32,
+                        _ => (consumed as f32 * 100_f32 / total as f32).to_two_decimals(),
+                    },
+                })
+                .collect(),
+            months: months
+                .into_iter()
+                .map(|(date, (cents, count))| MonthRow {
+                    month: date.format("%Y-%m").to_string(),
+                    amount: from_cents(cents),
+                    count,
+                })
+                .collect(),
+            merchants,
+        }
+    }
+
+    pub fn get_total(&self) -> f32 {
+        self.total
+    }
+
+    /// Largest first.
+    pub fn get_categories(&self) -> &[CategoryRow] {
+        &self.categories
+    }
+
+    pub fn get_people(&self) -> &[PersonRow] {
+        &self.people
+    }
+
+    /// Every month from the first to the last with spending, in order.
+    pub fn get_months(&self) -> &[MonthRow] {
+        &self.months
+    }
+
+    /// The ten largest, largest first.
+    pub fn get_merchants(&self) -> &[MerchantRow] {
+        &self.merchants
+    }
+
+    /// One report, or every report if `kind` is `None`. CSV holds a single table, so it needs
+    /// a kind.
+    pub fn render(
+        &self,
+        kind: Option<ReportKind>,
+        format: ReportFormat,
+    ) -> Result<String, ReportError> {
+        let kinds: Vec<ReportKind> = kind.map_or(ReportKind::ALL.to_vec(), |k| vec![k]);
+        match (format, kind) {
+            (ReportFormat::Table, _) => Ok(kinds
+                .iter()
+                .map(|k| self.render_table(*k))
+                .collect::<Vec<String>>()
+                .join("\n")),
+            (ReportFormat::Csv, Some(kind)) => self.render_csv(kind),
+            (ReportFormat::Csv, None) => Err(ReportError),
+            (ReportFormat::Json, Some(kind)) => Ok(self.json(kind).to_string()),
+            (ReportFormat::Json, None) => serde_json::to_string(self).map_err(|_| ReportError),
+        }
+    }
+
+    fn json(&self, kind: ReportKind) -> serde_json::Value {
+        match kind {
+            ReportKind::Categories => json!(self.categories),
+            ReportKind::People | ReportKind::Shares => json!(self.people),
+            ReportKind::Months => json!(self.months),
+            ReportKind::Merchants => json!(self.merchants),
+        }
+    }
+
+    fn table(&self, kind: ReportKind) -> Table {
+        let money = |amount: f32| format!("{:.2}", amount);
+        match kind {
+            ReportKind::Categories => Table {
+                columns: vec!["category", "amount", "count"],
+                rows: self
+                    .categories
+                    .iter()
+                    .map(|r| vec![r.category.clone(), money(r.amount), r.count.to_string()])
+                    .collect(),
+                bars: self.categories.iter().map(|r| r.amount).collect(),
+            },
+            ReportKind::People => Table {
+                columns: vec!["name", "paid", "consumed", "difference"],
+                rows: self
+                    .people
+                    .iter()
+                    .map(|r| {
+                        vec![
+                            r.name.clone(),
+                            money(r.paid),
+                            money(r.consumed),
+                            money(from_cents(r.paid.to_cents() - r.consumed.to_cents())),
+                        ]
+                    })
+                    .collect(),
+                bars: self.people.iter().map(|r| r.consumed).collect(),
+            },
+            ReportKind::Months => Table {
+                columns: vec!["month", "amount", "count"],
+                rows: self
+                    .months
+                    .iter()
+                    .map(|r| vec![r.month.clone(), money(r.amount), r.count.to_string()])
+                    .collect(),
+                bars: self.months.iter().map(|r| r.amount).collect(),
+            },
+            ReportKind::Merchants => Table {
+                columns: vec!["merchant", "amount", "count"],
+                rows: self
+                    .merchants
+                    .iter()
+                    .map(|r| vec![r.merchant.clone(), money(r.amount), r.count.to_string()])
+                    .collect(),
+                bars: self.merchants.iter().map(|r| r.amount).collect(),
+            },
+            ReportKind::Shares => Table {
+                columns: vec!["name", "consumed", "share"],
+                rows: self
+                    .people
+                    .iter()
+                    .map(|r| {
+                        vec![
+                            r.name.clone(),
+                            money(r.consumed),
+                            format!("{:.1}%", r.share),
+                        ]
+                    })
+                    .collect(),
+                bars: self.people.iter().map(|r| r.share).collect(),
+            },
+        }
+    }
+
+    /// The first column is left-aligned, the rest right-aligned, followed by a bar scaled to
+    /// the largest value.
+    fn render_table(&self, kind: ReportKind) -> String {
+        let table: Table = self.table(kind);
+        let mut output: String = format!("{} (total {:.2})\n", kind.title(), self.total);
+        if table.rows.is_empty() {
+            output.push_str("Nothing to report.\n");
+            return output;
+        }
+
+        let widths: Vec<usize> = (0..table.columns.len())
+            .map(|column| {
+                table
+                    .rows
+                    .iter()
+                    .map(|row| row[column].chars().count())
+                    .chain([table.columns[column].len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let line = |cells: &[String]| -> String {
+            cells
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (cell, width))| match column {
+                    0 => format!("{:<width$}", cell, width = width),
+                    _ => format!("{:>width$}", cell, width = width),
+                })
+                .collect::<Vec<String>>()
+                .join("  ")
+        };
+        let header: Vec<String> = table.columns.iter().map(|c| c.to_string()).collect();
+        output.push_str(line(&header).trim_end());
+        output.push('\n');
+        for (row, bar) in table.rows.iter().zip(bars(&table.bars)) {
+            output.push_str(format!("{}  {}", line(row), bar).trim_end());
+            output.push('\n');
+        }
+        if kind == ReportKind::Months {
+            let amounts: Vec<f32> = self.months.iter().map(|r| r.amount).collect();
+            output.push_str(&format!("trend  {}\n", sparkline(&amounts)));
+        }
+        output
+    }
+
+    fn render_csv(&self, kind: ReportKind) -> Result<String, ReportError> {
+        let table: Table = self.table(kind);
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .write_record(&table.columns)
+            .map_err(|_| ReportError)?;
+        for row in &table.rows {
+            writer.write_record(row).map_err(|_| ReportError)?;
+        }
+        let bytes: Vec<u8> = writer.into_inner().map_err(|_| ReportError)?;
+        String::from_utf8(bytes).map_err(|_| ReportError)
+    }
+}
+
+/// The first day of the month `date` is in.
+fn month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// `#` bars scaled so the largest value fills the width. Any value above zero gets at least
+/// one `#`.
+fn bars(values: &[f32]) -> Vec<String> {
+    let largest: f32 = values.iter().cloned().fold(0_f32, f32::max);
+    values
+        .iter()
+        .map(|value| {
+            if largest <= 0_f32 || *value <= 0_f32 {
+                return String::new();
+            }
+            let length: usize = (value / largest * BAR_WIDTH as f32).round() as usize;
+            "#".repeat(length.max(1))
+        })
+        .collect()
+}
+
+/// One block per value, from `▁` for the smallest to `█` for the largest.
+pub fn sparkline(values: &[f32]) -> String {
+    let smallest: f32 = values.iter().cloned().fold(f32::INFINITY, f32::min);
+    let largest: f32 = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    values
+        .iter()
+        .map(|value| {
+            if largest <= smallest {
+                return SPARKS[0];
+            }
+            let level: f32 = (value - smallest) / (largest - smallest) * 7_f32;
+            SPARKS[level.round() as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::details::Details;
+    use crate::taxonomy::Taxonomy;
+
+    fn entry(
+        name: &str,
+        date: (u32, u32),
+        category: Option<&str>,
+        paid: &[(&str, f32)],
+        consumed: &[(&str, f32)],
+    ) -> LedgerEntry {
+        let amounts = |amounts: &[(&str, f32)]| -> BTreeMap<String, f32> {
+            amounts.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+        };
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, date.0, date.1).unwrap();
+        let mut entry: LedgerEntry =
+            LedgerEntry::new(name, date, amounts(paid), amounts(consumed)).unwrap();
+        let mut details: Details = Details::new();
+        if let Some(category) = category {
+            details
+                .set_category(&Taxonomy::default(), category)
+                .unwrap();
+        }
+        entry.set_details(details);
+        entry
+    }
+
+    fn ledger() -> Ledger {
+        let mut ledger: Ledger = Ledger::new();
+        for entry in [
+            entry(
+                "Rent",
+                (1, 1),
+                Some("rent"),
+                &[("alice", 900.00)],
+                &[("alice", 450.00), ("bob", 450.00)],
+            ),
+            entry(
+                "Costco",
+                (1, 10),
+                Some("groceries"),
+                &[("bob", 60.00)],
+                &[("alice", 30.00), ("bob", 30.00)],
+            ),
+            entry(
+                "costco ",
+                (3, 2),
+                Some("groceries"),
+                &[("alice", 40.00)],
+                &[("alice", 10.00), ("bob", 30.00)],
+            ),
+            entry(
+                "Pizza",
+                (3, 20),
+                None,
+                &[("bob", 20.00)],
+                &[("alice", 10.00), ("bob", 10.00)],
+            ),
+        ] {
+            ledger.record_entry(entry);
+        }
+        ledger
+    }
+
+    #[test]
+    fn test_new() {
+        let report: Report = Report::new(&ledger(), &Filter::new());
+        assert_eq!(report.get_total(), 1020.00);
+
+        let categories: Vec<(&str, f32, usize)> = report
+            .get_categories()
+            .iter()
+            .map(|r| (r.category.as_str(), r.amount, r.count))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                ("Housing:Rent", 900.00, 1),
+                ("Food:Groceries", 100.00, 2),
+                ("Uncategorized", 20.00, 1)
+            ]
+        );
+
+        let people: Vec<(&str, f32, f32, f32)> = report
+            .get_people()
+            .iter()
+            .map(|r| (r.name.as_str(), r.paid, r.consumed, r.share))
+            .collect();
+        assert_eq!(
+            people,
+            vec![
+                ("alice", 940.00, 500.00, 49.02),
+                ("bob", 80.00, 520.00, 50.98)
+            ]
+        );
+
+        let months: Vec<(&str, f32)> = report
+            .get_months()
+            .iter()
+            .map(|r| (r.month.as_str(), r.amount))
+            .collect();
+        assert_eq!(
+            months,
+            vec![("2026-01", 960.00), ("2026-02", 0.00), ("2026-03", 60.00)]
+        );
+
+        let merchants: Vec<(&str, f32, usize)> = report
+            .get_merchants()
+            .iter()
+            .map(|r| (r.merchant.as_str(), r.amount, r.count))
+            .collect();
+        assert_eq!(
+            merchants,
+            vec![
+                ("Rent", 900.00, 1),
+                ("Costco", 100.00, 2),
+                ("Pizza", 20.00, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_with_filter() {
+        let mut filter: Filter = Filter::new();
+        filter.set_since(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap());
+        let report: Report = Report::new(&ledger(), &filter);
+        assert_eq!(report.get_total(), 60.00);
+        assert_eq!(report.get_months().len(), 1);
+
+        let empty: Report = Report::new(&Ledger::new(), &Filter::new());
+        assert_eq!(empty, Report::default());
+        assert_eq!(
+            empty.render(Some(ReportKind::Shares), ReportFormat::Table),
+            Ok("Share of the total (total 0.00)\nNothing to report.\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let report: Report = Report::new(&ledger(), &Filter::new());
+        assert_eq!(
+            report.render(Some(ReportKind::Months), ReportFormat::Table),
+            Ok("Spend per month (total 1020.00)
+month    amount  count
+2026-01  960.00      2  ##############################
+2026-02    0.00      0
+2026-03   60.00      2  ##
+trend  █▁▁
+"
+            .to_string())
+        );
+        assert_eq!(
+            report.render(Some(ReportKind::Shares), ReportFormat::Table),
+            Ok("Share of the total (total 1020.00)
+name   consumed  share
+alice    500.00  49.0%  #############################
+bob      520.00  51.0%  ##############################
+"
+            .to_string())
+        );
+        let all: String = report.render(None, ReportFormat::Table).unwrap();
+        assert_eq!(all.matches("(total 1020.00)").count(), 5);
+    }
+
+    #[test]
+    fn test_render_csv_and_json() {
+        let report: Report = Report::new(&ledger(), &Filter::new());
+        assert_eq!(
+            report.render(Some(ReportKind::People), ReportFormat::Csv),
+            Ok("name,paid,consumed,difference
+alice,940.00,500.00,440.00
+bob,80.00,520.00,-440.00
+"
+            .to_string())
+        );
+        assert_eq!(report.render(None, ReportFormat::Csv), Err(ReportError));
+
+        let categories: serde_json::Value = serde_json::from_str(
+            &report
+                .render(Some(ReportKind::Categories), ReportFormat::Json)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            categories[1],
+            json!({"category": "Food:Groceries", "amount": 100.0, "count": 2})
+        );
+        let all: serde_json::Value =
+            serde_json::from_str(&report.render(None, ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(all["total"], 1020.0);
+        assert_eq!(all["months"][1]["amount"], 0.0);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(
+            sparkline(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]),
+            "▁▂▃▄▅▆▇█"
+        );
+        assert_eq!(sparkline(&[5.0, 5.0]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
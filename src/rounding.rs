@@ -1,11 +1,116 @@
 pub trait Round {
     fn to_two_decimals(self) -> f32;
+    fn to_cents(self) -> i64;
 }
 
 impl Round for f32 {
     fn to_two_decimals(self) -> f32 {
-        ((self * 100_f32) as f32).round() / 100_f32
+        (self * 100_f32).round() / 100_f32
     }
+
+    fn to_cents(self) -> i64 {
+        (self as f64 * 100_f64).round() as i64
+    }
+}
+
+pub fn from_cents(cents: i64) -> f32 {
+    (cents as f64 / 100_f64) as f32
+}
+
+/// Reads an amount as written in exports and statements, such as `-12.50`, `+12.50`,
+/// `1,234.50`, `1.234,50` or `12,50`, into cents. When both a comma and a point appear, the
+/// last is the decimal point and the other separates thousands. A lone separator followed by
+/// three digits, such as `1,042`, could be either, so the amount is refused.
+pub fn parse_cents(value: &str) -> Option<i64> {
+    let value: &str = value.trim();
+    let (negative, digits): (bool, &str) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let separators: Vec<char> = digits.chars().filter(|c| matches!(c, '.' | ',')).collect();
+    let (whole, fraction, thousands): (&str, &str, Option<char>) = match separators.last() {
+        None => (digits, "", None),
+        Some(&last) => {
+            let position: usize = digits.rfind(last)?;
+            let other: Option<char> = separators.iter().copied().find(|c| *c != last);
+            let repeated: bool = separators.iter().filter(|c| **c == last).count() > 1;
+            let fraction: &str = &digits[position + 1..];
+            match (other, repeated) {
+                (None, true) => (digits, "", Some(last)),
+                _ if fraction.is_empty() || fraction.len() > 2 => return None,
+                (other, _) => (&digits[..position], fraction, other),
+            }
+        }
+    };
+
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    let whole: String = match thousands {
+        Some(thousands) => {
+            let groups: Vec<&str> = whole.split(thousands).collect();
+            let valid: bool = groups.iter().enumerate().all(|(i, group)| {
+                all_digits(group)
+                    && if i == 0 {
+                        (1..=3).contains(&group.len())
+                    } else {
+                        group.len() == 3
+                    }
+            });
+            if !valid {
+                return None;
+            }
+            groups.concat()
+        }
+        None => whole.to_string(),
+    };
+    if !all_digits(&whole) || !all_digits(fraction) || (whole.is_empty() && fraction.is_empty()) {
+        return None;
+    }
+
+    let whole: i64 = match whole.is_empty() {
+        true => 0,
+        false => whole.parse::<i64>().ok()?,
+    };
+    let fraction: i64 = format!("{:0<2}", fraction).parse::<i64>().ok()?;
+    let cents: i64 = whole.checked_mul(100)?.checked_add(fraction)?;
+    Some(if negative { -cents } else { cents })
+}
+
+/// Divides `total` into parts proportional to `weights`. Leftover cents go to the parts with
+/// the largest remainders (earliest first on ties), so the parts always add up to `total`.
+/// If every weight is zero the total is divided equally.
+pub fn allocate(total: f32, weights: &[f32]) -> Vec<f32> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+
+    let total_cents: i64 = total.to_cents();
+    let mut weights: Vec<f64> = weights.iter().map(|w| w.max(0_f32) as f64).collect();
+    let mut weight_sum: f64 = weights.iter().sum();
+    if weight_sum == 0_f64 {
+        weights = vec![1_f64; weights.len()];
+        weight_sum = weights.len() as f64;
+    }
+
+    let mut cents: Vec<i64> = Vec::with_capacity(weights.len());
+    let mut remainders: Vec<(usize, f64)> = Vec::with_capacity(weights.len());
+    for (index, weight) in weights.iter().enumerate() {
+        let exact: f64 = total_cents as f64 * weight / weight_sum;
+        let floor: f64 = exact.floor();
+        cents.push(floor as i64);
+        remainders.push((index, exact - floor));
+    }
+
+    let mut leftover: i64 = total_cents - cents.iter().sum::<i64>();
+    remainders.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    for (index, _) in remainders.iter().cycle() {
+        if leftover <= 0 {
+            break;
+        }
+        cents[*index] += 1;
+        leftover -= 1;
+    }
+
+    cents.into_iter().map(from_cents).collect()
 }
 
 #[cfg(test)]
@@ -14,13 +119,66 @@ mod tests {
 
     #[test]
     fn test_to_two_decimals_rounds_up() {
-        let num: f32 = 1.235210983901;
+        let num: f32 = 1.235_211;
         assert_eq!(num.to_two_decimals(), 1.24)
     }
 
     #[test]
     fn test_to_two_decimals_rounds_down() {
-        let num: f32 = 1.2345210983901;
+        let num: f32 = 1.234_521_2;
         assert_eq!(num.to_two_decimals(), 1.23)
     }
+
+    #[test]
+    fn test_parse_cents() {
+        assert_eq!(parse_cents("-12.50"), Some(-1250));
+        assert_eq!(parse_cents("+1,234.50"), Some(123450));
+        assert_eq!(parse_cents("12,5"), Some(1250));
+        assert_eq!(parse_cents("twelve"), None);
+        assert_eq!(parse_cents("1234"), Some(123400));
+    }
+
+    #[test]
+    fn test_parse_cents_thousands_separators() {
+        assert_eq!(parse_cents("1.042,50"), Some(104250));
+        assert_eq!(parse_cents("-1.234.567,8"), Some(-123456780));
+        assert_eq!(parse_cents("1,234,567"), Some(123456700));
+        assert_eq!(parse_cents("1.234.567"), Some(123456700));
+        assert_eq!(parse_cents("12.345,6"), Some(1234560));
+    }
+
+    #[test]
+    fn test_parse_cents_failure_ambiguous_or_malformed() {
+        assert_eq!(parse_cents("1,042"), None);
+        assert_eq!(parse_cents("1.042"), None);
+        assert_eq!(parse_cents("1,04,250.00"), None);
+        assert_eq!(parse_cents("1.042.50"), None);
+        assert_eq!(parse_cents("12.505"), None);
+        assert_eq!(parse_cents("12."), None);
+        assert_eq!(parse_cents("-"), None);
+    }
+
+    #[test]
+    fn test_to_cents() {
+        assert_eq!(12.34_f32.to_cents(), 1234);
+        assert_eq!((-0.07_f32).to_cents(), -7);
+    }
+
+    #[test]
+    fn test_allocate_equal_weights_keeps_every_cent() {
+        let parts: Vec<f32> = allocate(10.00, &[1.0, 1.0, 1.0]);
+        assert_eq!(parts, vec![3.34, 3.33, 3.33]);
+    }
+
+    #[test]
+    fn test_allocate_proportional() {
+        let parts: Vec<f32> = allocate(2.20, &[2.75, 10.00]);
+        assert_eq!(parts, vec![0.47, 1.73]);
+    }
+
+    #[test]
+    fn test_allocate_zero_weights_divides_equally() {
+        let parts: Vec<f32> = allocate(1.00, &[0.0, 0.0]);
+        assert_eq!(parts, vec![0.50, 0.50]);
+    }
 }
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::user::User;
+
+/// Why a selection could not be resolved. Carries the offending reference so callers can tell
+/// the user what to fix.
+#[derive(Debug, PartialEq)]
+pub enum SelectionError {
+    Empty,
+    NoMatch(String),
+    Ambiguous(String, Vec<String>),
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelectionError::Empty => write!(f, "nobody was selected"),
+            SelectionError::NoMatch(reference) => {
+                write!(f, "{} did not match anyone", reference)
+            }
+            SelectionError::Ambiguous(reference, candidates) => write!(
+                f,
+                "{} is ambiguous, it could be {}",
+                reference,
+                candidates.join(" or ")
+            ),
+        }
+    }
+}
+
+/// Resolves a selection of people from `roster`. The grammar is:
+///
+/// - `all` for everyone
+/// - `<selection> except <selection>` to leave people out, e.g. `all except bob`
+/// - references separated by `:` or `,`, where each reference is one of, in order of
+///   precedence, a full name, a 1-based number from the printed roster, an alias from
+///   `aliases`, a person's initials or the unique prefix of a name
+///
+/// Matching ignores case. Fails rather than guessing when a reference matches nobody or more
+/// than one person, and when the selection ends up empty.
+pub fn select<'a>(
+    roster: &'a [User],
+    aliases: &BTreeMap<String, Vec<String>>,
+    input: &str,
+) -> Result<Vec<&'a User>, SelectionError> {
+    let indices: Vec<usize> = select_indices(roster, aliases, input)?;
+    Ok(indices.into_iter().map(|i| &roster[i]).collect())
+}
+
+/// Resolves a single person, using the same rules as one reference in `select`.
+pub fn select_one<'a>(roster: &'a [User], reference: &str) -> Result<&'a User, SelectionError> {
+    let index: usize = resolve_one(roster, reference)?;
+    Ok(&roster[index])
+}
+
+pub(crate) fn resolve_one(roster: &[User], reference: &str) -> Result<usize, SelectionError> {
+    let indices: Vec<usize> = resolve(roster, &BTreeMap::new(), reference)?;
+    match indices.as_slice() {
+        [index] => Ok(*index),
+        _ => Err(SelectionError::NoMatch(reference.trim().to_string())),
+    }
+}
+
+fn select_indices(
+    roster: &[User],
+    aliases: &BTreeMap<String, Vec<String>>,
+    input: &str,
+) -> Result<Vec<usize>, SelectionError> {
+    const EXCEPT: &str = " except ";
+    // Found on `input` itself, since lowercasing can change the length of non-ASCII names.
+    let position: Option<usize> = input.char_indices().map(|(i, _)| i).find(|i| {
+        input
+            .get(*i..*i + EXCEPT.len())
+            .is_some_and(|window| window.eq_ignore_ascii_case(EXCEPT))
+    });
+    let (included, excluded) = match position {
+        Some(position) => (&input[..position], Some(&input[position + EXCEPT.len()..])),
+        None => (input, None),
+    };
+
+    let mut indices: Vec<usize> = Vec::new();
+    for reference in split_references(included) {
+        for index in resolve(roster, aliases, reference)? {
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+    }
+
+    if let Some(excluded) = excluded {
+        let excluded: Vec<usize> = select_indices(roster, aliases, excluded)?;
+        indices.retain(|index| !excluded.contains(index));
+    }
+
+    if indices.is_empty() {
+        return Err(SelectionError::Empty);
+    }
+    Ok(indices)
+}
+
+fn split_references(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split([':', ','])
+        .map(|reference| reference.trim())
+        .filter(|reference| !reference.is_empty())
+}
+
+fn resolve(
+    roster: &[User],
+    aliases: &BTreeMap<String, Vec<String>>,
+    reference: &str,
+) -> Result<Vec<usize>, SelectionError> {
+    let reference: &str = reference.trim();
+    let lowercase: String = reference.to_lowercase();
+
+    if lowercase == "all" {
+        return Ok((0..roster.len()).collect());
+    }
+
+    if let Some(index) = roster
+        .iter()
+        .position(|u| u.get_name().to_lowercase() == lowercase)
+    {
+        return Ok(vec![index]);
+    }
+
+    if let Ok(position) = reference.parse::<usize>() {
+        if position >= 1 && position <= roster.len() {
+            return Ok(vec![position - 1]);
+        }
+        return Err(SelectionError::NoMatch(reference.to_string()));
+    }
+
+    if let Some((_, members)) = aliases.iter().find(|(k, _)| k.to_lowercase() == lowercase) {
+        let mut indices: Vec<usize> = Vec::new();
+        for member in members {
+            indices.push(resolve_one(roster, member)?);
+        }
+        return Ok(indices);
+    }
+
+    let by_initials: Vec<usize> = matching(roster, |u| u.initials() == lowercase);
+    let by_prefix: Vec<usize> = matching(roster, |u| {
+        u.get_name().to_lowercase().starts_with(&lowercase)
+    });
+    for candidates in [by_initials, by_prefix] {
+        match candidates.as_slice() {
+            [] => continue,
+            [index] => return Ok(vec![*index]),
+            _ => {
+                return Err(SelectionError::Ambiguous(
+                    reference.to_string(),
+                    candidates
+                        .iter()
+                        .map(|i| roster[*i].get_name().to_string())
+                        .collect(),
+                ))
+            }
+        }
+    }
+
+    Err(SelectionError::NoMatch(reference.to_string()))
+}
+
+fn matching(roster: &[User], predicate: impl Fn(&User) -> bool) -> Vec<usize> {
+    roster
+        .iter()
+        .enumerate()
+        .filter(|(_, u)| predicate(u))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster() -> Vec<User> {
+        vec![
+            User::new("Alice Smith").unwrap(),
+            User::new("Bob").unwrap(),
+            User::new("Ben").unwrap(),
+            User::new("Carol").unwrap(),
+        ]
+    }
+
+    fn names(users: Vec<&User>) -> Vec<&str> {
+        users.iter().map(|u| u.get_name()).collect()
+    }
+
+    #[test]
+    fn test_select_all() {
+        let roster: Vec<User> = roster();
+        let selected: Vec<&User> = select(&roster, &BTreeMap::new(), "ALL").unwrap();
+        assert_eq!(names(selected), vec!["Alice Smith", "Bob", "Ben", "Carol"]);
+    }
+
+    #[test]
+    fn test_select_all_except() {
+        let roster: Vec<User> = roster();
+        let selected: Vec<&User> = select(&roster, &BTreeMap::new(), "all except bob, 3").unwrap();
+        assert_eq!(names(selected), vec!["Alice Smith", "Carol"]);
+    }
+
+    #[test]
+    fn test_select_except_with_non_ascii_names() {
+        let roster: Vec<User> = vec![
+            User::new("İİ").unwrap(),
+            User::new("İx").unwrap(),
+            User::new("x").unwrap(),
+        ];
+        let selected: Vec<&User> = select(&roster, &BTreeMap::new(), "İİ:x EXCEPT x").unwrap();
+        assert_eq!(names(selected), vec!["İİ"]);
+        let selected: Vec<&User> = select(&roster, &BTreeMap::new(), "İx except İİ").unwrap();
+        assert_eq!(names(selected), vec!["İx"]);
+    }
+
+    #[test]
+    fn test_select_names_numbers_initials_and_prefixes() {
+        let roster: Vec<User> = roster();
+        let selected: Vec<&User> = select(&roster, &BTreeMap::new(), "as:2,car").unwrap();
+        assert_eq!(names(selected), vec!["Alice Smith", "Bob", "Carol"]);
+    }
+
+    #[test]
+    fn test_select_alias() {
+        let roster: Vec<User> = roster();
+        let mut aliases: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        aliases.insert(
+            "boys".to_string(),
+            vec!["Bob".to_string(), "Ben".to_string()],
+        );
+
+        let selected: Vec<&User> = select(&roster, &aliases, "Boys:bob").unwrap();
+        assert_eq!(names(selected), vec!["Bob", "Ben"]);
+    }
+
+    #[test]
+    fn test_select_failure_ambiguous() {
+        let roster: Vec<User> = roster();
+        let maybe_selected = select(&roster, &BTreeMap::new(), "b");
+        assert_eq!(
+            maybe_selected.unwrap_err(),
+            SelectionError::Ambiguous("b".to_string(), vec!["Bob".to_string(), "Ben".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_select_failure_no_match() {
+        let roster: Vec<User> = roster();
+        let maybe_selected = select(&roster, &BTreeMap::new(), "alice:dave");
+        assert_eq!(
+            maybe_selected.unwrap_err(),
+            SelectionError::NoMatch("dave".to_string())
+        );
+    }
+
+    #[test]
+    fn test_select_failure_empty() {
+        let roster: Vec<User> = roster();
+        let maybe_selected = select(&roster, &BTreeMap::new(), "all except all");
+        assert_eq!(maybe_selected.unwrap_err(), SelectionError::Empty);
+    }
+}
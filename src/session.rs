@@ -0,0 +1,507 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::details::{Details, Filter};
+use crate::expense::{CalculatedExpense, PendingExpense};
+use crate::split::{Breakdown, ProcessedSplit, Split};
+use crate::user::User;
+
+#[derive(Debug, PartialEq)]
+pub struct SessionError;
+
+/// An expense that has been entered but can still be changed. Participants are kept by name
+/// so the draft does not borrow from the roster.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DraftExpense {
+    name: String,
+    amount: f32,
+    participants: Vec<String>,
+    #[serde(default)]
+    details: Details,
+}
+
+/// A bill being entered: the people on it and the expenses entered so far. Everything is owned
+/// and editable; the borrowed `PendingExpense` to `Split` pipeline only runs when a split is
+/// asked for.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    group: Option<String>,
+    roster: Vec<User>,
+    aliases: BTreeMap<String, Vec<String>>,
+    expenses: Vec<DraftExpense>,
+    #[serde(default)]
+    tax_and_tip: Option<(f32, f32)>,
+}
+
+impl DraftExpense {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_amount(&self) -> f32 {
+        self.amount
+    }
+
+    pub fn get_participants(&self) -> &[String] {
+        &self.participants
+    }
+
+    pub fn get_details(&self) -> &Details {
+        &self.details
+    }
+}
+
+impl Session {
+    /// `group` names the saved group the roster came from, if any.
+    pub fn new(
+        group: Option<&str>,
+        roster: Vec<User>,
+        aliases: BTreeMap<String, Vec<String>>,
+    ) -> Result<Self, SessionError> {
+        if roster.is_empty() {
+            return Err(SessionError);
+        }
+
+        Ok(Session {
+            group: group.map(|g| g.to_string()),
+            roster,
+            aliases,
+            expenses: Vec::new(),
+            tax_and_tip: None,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SessionError> {
+        let contents: String = fs::read_to_string(path).map_err(|_| SessionError)?;
+        serde_json::from_str(&contents).map_err(|_| SessionError)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SessionError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|_| SessionError)?;
+        }
+        let contents: String = serde_json::to_string_pretty(self).map_err(|_| SessionError)?;
+        fs::write(path, contents).map_err(|_| SessionError)
+    }
+
+    pub fn get_group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    pub fn get_roster(&self) -> &[User] {
+        &self.roster
+    }
+
+    /// Names are unique within a session, ignoring case.
+    pub fn add_participant(&mut self, user: User) -> Result<(), SessionError> {
+        if self
+            .roster
+            .iter()
+            .any(|u| u.get_name().eq_ignore_ascii_case(user.get_name()))
+        {
+            return Err(SessionError);
+        }
+        self.roster.push(user);
+        Ok(())
+    }
+
+    /// Fails if the participant is still on an expense, or is the last one left.
+    pub fn remove_participant(&mut self, name: &str) -> Result<User, SessionError> {
+        let index: usize = self
+            .roster
+            .iter()
+            .position(|u| u.get_name() == name)
+            .ok_or(SessionError)?;
+        if self.roster.len() == 1
+            || self
+                .expenses
+                .iter()
+                .any(|e| e.participants.iter().any(|p| p == name))
+        {
+            return Err(SessionError);
+        }
+        Ok(self.roster.remove(index))
+    }
+
+    pub fn get_aliases(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.aliases
+    }
+
+    pub fn get_expenses(&self) -> &[DraftExpense] {
+        &self.expenses
+    }
+
+    /// Validates like `PendingExpense::new`, and every participant must be on the roster.
+    pub fn add_expense(
+        &mut self,
+        name: &str,
+        amount: f32,
+        participants: &[&User],
+    ) -> Result<(), SessionError> {
+        let expense: DraftExpense = self.draft(name, amount, participants)?;
+        self.expenses.push(expense);
+        Ok(())
+    }
+
+    pub fn edit_name(&mut self, index: usize, name: &str) -> Result<(), SessionError> {
+        let expense: &mut DraftExpense = self.expenses.get_mut(index).ok_or(SessionError)?;
+        PendingExpense::new(name, expense.amount).map_err(|_| SessionError)?;
+        expense.name = name.to_string();
+        Ok(())
+    }
+
+    pub fn edit_amount(&mut self, index: usize, amount: f32) -> Result<(), SessionError> {
+        let expense: &mut DraftExpense = self.expenses.get_mut(index).ok_or(SessionError)?;
+        PendingExpense::new(&expense.name, amount).map_err(|_| SessionError)?;
+        expense.amount = amount;
+        Ok(())
+    }
+
+    /// Changes the name and amount together, or neither if either is invalid.
+    pub fn edit_name_and_amount(
+        &mut self,
+        index: usize,
+        name: &str,
+        amount: f32,
+    ) -> Result<(), SessionError> {
+        let expense: &mut DraftExpense = self.expenses.get_mut(index).ok_or(SessionError)?;
+        PendingExpense::new(name, amount).map_err(|_| SessionError)?;
+        expense.name = name.to_string();
+        expense.amount = amount;
+        Ok(())
+    }
+
+    pub fn edit_participants(
+        &mut self,
+        index: usize,
+        participants: &[&User],
+    ) -> Result<(), SessionError> {
+        let expense: &DraftExpense = self.expenses.get(index).ok_or(SessionError)?;
+        let mut edited: DraftExpense = self.draft(&expense.name, expense.amount, participants)?;
+        edited.details = expense.details.clone();
+        self.expenses[index] = edited;
+        Ok(())
+    }
+
+    /// Category, tags and notes for the expense, kept when it is recorded in a ledger.
+    pub fn set_details(&mut self, index: usize, details: Details) -> Result<(), SessionError> {
+        let expense: &mut DraftExpense = self.expenses.get_mut(index).ok_or(SessionError)?;
+        expense.details = details;
+        Ok(())
+    }
+
+    pub fn remove_expense(&mut self, index: usize) -> Result<DraftExpense, SessionError> {
+        if index >= self.expenses.len() {
+            return Err(SessionError);
+        }
+        Ok(self.expenses.remove(index))
+    }
+
+    pub fn get_tax_and_tip(&self) -> Option<(f32, f32)> {
+        self.tax_and_tip
+    }
+
+    /// Fails, keeping whatever was set before, if the split would reject the tax or tip.
+    pub fn set_tax_and_tip(&mut self, tax: f32, tip: f32) -> Result<(), SessionError> {
+        self.split(tax, tip, &[])?;
+        self.tax_and_tip = Some((tax, tip));
+        Ok(())
+    }
+
+    /// The running split before tax, tip and payments are known.
+    pub fn preview(&self) -> Result<Breakdown, SessionError> {
+        self.split(0_f32, 0_f32, &[])
+    }
+
+    /// Runs every draft expense through `Expense::calculate` and `Split::process`. `payers`
+    /// pay towards the split as a whole, see `Split::add_payer`.
+    pub fn split(
+        &self,
+        tax: f32,
+        tip: f32,
+        payers: &[(&User, f32)],
+    ) -> Result<Breakdown, SessionError> {
+        self.process(tax, tip, payers, |split| split.breakdown())
+    }
+
+    /// Everyone's share of the expenses matching `filter` so far, see
+    /// `ProcessedSplit::subtotals_matching`. Expenses are taken to be on `date`.
+    pub fn subtotals_matching(
+        &self,
+        date: NaiveDate,
+        filter: &Filter,
+    ) -> Result<BTreeMap<String, f32>, SessionError> {
+        self.process(0_f32, 0_f32, &[], |split| {
+            split
+                .subtotals_matching(date, filter)
+                .into_iter()
+                .map(|(user, subtotal)| (user.get_name().to_string(), subtotal))
+                .collect()
+        })
+    }
+
+    /// The details of every expense, combined for recording the bill as one ledger entry.
+    pub fn merged_details(&self) -> Details {
+        Details::merge(self.expenses.iter().map(|e| &e.details))
+    }
+
+    fn process<T>(
+        &self,
+        tax: f32,
+        tip: f32,
+        payers: &[(&User, f32)],
+        read: impl FnOnce(&ProcessedSplit) -> T,
+    ) -> Result<T, SessionError> {
+        let mut calculated_expenses: Vec<CalculatedExpense> = Vec::new();
+        for expense in &self.expenses {
+            let mut pending_expense: PendingExpense =
+                PendingExpense::new(&expense.name, expense.amount).map_err(|_| SessionError)?;
+            for name in &expense.participants {
+                pending_expense.add_participant(self.member(name)?);
+            }
+            *pending_expense.get_details_mut() = expense.details.clone();
+            let calculated_expense: CalculatedExpense = pending_expense
+                .finalize()
+                .map_err(|_| SessionError)?
+                .calculate();
+            calculated_expenses.push(calculated_expense);
+        }
+
+        let mut split: Split =
+            Split::new(calculated_expenses.iter().collect(), tax, tip).map_err(|_| SessionError)?;
+        for (user, amount) in payers {
+            split
+                .add_payer(self.member(user.get_name())?, *amount)
+                .map_err(|_| SessionError)?;
+        }
+
+        Ok(read(&split.process().map_err(|_| SessionError)?))
+    }
+
+    fn member(&self, name: &str) -> Result<&User, SessionError> {
+        self.roster
+            .iter()
+            .find(|u| u.get_name() == name)
+            .ok_or(SessionError)
+    }
+
+    fn draft(
+        &self,
+        name: &str,
+        amount: f32,
+        participants: &[&User],
+    ) -> Result<DraftExpense, SessionError> {
+        PendingExpense::new(name, amount).map_err(|_| SessionError)?;
+        if participants.is_empty() || participants.iter().any(|p| !self.roster.contains(p)) {
+            return Err(SessionError);
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        for participant in participants {
+            if !names.iter().any(|n| n == participant.get_name()) {
+                names.push(participant.get_name().to_string());
+            }
+        }
+
+        Ok(DraftExpense {
+            name: name.to_string(),
+            amount,
+            participants: names,
+            details: Details::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        let roster: Vec<User> = vec![User::new("alice").unwrap(), User::new("bob").unwrap()];
+        Session::new(None, roster, BTreeMap::new()).unwrap()
+    }
+
+    #[test]
+    fn test_new_failure_roster_is_empty() {
+        let maybe_session = Session::new(None, Vec::new(), BTreeMap::new());
+        assert_eq!(maybe_session.unwrap_err(), SessionError);
+    }
+
+    #[test]
+    fn test_add_expense_failure() {
+        let mut session: Session = session();
+        let stranger: User = User::new("carol").unwrap();
+        let alice: User = session.get_roster()[0].clone();
+
+        assert_eq!(session.add_expense("", 1.00, &[&alice]), Err(SessionError));
+        assert_eq!(
+            session.add_expense("wine", -1.00, &[&alice]),
+            Err(SessionError)
+        );
+        assert_eq!(session.add_expense("wine", 1.00, &[]), Err(SessionError));
+        assert_eq!(
+            session.add_expense("wine", 1.00, &[&stranger]),
+            Err(SessionError)
+        );
+        assert!(session.get_expenses().is_empty());
+    }
+
+    #[test]
+    fn test_edit_and_remove_expense() {
+        let mut session: Session = session();
+        let (alice, bob): (User, User) = (
+            session.get_roster()[0].clone(),
+            session.get_roster()[1].clone(),
+        );
+        session
+            .add_expense("pizza", 30.00, &[&alice, &bob])
+            .unwrap();
+        session.add_expense("wine", 12.00, &[&alice]).unwrap();
+
+        session.edit_name(0, "pizzas").unwrap();
+        session.edit_amount(0, 36.00).unwrap();
+        session.edit_participants(1, &[&bob]).unwrap();
+        assert_eq!(session.edit_amount(0, -1.00), Err(SessionError));
+        assert_eq!(session.edit_name(2, "soda"), Err(SessionError));
+        assert_eq!(
+            session.edit_name_and_amount(0, "", 40.00),
+            Err(SessionError)
+        );
+
+        assert_eq!(session.get_expenses()[0].get_name(), "pizzas");
+        assert_eq!(session.get_expenses()[0].get_amount(), 36.00);
+        assert_eq!(
+            session.get_expenses()[1].get_participants(),
+            &["bob".to_string()]
+        );
+
+        let removed: DraftExpense = session.remove_expense(0).unwrap();
+        assert_eq!(removed.get_name(), "pizzas");
+        assert_eq!(session.get_expenses().len(), 1);
+        assert_eq!(session.remove_expense(1), Err(SessionError));
+    }
+
+    #[test]
+    fn test_preview_and_split() {
+        let mut session: Session = session();
+        let (alice, bob): (User, User) = (
+            session.get_roster()[0].clone(),
+            session.get_roster()[1].clone(),
+        );
+        session
+            .add_expense("pizza", 30.00, &[&alice, &bob])
+            .unwrap();
+        session.add_expense("wine", 12.00, &[&alice]).unwrap();
+
+        let preview: Breakdown = session.preview().unwrap();
+        assert_eq!(preview.rows[0].total, 27.00);
+        assert_eq!(preview.rows[1].total, 15.00);
+
+        let breakdown: Breakdown = session.split(4.20, 0.00, &[(&bob, 46.20)]).unwrap();
+        assert_eq!(breakdown.rows[0].total, 29.70);
+        assert_eq!(breakdown.rows[0].balance, Some(-29.70));
+        assert_eq!(breakdown.rows[1].balance, Some(29.70));
+    }
+
+    #[test]
+    fn test_details_and_subtotals_matching() {
+        let mut session: Session = session();
+        let (alice, bob): (User, User) = (
+            session.get_roster()[0].clone(),
+            session.get_roster()[1].clone(),
+        );
+        session
+            .add_expense("groceries", 30.00, &[&alice, &bob])
+            .unwrap();
+        session.add_expense("wine", 12.00, &[&alice]).unwrap();
+        let mut details: Details = Details::new();
+        details.add_tag("weekly").unwrap();
+        details.set_notes("from the market").unwrap();
+        session.set_details(0, details.clone()).unwrap();
+        session.edit_participants(0, &[&bob]).unwrap();
+        assert_eq!(session.set_details(2, Details::new()), Err(SessionError));
+
+        assert_eq!(session.get_expenses()[0].get_details(), &details);
+        assert_eq!(session.merged_details(), details);
+
+        let mut weekly: Filter = Filter::new();
+        weekly.add_tag("weekly").unwrap();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert_eq!(
+            session.subtotals_matching(date, &weekly).unwrap(),
+            BTreeMap::from([("bob".to_string(), 30.00)])
+        );
+    }
+
+    #[test]
+    fn test_preview_failure_no_expenses() {
+        let session: Session = session();
+        assert_eq!(session.preview().unwrap_err(), SessionError);
+    }
+
+    #[test]
+    fn test_add_and_remove_participant() {
+        let mut session: Session = session();
+        let alice: User = session.get_roster()[0].clone();
+        session.add_expense("pizza", 30.00, &[&alice]).unwrap();
+
+        assert_eq!(
+            session.add_participant(User::new("ALICE").unwrap()),
+            Err(SessionError)
+        );
+        session
+            .add_participant(User::new("carol").unwrap())
+            .unwrap();
+        assert_eq!(session.get_roster().len(), 3);
+
+        assert_eq!(session.remove_participant("alice"), Err(SessionError));
+        assert_eq!(
+            session.remove_participant("carol").unwrap().get_name(),
+            "carol"
+        );
+        assert_eq!(session.get_roster().len(), 2);
+    }
+
+    #[test]
+    fn test_set_tax_and_tip() {
+        let mut session: Session = session();
+        let alice: User = session.get_roster()[0].clone();
+        session.add_expense("pizza", 30.00, &[&alice]).unwrap();
+
+        assert_eq!(session.set_tax_and_tip(-1.00, 0.00), Err(SessionError));
+        assert_eq!(session.get_tax_and_tip(), None);
+
+        session.set_tax_and_tip(2.40, 5.00).unwrap();
+        assert_eq!(session.get_tax_and_tip(), Some((2.40, 5.00)));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut session: Session = session();
+        let (alice, bob): (User, User) = (
+            session.get_roster()[0].clone(),
+            session.get_roster()[1].clone(),
+        );
+        session
+            .add_expense("pizza", 30.00, &[&alice, &bob])
+            .unwrap();
+        session.set_tax_and_tip(2.40, 5.00).unwrap();
+
+        let path = std::env::temp_dir().join(format!("heesab-session-{}.json", std::process::id()));
+        session.save(&path).unwrap();
+        let loaded: Session = Session::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, session);
+    }
+
+    #[test]
+    fn test_load_failure_missing_file() {
+        let path = std::env::temp_dir().join("heesab-session-does-not-exist.json");
+        assert_eq!(Session::load(&path).unwrap_err(), SessionError);
+    }
+}
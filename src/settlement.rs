@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::rounding::{from_cents, Round};
+use crate::user::User;
+
+#[derive(Debug, PartialEq)]
+pub struct SettlementError;
+
+/// A repayment from one user to another. Settlements move money between users without being
+/// an expense, so they only change outstanding balances.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Settlement {
+    from: String,
+    to: String,
+    amount: f32,
+    date: NaiveDate,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    reconciled: bool,
+}
+
+impl Settlement {
+    pub fn new(
+        from: &User,
+        to: &User,
+        amount: f32,
+        date: NaiveDate,
+    ) -> Result<Self, SettlementError> {
+        if from == to {
+            return Err(SettlementError);
+        }
+
+        if amount <= 0_f32 {
+            return Err(SettlementError);
+        }
+
+        Ok(Settlement {
+            from: from.get_name().to_string(),
+            to: to.get_name().to_string(),
+            amount: amount.to_two_decimals(),
+            date,
+            reconciled: false,
+        })
+    }
+
+    /// Suggests repayments that clear every balance from `Ledger::balances`, pairing whoever
+    /// owes the most with whoever is owed the most until everyone is settled up. This needs at
+    /// most one fewer repayment than there are people with a balance.
+    pub fn suggest(balances: &BTreeMap<String, f32>, date: NaiveDate) -> Vec<Self> {
+        let mut owed: Vec<(String, i64)> = Vec::new();
+        let mut owing: Vec<(String, i64)> = Vec::new();
+        for (name, balance) in balances {
+            match balance.to_cents() {
+                cents if cents > 0 => owed.push((name.clone(), cents)),
+                cents if cents < 0 => owing.push((name.clone(), -cents)),
+                _ => {}
+            }
+        }
+
+        let mut settlements: Vec<Settlement> = Vec::new();
+        loop {
+            owed.sort_by_key(|(_, cents)| std::cmp::Reverse(*cents));
+            owing.sort_by_key(|(_, cents)| std::cmp::Reverse(*cents));
+            let (Some(to), Some(from)) = (owed.first_mut(), owing.first_mut()) else {
+                break;
+            };
+            let cents: i64 = to.1.min(from.1);
+            settlements.push(Settlement {
+                from: from.0.clone(),
+                to: to.0.clone(),
+                amount: from_cents(cents),
+                date,
+                reconciled: false,
+            });
+            to.1 -= cents;
+            from.1 -= cents;
+            owed.retain(|(_, cents)| *cents > 0);
+            owing.retain(|(_, cents)| *cents > 0);
+        }
+        settlements
+    }
+
+    pub fn get_from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn get_to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn get_amount(&self) -> f32 {
+        self.amount
+    }
+
+    pub fn get_date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// Whether the repayment was found on a bank statement.
+    pub fn is_reconciled(&self) -> bool {
+        self.reconciled
+    }
+
+    pub fn reconcile(&mut self) {
+        self.reconciled = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_success() {
+        let user_one: User = User::new("user_one").unwrap();
+        let user_two: User = User::new("user_two").unwrap();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+
+        let settlement: Settlement = Settlement::new(&user_one, &user_two, 20.00, date).unwrap();
+
+        assert_eq!(settlement.get_from(), "user_one");
+        assert_eq!(settlement.get_to(), "user_two");
+        assert_eq!(settlement.get_amount(), 20.00);
+        assert_eq!(settlement.get_date(), date);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+        let balances: BTreeMap<String, f32> = BTreeMap::from([
+            ("alice".to_string(), 50.00),
+            ("bob".to_string(), -30.00),
+            ("carol".to_string(), -15.50),
+            ("dave".to_string(), -4.50),
+            ("erin".to_string(), 0.00),
+        ]);
+
+        let suggested: Vec<(String, String, f32)> = Settlement::suggest(&balances, date)
+            .into_iter()
+            .map(|s| (s.from, s.to, s.amount))
+            .collect();
+        assert_eq!(
+            suggested,
+            vec![
+                ("bob".to_string(), "alice".to_string(), 30.00),
+                ("carol".to_string(), "alice".to_string(), 15.50),
+                ("dave".to_string(), "alice".to_string(), 4.50),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_failure_same_user() {
+        let user_one: User = User::new("user_one").unwrap();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+
+        let maybe_settlement: Result<Settlement, SettlementError> =
+            Settlement::new(&user_one, &user_one, 20.00, date);
+        assert_eq!(maybe_settlement.unwrap_err(), SettlementError);
+    }
+
+    #[test]
+    fn test_new_failure_amount_not_positive() {
+        let user_one: User = User::new("user_one").unwrap();
+        let user_two: User = User::new("user_two").unwrap();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+
+        let maybe_settlement: Result<Settlement, SettlementError> =
+            Settlement::new(&user_one, &user_two, 0.00, date);
+        assert_eq!(maybe_settlement.unwrap_err(), SettlementError);
+    }
+}
@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 
-use crate::expense::Expense;
-use crate::rounding::Round;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::details::Filter;
+use crate::expense::CalculatedExpense;
+use crate::rounding::{allocate, from_cents, Round};
 use crate::upserting::Upsertable;
 use crate::user::User;
 
@@ -10,11 +14,23 @@ pub struct SplitError;
 
 #[derive(Debug, PartialEq)]
 pub struct Split<'a> {
-    expenses: Vec<&'a Expense<'a>>,
+    expenses: Vec<&'a CalculatedExpense<'a>>,
+    payers: HashMap<&'a User, f32>,
+    subtotal: f32,
+    tax: f32,
+    tip: f32,
+    total: f32,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ProcessedSplit<'a> {
+    expenses: Vec<&'a CalculatedExpense<'a>>,
     user_subtotals: HashMap<&'a User, f32>,
     user_taxes: HashMap<&'a User, f32>,
     user_tips: HashMap<&'a User, f32>,
     user_totals: HashMap<&'a User, f32>,
+    user_paid: HashMap<&'a User, f32>,
+    user_balances: HashMap<&'a User, f32>,
     subtotal: f32,
     tax: f32,
     tip: f32,
@@ -22,14 +38,22 @@ pub struct Split<'a> {
 }
 
 impl<'a> Split<'a> {
-    pub fn new(expenses: Vec<&'a Expense<'a>>, tax: f32, tip: f32) -> Result<Self, SplitError> {
-        if expenses.len() == 0 {
+    /// `tax` and `tip` apply to the whole split and are shared in proportion to each user's
+    /// subtotal, on top of any tax and tip already attached to individual expenses.
+    pub fn new(
+        expenses: Vec<&'a CalculatedExpense<'a>>,
+        tax: f32,
+        tip: f32,
+    ) -> Result<Self, SplitError> {
+        if expenses.is_empty() {
             return Err(SplitError);
         }
 
         let mut subtotal: f32 = 0_f32;
+        let mut expense_extras: f32 = 0_f32;
         for expense in &expenses {
             subtotal += expense.get_amount();
+            expense_extras += expense.get_tax() + expense.get_tip();
         }
 
         if subtotal < 0_f32 {
@@ -46,82 +70,271 @@ impl<'a> Split<'a> {
 
         Ok(Split {
             expenses,
-            user_subtotals: HashMap::new(),
-            user_taxes: HashMap::new(),
-            user_tips: HashMap::new(),
-            user_totals: HashMap::new(),
+            payers: HashMap::new(),
             subtotal,
             tax,
             tip,
-            total: subtotal + tax + tip,
+            total: (subtotal + expense_extras + tax + tip).to_two_decimals(),
         })
     }
 
-    fn calculate_subtotals(&mut self) {
+    /// Records a payment towards the split as a whole, for bills paid without attributing
+    /// payments to individual expenses.
+    pub fn add_payer(&mut self, user: &'a User, amount: f32) -> Result<(), SplitError> {
+        if amount <= 0_f32 {
+            return Err(SplitError);
+        }
+        self.payers.upsert_all(&HashMap::from([(user, amount)]));
+        Ok(())
+    }
+
+    fn users(&self) -> Vec<&'a User> {
+        let mut users: Vec<&'a User> = Vec::new();
+        for expense in &self.expenses {
+            for participant in expense.get_participants() {
+                if !users.contains(participant) {
+                    users.push(participant);
+                }
+            }
+        }
+        users
+    }
+
+    fn calculate_subtotals(&self) -> HashMap<&'a User, f32> {
+        let mut user_subtotals: HashMap<&'a User, f32> = HashMap::new();
         for expense in &self.expenses {
-            self.user_subtotals
-                .upsert_all(&expense.get_user_to_amount());
+            user_subtotals.upsert_all(&expense.get_user_to_amount());
         }
+        user_subtotals
     }
 
-    fn calculate_taxes_and_tips(&mut self) {
-        let mut remaining_tax: f32 = self.tax;
-        let mut remaining_tip: f32 = self.tip;
+    fn calculate_taxes_and_tips(
+        &self,
+        user_subtotals: &HashMap<&'a User, f32>,
+    ) -> (HashMap<&'a User, f32>, HashMap<&'a User, f32>) {
+        let users: Vec<&'a User> = self.users();
+        let weights: Vec<f32> = users.iter().map(|u| user_subtotals[u]).collect();
+        let taxes: Vec<f32> = allocate(self.tax, &weights);
+        let tips: Vec<f32> = allocate(self.tip, &weights);
 
-        for (k, v) in &self.user_subtotals {
-            let percent: f32 = v / self.subtotal;
+        let mut user_taxes: HashMap<&'a User, f32> = users.iter().copied().zip(taxes).collect();
+        let mut user_tips: HashMap<&'a User, f32> = users.iter().copied().zip(tips).collect();
 
-            let tax: f32 = (self.tax * percent).to_two_decimals();
-            let tip: f32 = (self.tip * percent).to_two_decimals();
+        for expense in &self.expenses {
+            user_taxes.upsert_all(&expense.get_user_to_tax());
+            user_tips.upsert_all(&expense.get_user_to_tip());
+        }
 
-            if tax < remaining_tax {
-                self.user_taxes.insert(k, tax);
-            } else {
-                self.user_taxes.insert(k, remaining_tax);
-            }
+        (user_taxes, user_tips)
+    }
 
-            if tip < remaining_tip {
-                self.user_tips.insert(k, tip);
-            } else {
-                self.user_tips.insert(k, remaining_tip);
+    fn calculate_paid(&self) -> HashMap<&'a User, f32> {
+        let mut user_paid: HashMap<&'a User, f32> = HashMap::new();
+        for expense in &self.expenses {
+            user_paid.upsert_all(&expense.get_user_to_paid());
+        }
+        user_paid.upsert_all(&self.payers);
+        user_paid
+    }
+
+    /// Fails if payments were recorded, on expenses or on the split, but do not cover the total
+    /// to the cent. Without any payments the split still processes and has no balances.
+    pub fn process(self) -> Result<ProcessedSplit<'a>, SplitError> {
+        let user_subtotals: HashMap<&'a User, f32> = self.calculate_subtotals();
+        let (user_taxes, user_tips) = self.calculate_taxes_and_tips(&user_subtotals);
+
+        let mut user_totals: HashMap<&'a User, f32> = HashMap::new();
+        user_totals.upsert_all(&user_subtotals);
+        user_totals.upsert_all(&user_taxes);
+        user_totals.upsert_all(&user_tips);
+        let user_totals: HashMap<&'a User, f32> = round_all(user_totals);
+
+        let user_paid: HashMap<&'a User, f32> = round_all(self.calculate_paid());
+        let mut user_balances: HashMap<&'a User, f32> = HashMap::new();
+        if !user_paid.is_empty() {
+            let paid: i64 = user_paid.values().map(|v| v.to_cents()).sum();
+            if paid != self.total.to_cents() {
+                return Err(SplitError);
             }
 
-            remaining_tax = (remaining_tax - tax).to_two_decimals();
-            remaining_tip = (remaining_tip - tip).to_two_decimals();
+            for user in user_totals.keys().chain(user_paid.keys()) {
+                let paid: i64 = user_paid.get(user).map_or(0, |v| v.to_cents());
+                let owed: i64 = user_totals.get(user).map_or(0, |v| v.to_cents());
+                user_balances.insert(user, from_cents(paid - owed));
+            }
         }
+
+        Ok(ProcessedSplit {
+            expenses: self.expenses,
+            user_subtotals: round_all(user_subtotals),
+            user_taxes: round_all(user_taxes),
+            user_tips: round_all(user_tips),
+            user_totals,
+            user_paid,
+            user_balances,
+            subtotal: self.subtotal,
+            tax: self.tax,
+            tip: self.tip,
+            total: self.total,
+        })
+    }
+}
+
+fn round_all(map: HashMap<&User, f32>) -> HashMap<&User, f32> {
+    map.into_iter()
+        .map(|(k, v)| (k, v.to_two_decimals()))
+        .collect()
+}
+
+impl<'a> ProcessedSplit<'a> {
+    pub fn get_expenses(&self) -> &[&'a CalculatedExpense<'a>] {
+        &self.expenses
     }
 
-    fn calculate_totals(&mut self) {
-        self.user_totals.upsert_all(&self.user_subtotals);
-        self.user_totals.upsert_all(&self.user_taxes);
-        self.user_totals.upsert_all(&self.user_tips);
+    pub fn get_user_subtotals(&self) -> HashMap<&'a User, f32> {
+        self.user_subtotals.clone()
     }
 
-    pub fn process(&mut self) -> HashMap<&User, f32> {
-        self.calculate_subtotals();
-        self.calculate_taxes_and_tips();
-        self.calculate_totals();
+    pub fn get_user_taxes(&self) -> HashMap<&'a User, f32> {
+        self.user_taxes.clone()
+    }
+
+    pub fn get_user_tips(&self) -> HashMap<&'a User, f32> {
+        self.user_tips.clone()
+    }
 
+    pub fn get_user_totals(&self) -> HashMap<&'a User, f32> {
         self.user_totals.clone()
     }
 
+    pub fn get_user_paid(&self) -> HashMap<&'a User, f32> {
+        self.user_paid.clone()
+    }
+
+    /// What each user paid minus what they consumed. Positive means they are owed money.
+    /// Empty when no payments were recorded.
+    pub fn get_user_balances(&self) -> HashMap<&'a User, f32> {
+        self.user_balances.clone()
+    }
+
+    pub fn get_total(&self) -> f32 {
+        self.total
+    }
+
+    /// Each user's share of the expenses matching `filter`, before tax and tip, such as what
+    /// everyone spent on groceries. Expenses without a time of their own are taken to be on
+    /// `date`, the day of the split.
+    pub fn subtotals_matching(&self, date: NaiveDate, filter: &Filter) -> HashMap<&'a User, f32> {
+        let mut subtotals: HashMap<&'a User, f32> = HashMap::new();
+        for expense in &self.expenses {
+            let expense_date: NaiveDate = expense.get_details().get_date().unwrap_or(date);
+            if filter.matches(expense_date, expense.get_details()) {
+                subtotals.upsert_all(&expense.get_user_to_amount());
+            }
+        }
+        round_all(subtotals)
+    }
+
+    /// An owned copy of the per-user results, ordered by when each user first appears in the
+    /// expenses, with anyone who only paid at the end in name order.
+    pub fn breakdown(&self) -> Breakdown {
+        let mut users: Vec<&'a User> = Vec::new();
+        for expense in &self.expenses {
+            for participant in expense.get_participants() {
+                if !users.contains(participant) {
+                    users.push(participant);
+                }
+            }
+        }
+        let mut payers_only: Vec<&'a User> = self
+            .user_paid
+            .keys()
+            .filter(|k| !users.contains(k))
+            .copied()
+            .collect();
+        payers_only.sort_by_key(|u| u.get_name());
+        users.extend(payers_only);
+
+        let rows: Vec<BreakdownRow> = users
+            .iter()
+            .map(|user| BreakdownRow {
+                name: user.get_name().to_string(),
+                subtotal: *self.user_subtotals.get(user).unwrap_or(&0_f32),
+                tax: *self.user_taxes.get(user).unwrap_or(&0_f32),
+                tip: *self.user_tips.get(user).unwrap_or(&0_f32),
+                total: *self.user_totals.get(user).unwrap_or(&0_f32),
+                paid: self
+                    .user_balances
+                    .get(user)
+                    .map(|_| *self.user_paid.get(user).unwrap_or(&0_f32)),
+                balance: self.user_balances.get(user).copied(),
+            })
+            .collect();
+
+        let cents =
+            |f: fn(&BreakdownRow) -> f32| from_cents(rows.iter().map(|r| f(r).to_cents()).sum());
+        Breakdown {
+            subtotal: cents(|r| r.subtotal),
+            tax: cents(|r| r.tax),
+            tip: cents(|r| r.tip),
+            total: self.total,
+            rows,
+        }
+    }
+
+    pub fn print(&self) {
+        self.breakdown().print();
+    }
+}
+
+/// The result of a processed split as plain owned data, for display or serialization once
+/// the users and expenses it was calculated from are gone.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Breakdown {
+    pub rows: Vec<BreakdownRow>,
+    pub subtotal: f32,
+    pub tax: f32,
+    pub tip: f32,
+    pub total: f32,
+}
+
+/// One user's share of a split. `paid` and `balance` are only set when payments were recorded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownRow {
+    pub name: String,
+    pub subtotal: f32,
+    pub tax: f32,
+    pub tip: f32,
+    pub total: f32,
+    pub paid: Option<f32>,
+    pub balance: Option<f32>,
+}
+
+impl Breakdown {
     pub fn print(&self) {
         println!();
-        for (k, v) in &self.user_totals {
-            let subtotal: &f32 = self.user_subtotals.get(k).unwrap();
-            let tax: &f32 = self.user_taxes.get(k).unwrap();
-            let tip: &f32 = self.user_tips.get(k).unwrap();
-            println!(
-                "{} - subtotal: ${}, tax: ${}, tip: ${}, total: ${}",
-                k, subtotal, tax, tip, v
-            )
+        for row in &self.rows {
+            match (row.paid, row.balance) {
+                (Some(paid), Some(balance)) => println!(
+                    "{} - subtotal: ${}, tax: ${}, tip: ${}, total: ${}, paid: ${}, balance: ${}",
+                    row.name, row.subtotal, row.tax, row.tip, row.total, paid, balance
+                ),
+                _ => println!(
+                    "{} - subtotal: ${}, tax: ${}, tip: ${}, total: ${}",
+                    row.name, row.subtotal, row.tax, row.tip, row.total
+                ),
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::expense::PendingExpense;
+    use chrono::DateTime;
+
+    use crate::details::Details;
+    use crate::expense::{Expense, PendingExpense};
+    use crate::taxonomy::Taxonomy;
 
     use super::*;
 
@@ -137,12 +350,12 @@ mod tests {
 
         pending_expense_one.add_participant(&user_one);
         pending_expense_one.add_participant(&user_two);
-        let expense_one: Expense = pending_expense_one.finalize().unwrap();
+        let expense_one: CalculatedExpense = pending_expense_one.finalize().unwrap().calculate();
 
         pending_expense_two.add_participant(&user_two);
-        let expense_two: Expense = pending_expense_two.finalize().unwrap();
+        let expense_two: CalculatedExpense = pending_expense_two.finalize().unwrap().calculate();
 
-        let expenses: Vec<&Expense> = vec![&expense_one, &expense_two];
+        let expenses: Vec<&CalculatedExpense> = vec![&expense_one, &expense_two];
         let tax: f32 = 2.20;
         let tip: f32 = 3.45;
         let subtotal: f32 = expenses.iter().map(|e| e.get_amount()).sum();
@@ -151,10 +364,7 @@ mod tests {
 
         let expected: Split = Split {
             expenses: vec![&expense_one, &expense_two],
-            user_subtotals: HashMap::new(),
-            user_taxes: HashMap::new(),
-            user_tips: HashMap::new(),
-            user_totals: HashMap::new(),
+            payers: HashMap::new(),
             subtotal,
             tax,
             tip,
@@ -188,12 +398,12 @@ mod tests {
 
         pending_expense_one.add_participant(&user_one);
         pending_expense_one.add_participant(&user_two);
-        let expense_one: Expense = pending_expense_one.finalize().unwrap();
+        let expense_one: CalculatedExpense = pending_expense_one.finalize().unwrap().calculate();
 
         pending_expense_two.add_participant(&user_two);
-        let expense_two: Expense = pending_expense_two.finalize().unwrap();
+        let expense_two: CalculatedExpense = pending_expense_two.finalize().unwrap().calculate();
 
-        let expenses: Vec<&Expense> = vec![&expense_one, &expense_two];
+        let expenses: Vec<&CalculatedExpense> = vec![&expense_one, &expense_two];
         let tax: f32 = -2.20;
         let tip: f32 = 3.45;
 
@@ -214,12 +424,12 @@ mod tests {
 
         pending_expense_one.add_participant(&user_one);
         pending_expense_one.add_participant(&user_two);
-        let expense_one: Expense = pending_expense_one.finalize().unwrap();
+        let expense_one: CalculatedExpense = pending_expense_one.finalize().unwrap().calculate();
 
         pending_expense_two.add_participant(&user_two);
-        let expense_two: Expense = pending_expense_two.finalize().unwrap();
+        let expense_two: CalculatedExpense = pending_expense_two.finalize().unwrap().calculate();
 
-        let expenses: Vec<&Expense> = vec![&expense_one, &expense_two];
+        let expenses: Vec<&CalculatedExpense> = vec![&expense_one, &expense_two];
         let tax: f32 = 2.20;
         let tip: f32 = -3.45;
 
@@ -240,20 +450,20 @@ mod tests {
 
         pending_expense_one.add_participant(&user_one);
         pending_expense_one.add_participant(&user_two);
-        let mut expense_one: Expense = pending_expense_one.finalize().unwrap();
-        expense_one.calculate();
+        let expense_one: Expense = pending_expense_one.finalize().unwrap();
+        let expense_one: CalculatedExpense = expense_one.calculate();
 
         pending_expense_two.add_participant(&user_two);
-        let mut expense_two: Expense = pending_expense_two.finalize().unwrap();
-        expense_two.calculate();
+        let expense_two: Expense = pending_expense_two.finalize().unwrap();
+        let expense_two: CalculatedExpense = expense_two.calculate();
 
-        let expenses: Vec<&Expense> = vec![&expense_one, &expense_two];
+        let expenses: Vec<&CalculatedExpense> = vec![&expense_one, &expense_two];
         let subtotal: f32 = 12.75;
         let tax: f32 = 2.20;
         let tip: f32 = 3.45;
 
-        let mut split: Split = Split::new(expenses, tax, tip).unwrap();
-        _ = split.process();
+        let split: Split = Split::new(expenses, tax, tip).unwrap();
+        let processed_split: ProcessedSplit = split.process().unwrap();
 
         let mut user_subtotals: HashMap<&User, f32> = HashMap::new();
         user_subtotals.insert(&user_one, 2.75);
@@ -271,18 +481,150 @@ mod tests {
         user_totals.insert(&user_one, 3.96);
         user_totals.insert(&user_two, 14.44);
 
-        let expected: Split = Split {
+        let expected: ProcessedSplit = ProcessedSplit {
             expenses: vec![&expense_one, &expense_two],
             user_subtotals,
             user_taxes,
             user_tips,
             user_totals,
+            user_paid: HashMap::new(),
+            user_balances: HashMap::new(),
             subtotal,
             tax,
             tip,
             total: subtotal + tax + tip,
         };
 
-        assert_eq!(split, expected);
+        assert_eq!(processed_split, expected);
+    }
+
+    #[test]
+    fn test_process_with_payers() {
+        let user_one: User = User::new("user_one").unwrap();
+        let user_two: User = User::new("user_two").unwrap();
+        let user_three: User = User::new("user_three").unwrap();
+
+        let mut pending_expense_one: PendingExpense =
+            PendingExpense::new("pending_expense_one", 30.00).unwrap();
+        pending_expense_one.add_participant(&user_one);
+        pending_expense_one.add_participant(&user_two);
+        pending_expense_one.add_participant(&user_three);
+        pending_expense_one.set_tip(3.00).unwrap();
+        pending_expense_one.add_payer(&user_one, 20.00).unwrap();
+        pending_expense_one.add_payer(&user_two, 13.00).unwrap();
+        let expense_one: CalculatedExpense = pending_expense_one.finalize().unwrap().calculate();
+
+        let mut pending_expense_two: PendingExpense =
+            PendingExpense::new("pending_expense_two", 10.00).unwrap();
+        pending_expense_two.add_participant(&user_three);
+        let expense_two: CalculatedExpense = pending_expense_two.finalize().unwrap().calculate();
+
+        let mut split: Split = Split::new(vec![&expense_one, &expense_two], 0.00, 0.00).unwrap();
+        split.add_payer(&user_three, 10.00).unwrap();
+        let processed_split: ProcessedSplit = split.process().unwrap();
+
+        let mut expected_balances: HashMap<&User, f32> = HashMap::new();
+        expected_balances.insert(&user_one, 9.00);
+        expected_balances.insert(&user_two, 2.00);
+        expected_balances.insert(&user_three, -11.00);
+        assert_eq!(processed_split.get_user_balances(), expected_balances);
+    }
+
+    #[test]
+    fn test_process_failure_payments_do_not_cover_total() {
+        let user_one: User = User::new("user_one").unwrap();
+
+        let mut pending_expense: PendingExpense =
+            PendingExpense::new("pending_expense", 10.00).unwrap();
+        pending_expense.add_participant(&user_one);
+        let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+
+        let mut split: Split = Split::new(vec![&expense], 1.00, 0.00).unwrap();
+        split.add_payer(&user_one, 10.00).unwrap();
+
+        assert_eq!(split.process().unwrap_err(), SplitError);
+    }
+
+    #[test]
+    fn test_add_payer_failure_not_positive() {
+        let user_one: User = User::new("user_one").unwrap();
+
+        let mut pending_expense: PendingExpense =
+            PendingExpense::new("pending_expense", 10.00).unwrap();
+        pending_expense.add_participant(&user_one);
+        let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+
+        let mut split: Split = Split::new(vec![&expense], 0.00, 0.00).unwrap();
+        assert_eq!(split.add_payer(&user_one, 0.00), Err(SplitError));
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let user_one: User = User::new("user_one").unwrap();
+        let user_two: User = User::new("user_two").unwrap();
+        let user_three: User = User::new("user_three").unwrap();
+
+        let mut pending_expense: PendingExpense =
+            PendingExpense::new("pending_expense", 10.00).unwrap();
+        pending_expense.add_participant(&user_two);
+        pending_expense.add_participant(&user_one);
+        let expense: CalculatedExpense = pending_expense.finalize().unwrap().calculate();
+
+        let mut split: Split = Split::new(vec![&expense], 1.00, 0.00).unwrap();
+        split.add_payer(&user_three, 11.00).unwrap();
+        let breakdown: Breakdown = split.process().unwrap().breakdown();
+
+        let names: Vec<&str> = breakdown.rows.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["user_two", "user_one", "user_three"]);
+        assert_eq!(breakdown.rows[0].total, 5.50);
+        assert_eq!(breakdown.rows[2].paid, Some(11.00));
+        assert_eq!(breakdown.rows[2].balance, Some(11.00));
+        assert_eq!(breakdown.tax, 1.00);
+        assert_eq!(breakdown.total, 11.00);
+    }
+
+    #[test]
+    fn test_subtotals_matching() {
+        let user_one: User = User::new("user_one").unwrap();
+        let user_two: User = User::new("user_two").unwrap();
+        let taxonomy: Taxonomy = Taxonomy::default();
+
+        let mut groceries: PendingExpense = PendingExpense::new("groceries", 30.00).unwrap();
+        groceries.add_participant(&user_one);
+        groceries.add_participant(&user_two);
+        let details: &mut Details = groceries.get_details_mut();
+        details.set_category(&taxonomy, "groceries").unwrap();
+        details.add_tag("#weekly").unwrap();
+        let groceries: CalculatedExpense = groceries.finalize().unwrap().calculate();
+
+        let mut wine: PendingExpense = PendingExpense::new("wine", 12.00).unwrap();
+        wine.add_participant(&user_one);
+        wine.get_details_mut()
+            .set_time(DateTime::parse_from_rfc3339("2026-03-02T20:00:00+01:00").unwrap());
+        let wine: CalculatedExpense = wine.finalize().unwrap().calculate();
+
+        let split: ProcessedSplit = Split::new(vec![&groceries, &wine], 4.20, 0.00)
+            .unwrap()
+            .process()
+            .unwrap();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        let mut food: Filter = Filter::new();
+        food.set_category("food");
+        assert_eq!(
+            split.subtotals_matching(date, &food),
+            HashMap::from([(&user_one, 15.00), (&user_two, 15.00)])
+        );
+
+        let mut later: Filter = Filter::new();
+        later.set_since(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap());
+        assert_eq!(
+            split.subtotals_matching(date, &later),
+            HashMap::from([(&user_one, 12.00)])
+        );
+        assert_eq!(
+            split.subtotals_matching(date, &Filter::new()),
+            HashMap::from([(&user_one, 27.00), (&user_two, 15.00)])
+        );
     }
 }
@@ -0,0 +1,508 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+use quick_xml::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::expense::{PendingExpense, PendingExpenseError};
+use crate::ledger::Ledger;
+use crate::rounding::{from_cents, parse_cents, Round};
+use crate::user::User;
+
+/// How many days apart a card transaction and a recorded expense can be and still be taken
+/// for the same purchase, since cards often post a day or two after paying.
+const DUPLICATE_DAYS: i64 = 3;
+
+#[derive(Debug, PartialEq)]
+pub struct StatementError(String);
+
+impl StatementError {
+    fn new(message: &str) -> Self {
+        StatementError(message.to_string())
+    }
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Bank and card statement file formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatementFormat {
+    /// OFX, in both its SGML 1.x and XML 2.x forms. QFX is OFX with extra Quicken fields.
+    Ofx,
+    Qif,
+    /// ISO 20022 bank-to-customer statements, as exported by most European banks.
+    Camt053,
+}
+
+/// A statement line. The amount is what left the account, so money coming in, such as a
+/// repayment from a friend, is negative. The merchant is the other party.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transaction {
+    date: NaiveDate,
+    merchant: String,
+    amount: f32,
+}
+
+/// A transaction ready to be shared: an expense paid in full by whoever imported the
+/// statement, with no participants yet.
+#[derive(Debug)]
+pub struct Draft<'a> {
+    transaction: Transaction,
+    expense: PendingExpense<'a>,
+    duplicate: Option<String>,
+}
+
+impl StatementFormat {
+    /// Picks the format from a file extension: `ofx`, `qfx`, `qif` or `xml`.
+    pub fn from_extension(extension: &str) -> Result<Self, StatementError> {
+        match extension.to_lowercase().as_str() {
+            "ofx" | "qfx" => Ok(StatementFormat::Ofx),
+            "qif" => Ok(StatementFormat::Qif),
+            "xml" | "camt" | "053" => Ok(StatementFormat::Camt053),
+            _ => Err(StatementError::new(
+                "statements must be OFX, QFX, QIF or CAMT.053 files",
+            )),
+        }
+    }
+}
+
+impl Transaction {
+    pub fn get_date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn get_merchant(&self) -> &str {
+        &self.merchant
+    }
+
+    pub fn get_amount(&self) -> f32 {
+        self.amount
+    }
+}
+
+impl<'a> Draft<'a> {
+    pub fn get_transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+
+    /// Add participants here before finalizing.
+    pub fn get_expense_mut(&mut self) -> &mut PendingExpense<'a> {
+        &mut self.expense
+    }
+
+    /// The name of a ledger entry that looks like the same purchase, if any.
+    pub fn get_duplicate(&self) -> Option<&str> {
+        self.duplicate.as_deref()
+    }
+
+    pub fn into_expense(self) -> PendingExpense<'a> {
+        self.expense
+    }
+}
+
+/// Reads the money that left the account. Incoming payments, such as refunds or salary, are
+/// left out since they are not expenses.
+pub fn parse(format: StatementFormat, contents: &str) -> Result<Vec<Transaction>, StatementError> {
+    Ok(parse_all(format, contents)?
+        .into_iter()
+        .filter(|t| t.amount > 0_f32)
+        .collect())
+}
+
+/// Reads every transaction, incoming ones included.
+pub fn parse_all(
+    format: StatementFormat,
+    contents: &str,
+) -> Result<Vec<Transaction>, StatementError> {
+    match format {
+        StatementFormat::Ofx => parse_ofx(contents),
+        StatementFormat::Qif => parse_qif(contents),
+        StatementFormat::Camt053 => parse_camt053(contents),
+    }
+}
+
+/// Turns each transaction into an expense paid by `payer`, and flags any that `payer` has
+/// already recorded: an entry they paid the same amount for within a few days.
+pub fn drafts<'a>(
+    transactions: Vec<Transaction>,
+    payer: &'a User,
+    ledger: &Ledger,
+) -> Result<Vec<Draft<'a>>, PendingExpenseError> {
+    let mut drafts: Vec<Draft<'a>> = Vec::new();
+    for transaction in transactions {
+        let mut expense: PendingExpense<'a> =
+            PendingExpense::new(&transaction.merchant, transaction.amount)?;
+        expense.add_payer(payer, transaction.amount)?;
+        let duplicate: Option<String> = ledger
+            .get_entries()
+            .iter()
+            .find(|e| {
+                (e.get_date() - transaction.date).num_days().abs() <= DUPLICATE_DAYS
+                    && e.get_paid()
+                        .get(payer.get_name())
+                        .is_some_and(|paid| paid.to_cents() == transaction.amount.to_cents())
+            })
+            .map(|e| e.get_name().to_string());
+        drafts.push(Draft {
+            transaction,
+            expense,
+            duplicate,
+        });
+    }
+    Ok(drafts)
+}
+
+/// Spending is negative on statements, so amounts are flipped: what left the account is
+/// positive.
+fn spent(merchant: &str, date: NaiveDate, signed_cents: i64) -> Transaction {
+    Transaction {
+        date,
+        merchant: merchant.split_whitespace().collect::<Vec<&str>>().join(" "),
+        amount: from_cents(-signed_cents),
+    }
+}
+
+/// `<STMTTRN>` blocks with `<DTPOSTED>`, `<TRNAMT>` and `<NAME>` or `<MEMO>`. In OFX 1.x
+/// leaf elements are not closed, so each value runs to the next tag or line end.
+fn parse_ofx(contents: &str) -> Result<Vec<Transaction>, StatementError> {
+    let mut transactions: Vec<Transaction> = Vec::new();
+    for block in contents.split("<STMTTRN>").skip(1) {
+        let block: &str = block.split("</STMTTRN>").next().unwrap_or_default();
+        let value = |tag: &str| -> Option<String> {
+            let start: usize = block.find(&format!("<{}>", tag))? + tag.len() + 2;
+            let value: &str = block[start..].split(['<', '\n', '\r']).next()?.trim();
+            let value: String =
+                escape::unescape(value).map_or(value.to_string(), |v| v.to_string());
+            Some(value).filter(|v| !v.is_empty())
+        };
+        let invalid = || StatementError::new("an OFX transaction has no valid date or amount");
+        let date: NaiveDate = value("DTPOSTED")
+            .and_then(|d| NaiveDate::parse_from_str(d.get(..8)?, "%Y%m%d").ok())
+            .ok_or_else(invalid)?;
+        let cents: i64 = value("TRNAMT")
+            .and_then(|a| parse_cents(&a))
+            .ok_or_else(invalid)?;
+        let merchant: String = value("NAME")
+            .or_else(|| value("MEMO"))
+            .unwrap_or_else(|| "Card payment".to_string());
+        transactions.push(spent(&merchant, date, cents));
+    }
+    if transactions.is_empty() && !contents.contains("OFX") {
+        return Err(StatementError::new("this is not an OFX file"));
+    }
+    Ok(transactions)
+}
+
+/// Records of `D` date, `T` amount, `P` payee and `M` memo lines, each ending in `^`.
+fn parse_qif(contents: &str) -> Result<Vec<Transaction>, StatementError> {
+    if !contents.trim_start().starts_with("!Type:") {
+        return Err(StatementError::new("this is not a QIF file"));
+    }
+    let mut transactions: Vec<Transaction> = Vec::new();
+    let (mut date, mut cents, mut payee, mut memo) = (None, None, None, None);
+    for line in contents.lines().map(|l| l.trim()) {
+        let mut chars = line.chars();
+        let (code, value): (Option<char>, &str) = (chars.next(), chars.as_str().trim());
+        match code {
+            Some('D') => date = parse_qif_date(value),
+            Some('T') | Some('U') => cents = parse_cents(value),
+            Some('P') => payee = Some(value.to_string()),
+            Some('M') => memo = Some(value.to_string()),
+            Some('^') => {
+                let (Some(date), Some(cents)) = (date.take(), cents.take()) else {
+                    return Err(StatementError::new(
+                        "a QIF transaction has no valid date or amount",
+                    ));
+                };
+                let merchant: String = payee
+                    .take()
+                    .or(memo.take())
+                    .filter(|m| !m.is_empty())
+                    .unwrap_or_else(|| "Card payment".to_string());
+                transactions.push(spent(&merchant, date, cents));
+            }
+            _ => {}
+        }
+    }
+    Ok(transactions)
+}
+
+/// QIF dates are month first, such as `1/5/2026`, `01/05'26` or `1/ 5/26`. Some banks write
+/// `2026-01-05` instead.
+fn parse_qif_date(value: &str) -> Option<NaiveDate> {
+    let parts: Vec<i32> = value
+        .split(['/', '\'', '-', '.'])
+        .map(|p| p.trim().parse::<i32>())
+        .collect::<Result<Vec<i32>, _>>()
+        .ok()?;
+    let (year, month, day): (i32, i32, i32) = match parts[..] {
+        [year, month, day] if year > 999 => (year, month, day),
+        [month, day, year] if year < 100 => (2000 + year, month, day),
+        [month, day, year] => (year, month, day),
+        _ => return None,
+    };
+    NaiveDate::from_ymd_opt(year, month.try_into().ok()?, day.try_into().ok()?)
+}
+
+/// `<Ntry>` elements with `<Amt>`, `<CdtDbtInd>`, `<BookgDt>` and the other party's name or
+/// the remittance information as the merchant. The other party is the creditor of a debit and
+/// the debtor of a credit.
+fn parse_camt053(contents: &str) -> Result<Vec<Transaction>, StatementError> {
+    let invalid_xml = |e: quick_xml::Error| StatementError(format!("invalid XML: {}", e));
+    let mut reader: Reader<&[u8]> = Reader::from_str(contents);
+    let mut path: Vec<String> = Vec::new();
+    let mut transactions: Vec<Transaction> = Vec::new();
+    let mut seen_statement: bool = false;
+
+    let mut amount: Option<i64> = None;
+    let mut debit: bool = false;
+    let mut date: Option<NaiveDate> = None;
+    let mut creditor: Option<String> = None;
+    let mut debtor: Option<String> = None;
+    let mut remittance: Option<String> = None;
+    let mut text: String = String::new();
+
+    loop {
+        match reader.read_event().map_err(invalid_xml)? {
+            Event::Start(start) => {
+                let name: String = start.local_name().as_ref().to_string();
+                seen_statement |= name == "BkToCstmrStmt";
+                path.push(name);
+                text.clear();
+            }
+            Event::Text(t) => text.push_str(&t.xml10_content()),
+            Event::GeneralRef(r) => {
+                let reference: String = format!("&{};", &*r);
+                text.push_str(
+                    &escape::unescape(&reference).map_or(reference.clone(), |r| r.into()),
+                );
+            }
+            Event::End(_) => {
+                let value: &str = text.trim();
+                let parent: Option<&str> = path.len().checked_sub(2).map(|i| path[i].as_str());
+                let in_entry: bool = path.iter().any(|p| p == "Ntry");
+                match (path.last().map(|p| p.as_str()), parent) {
+                    // The entry's own amount, not the instructed amounts in its details.
+                    (Some("Amt"), Some("Ntry")) => amount = parse_cents(value),
+                    (Some("CdtDbtInd"), Some("Ntry")) => debit = value == "DBIT",
+                    (Some("Dt") | Some("DtTm"), Some("BookgDt")) if in_entry => {
+                        date = value
+                            .get(..10)
+                            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+                    }
+                    (Some("Nm"), _)
+                        if in_entry && path.iter().any(|p| p == "Cdtr") && creditor.is_none() =>
+                    {
+                        creditor = Some(value.to_string());
+                    }
+                    (Some("Nm"), _)
+                        if in_entry && path.iter().any(|p| p == "Dbtr") && debtor.is_none() =>
+                    {
+                        debtor = Some(value.to_string());
+                    }
+                    (Some("Ustrd") | Some("AddtlNtryInf"), _)
+                        if in_entry && remittance.is_none() =>
+                    {
+                        remittance = Some(value.to_string());
+                    }
+                    (Some("Ntry"), _) => {
+                        let (Some(cents), Some(date)) = (amount.take(), date.take()) else {
+                            return Err(StatementError::new(
+                                "a CAMT.053 entry has no valid date or amount",
+                            ));
+                        };
+                        let (creditor, debtor) = (creditor.take(), debtor.take());
+                        let merchant: String = if debit { creditor } else { debtor }
+                            .or(remittance.take())
+                            .filter(|m| !m.is_empty())
+                            .unwrap_or_else(|| "Bank payment".to_string());
+                        transactions.push(spent(
+                            &merchant,
+                            date,
+                            if debit { -cents } else { cents },
+                        ));
+                        debit = false;
+                    }
+                    _ => {}
+                }
+                path.pop();
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !seen_statement {
+        return Err(StatementError::new("this is not a CAMT.053 statement"));
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use crate::ledger::LedgerEntry;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, day).unwrap()
+    }
+
+    fn summary(transactions: &[Transaction]) -> Vec<(NaiveDate, &str, f32)> {
+        transactions
+            .iter()
+            .map(|t| (t.get_date(), t.get_merchant(), t.get_amount()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_ofx_sgml() {
+        let ofx: &str = "OFXHEADER:100
+DATA:OFXSGML
+<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20260105120000[-5:EST]
+<TRNAMT>-42.50
+<FITID>1001
+<NAME>TRATTORIA ROMA
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20260106
+<TRNAMT>100.00
+<NAME>SALARY
+</STMTTRN>
+<STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20260107<TRNAMT>-8.00<MEMO>Tom &amp; Jerry's</STMTTRN>
+</BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>";
+
+        assert_eq!(
+            summary(&parse(StatementFormat::Ofx, ofx).unwrap()),
+            vec![
+                (date(5), "TRATTORIA ROMA", 42.50),
+                (date(7), "Tom & Jerry's", 8.00)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ofx_xml() {
+        let ofx: &str = r#"<?xml version="1.0"?><?OFX OFXHEADER="200" VERSION="220"?>
+<OFX><CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS><BANKTRANLIST>
+<STMTTRN><TRNTYPE>DEBIT</TRNTYPE><DTPOSTED>20260105</DTPOSTED><TRNAMT>-12.00</TRNAMT><NAME>Cinema</NAME></STMTTRN>
+</BANKTRANLIST></CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1></OFX>"#;
+
+        assert_eq!(
+            summary(&parse(StatementFormat::Ofx, ofx).unwrap()),
+            vec![(date(5), "Cinema", 12.00)]
+        );
+    }
+
+    #[test]
+    fn test_parse_qif() {
+        let qif: &str = "!Type:CCard
+D01/05/2026
+T-1,042.50
+PHOTEL LUNA
+^
+D1/ 6'26
+T-3.20
+MCoffee
+^
+D01/07/2026
+T15.00
+PRefund
+^
+";
+
+        assert_eq!(
+            summary(&parse(StatementFormat::Qif, qif).unwrap()),
+            vec![(date(5), "HOTEL LUNA", 1042.50), (date(6), "Coffee", 3.20)]
+        );
+    }
+
+    #[test]
+    fn test_parse_camt053() {
+        let camt: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08">
+<BkToCstmrStmt><Stmt>
+<Ntry>
+  <Amt Ccy="EUR">23.40</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+  <BookgDt><Dt>2026-01-05</Dt></BookgDt>
+  <NtryDtls><TxDtls>
+    <AmtDtls><InstdAmt><Amt Ccy="EUR">23.40</Amt></InstdAmt></AmtDtls>
+    <RltdPties><Cdtr><Pty><Nm>B&#228;ckerei M&#252;ller &amp; Sohn</Nm></Pty></Cdtr></RltdPties>
+    <RmtInf><Ustrd>Card 1234</Ustrd></RmtInf>
+  </TxDtls></NtryDtls>
+</Ntry>
+<Ntry>
+  <Amt Ccy="EUR">2000.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>
+  <BookgDt><Dt>2026-01-06</Dt></BookgDt>
+  <NtryDtls><TxDtls><RltdPties>
+    <Dbtr><Pty><Nm>Example GmbH</Nm></Pty></Dbtr>
+    <Cdtr><Pty><Nm>Alice Smith</Nm></Pty></Cdtr>
+  </RltdPties></TxDtls></NtryDtls>
+</Ntry>
+<Ntry>
+  <Amt Ccy="EUR">60.00</Amt><CdtDbtInd>DBIT</CdtDbtInd>
+  <BookgDt><DtTm>2026-01-07T09:30:00</DtTm></BookgDt>
+  <AddtlNtryInf>Electricity January</AddtlNtryInf>
+</Ntry>
+</Stmt></BkToCstmrStmt></Document>"#;
+
+        assert_eq!(
+            summary(&parse(StatementFormat::Camt053, camt).unwrap()),
+            vec![
+                (date(5), "Bäckerei Müller & Sohn", 23.40),
+                (date(7), "Electricity January", 60.00)
+            ]
+        );
+        assert_eq!(
+            summary(&parse_all(StatementFormat::Camt053, camt).unwrap())[1],
+            (date(6), "Example GmbH", -2000.00)
+        );
+    }
+
+    #[test]
+    fn test_parse_failure_wrong_format() {
+        assert!(parse(StatementFormat::Qif, "<OFX></OFX>").is_err());
+        assert!(parse(StatementFormat::Camt053, "<Document/>").is_err());
+    }
+
+    #[test]
+    fn test_drafts() {
+        let alice: User = User::new("alice").unwrap();
+        let bob: User = User::new("bob").unwrap();
+        let mut ledger: Ledger = Ledger::new();
+        ledger.record_entry(
+            LedgerEntry::new(
+                "dinner",
+                date(6),
+                BTreeMap::from([("alice".to_string(), 42.50)]),
+                BTreeMap::from([("alice".to_string(), 21.25), ("bob".to_string(), 21.25)]),
+            )
+            .unwrap(),
+        );
+        let transactions: Vec<Transaction> = vec![
+            spent("TRATTORIA ROMA", date(5), -4250),
+            spent("CINEMA", date(5), -1200),
+        ];
+
+        let mut drafts: Vec<Draft> = drafts(transactions, &alice, &ledger).unwrap();
+        assert_eq!(drafts[0].get_duplicate(), Some("dinner"));
+        assert_eq!(drafts[1].get_duplicate(), None);
+
+        let mut draft: Draft = drafts.remove(1);
+        draft.get_expense_mut().add_participant(&alice);
+        draft.get_expense_mut().add_participant(&bob);
+        let expense = draft.into_expense().finalize().unwrap().calculate();
+        assert_eq!(expense.get_user_to_paid().get(&alice), Some(&12.00));
+        assert_eq!(expense.get_user_to_amount().get(&bob), Some(&6.00));
+    }
+}
@@ -0,0 +1,374 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::group::Group;
+use crate::ledger::Ledger;
+use crate::user::User;
+
+#[derive(Debug, PartialEq)]
+pub struct StoreError;
+
+/// Why a ledger saved before groups could not be imported.
+#[derive(Debug, PartialEq)]
+pub enum LedgerImportError {
+    /// The file could not be read, or a group already has the name.
+    Unreadable,
+    /// Names that differ only in case, which cannot all be members of one group.
+    NamesDifferInCase(Vec<String>),
+}
+
+impl fmt::Display for LedgerImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerImportError::Unreadable => write!(f, "it could not be read"),
+            LedgerImportError::NamesDifferInCase(names) => write!(
+                f,
+                "{} differ only in case, so spell each person the same way throughout",
+                names.join(", ")
+            ),
+        }
+    }
+}
+
+/// Every group heesab knows about, persisted together as one JSON file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Store {
+    groups: Vec<Group>,
+}
+
+impl Store {
+    pub fn new() -> Self {
+        Store::default()
+    }
+
+    /// Reads the store from `path`, or starts an empty one if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, StoreError> {
+        if !path.exists() {
+            return Ok(Store::new());
+        }
+        let contents: String = fs::read_to_string(path).map_err(|_| StoreError)?;
+        serde_json::from_str(&contents).map_err(|_| StoreError)
+    }
+
+    /// A group that differs from the copy already saved at `path` is saved with a newer
+    /// version, whichever way it was changed.
+    pub fn save(&self, path: &Path) -> Result<(), StoreError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|_| StoreError)?;
+        }
+        let mut store: Store = self.clone();
+        if let Ok(saved) = Store::load(path) {
+            for group in store.groups.iter_mut() {
+                if let Ok(index) = saved.find_group_index(group.get_name()) {
+                    group.advance_version(&saved.groups[index]);
+                }
+            }
+        }
+        let contents: String = serde_json::to_string_pretty(&store).map_err(|_| StoreError)?;
+        fs::write(path, contents).map_err(|_| StoreError)
+    }
+
+    /// Adds the ledger saved at `path` by versions of heesab before groups as a group named
+    /// `name`, with everyone it mentions as members. Fails if the file cannot be read, a
+    /// group already has the name, or names in it differ only in case.
+    pub fn import_ledger(&mut self, name: &str, path: &Path) -> Result<(), LedgerImportError> {
+        let contents: String =
+            fs::read_to_string(path).map_err(|_| LedgerImportError::Unreadable)?;
+        let ledger: Ledger =
+            serde_json::from_str(&contents).map_err(|_| LedgerImportError::Unreadable)?;
+        let mut group: Group = Group::new(name).map_err(|_| LedgerImportError::Unreadable)?;
+        let entries = ledger
+            .get_entries()
+            .iter()
+            .flat_map(|e| e.get_paid().keys().chain(e.get_consumed().keys()));
+        let settlements = ledger
+            .get_settlements()
+            .iter()
+            .flat_map(|s| [s.get_from(), s.get_to()]);
+        let mut names: Vec<&str> = Vec::new();
+        for member in entries.map(|n| n.as_str()).chain(settlements) {
+            if !names.contains(&member) {
+                names.push(member);
+            }
+        }
+        let clashing: Vec<String> = names
+            .iter()
+            .filter(|n| {
+                names
+                    .iter()
+                    .any(|other| other != *n && other.eq_ignore_ascii_case(n))
+            })
+            .map(|n| n.to_string())
+            .collect();
+        if !clashing.is_empty() {
+            return Err(LedgerImportError::NamesDifferInCase(clashing));
+        }
+        for member in names {
+            let user: User = User::new(member).map_err(|_| LedgerImportError::Unreadable)?;
+            group
+                .add_member(user)
+                .map_err(|_| LedgerImportError::Unreadable)?;
+        }
+        *group.get_ledger_mut() = ledger;
+        self.add_group(group)
+            .map_err(|_| LedgerImportError::Unreadable)
+    }
+
+    /// Group names are unique, ignoring case.
+    pub fn add_group(&mut self, group: Group) -> Result<(), StoreError> {
+        if self
+            .groups
+            .iter()
+            .any(|g| g.get_name().eq_ignore_ascii_case(group.get_name()))
+        {
+            return Err(StoreError);
+        }
+        self.groups.push(group);
+        Ok(())
+    }
+
+    /// Renames keep group names unique, ignoring case, and bump the group's version.
+    pub fn rename_group(&mut self, reference: &str, name: &str) -> Result<(), StoreError> {
+        let index: usize = self.find_group_index(reference)?;
+        if self
+            .groups
+            .iter()
+            .enumerate()
+            .any(|(i, g)| i != index && g.get_name().eq_ignore_ascii_case(name))
+        {
+            return Err(StoreError);
+        }
+        self.groups[index].set_name(name).map_err(|_| StoreError)?;
+        self.groups[index].bump_version();
+        Ok(())
+    }
+
+    pub fn remove_group(&mut self, reference: &str) -> Result<Group, StoreError> {
+        let index: usize = self.find_group_index(reference)?;
+        Ok(self.groups.remove(index))
+    }
+
+    pub fn get_groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// Looks up a group by name (ignoring case) or by its 1-based position as printed.
+    pub fn find_group(&self, reference: &str) -> Result<&Group, StoreError> {
+        let index: usize = self.find_group_index(reference)?;
+        Ok(&self.groups[index])
+    }
+
+    pub fn find_group_mut(&mut self, reference: &str) -> Result<&mut Group, StoreError> {
+        let index: usize = self.find_group_index(reference)?;
+        Ok(&mut self.groups[index])
+    }
+
+    fn find_group_index(&self, reference: &str) -> Result<usize, StoreError> {
+        let reference: &str = reference.trim();
+        if let Some(index) = self
+            .groups
+            .iter()
+            .position(|g| g.get_name().eq_ignore_ascii_case(reference))
+        {
+            return Ok(index);
+        }
+        match reference.parse::<usize>() {
+            Ok(position) if position >= 1 && position <= self.groups.len() => Ok(position - 1),
+            _ => Err(StoreError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::LedgerEntry;
+    use crate::settlement::Settlement;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_add_group_failure_duplicate_name() {
+        let mut store: Store = Store::new();
+        store.add_group(Group::new("Ski trip").unwrap()).unwrap();
+
+        let maybe_added: Result<(), StoreError> = store.add_group(Group::new("ski TRIP").unwrap());
+        assert_eq!(maybe_added, Err(StoreError));
+    }
+
+    #[test]
+    fn test_find_group() {
+        let mut store: Store = Store::new();
+        store.add_group(Group::new("Roommates").unwrap()).unwrap();
+        store.add_group(Group::new("Ski trip").unwrap()).unwrap();
+
+        assert_eq!(
+            store.find_group("roommates").unwrap().get_name(),
+            "Roommates"
+        );
+        assert_eq!(store.find_group("2").unwrap().get_name(), "Ski trip");
+        assert_eq!(store.find_group("3").unwrap_err(), StoreError);
+    }
+
+    #[test]
+    fn test_rename_and_remove_group() {
+        let mut store: Store = Store::new();
+        store.add_group(Group::new("Roommates").unwrap()).unwrap();
+        store.add_group(Group::new("Ski trip").unwrap()).unwrap();
+
+        assert_eq!(store.rename_group("1", "ski trip"), Err(StoreError));
+        store.rename_group("1", "Flatmates").unwrap();
+        assert_eq!(store.get_groups()[0].get_name(), "Flatmates");
+        assert_eq!(store.get_groups()[0].get_version(), 1);
+
+        assert_eq!(
+            store.remove_group("flatmates").unwrap().get_name(),
+            "Flatmates"
+        );
+        assert_eq!(store.get_groups().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let alice: User = User::new("alice").unwrap();
+        let bob: User = User::new("bob").unwrap();
+        let mut group: Group = Group::new("Roommates").unwrap();
+        group.add_member(alice.clone()).unwrap();
+        group.add_member(bob.clone()).unwrap();
+        group.get_ledger_mut().record_settlement(
+            Settlement::new(
+                &bob,
+                &alice,
+                20.00,
+                NaiveDate::from_ymd_opt(2026, 10, 10).unwrap(),
+            )
+            .unwrap(),
+        );
+        let mut store: Store = Store::new();
+        store.add_group(group).unwrap();
+
+        let path = std::env::temp_dir().join(format!("heesab-store-{}.json", std::process::id()));
+        store.save(&path).unwrap();
+        let loaded: Store = Store::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, store);
+    }
+
+    #[test]
+    fn test_save_advances_version_of_changed_groups() {
+        let mut store: Store = Store::new();
+        store.add_group(Group::new("Roommates").unwrap()).unwrap();
+        store.add_group(Group::new("Ski trip").unwrap()).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("heesab-versions-{}.json", std::process::id()));
+        store.save(&path).unwrap();
+
+        let mut changed: Store = Store::load(&path).unwrap();
+        changed
+            .find_group_mut("roommates")
+            .unwrap()
+            .add_member(User::new("alice").unwrap())
+            .unwrap();
+        changed.save(&path).unwrap();
+        changed.save(&path).unwrap();
+        let once: Store = Store::load(&path).unwrap();
+
+        let mut bumped: Store = once.clone();
+        bumped.find_group_mut("roommates").unwrap().bump_version();
+        bumped
+            .find_group_mut("roommates")
+            .unwrap()
+            .add_member(User::new("bob").unwrap())
+            .unwrap();
+        bumped.save(&path).unwrap();
+        let twice: Store = Store::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(once.find_group("roommates").unwrap().get_version(), 1);
+        assert_eq!(once.find_group("ski trip").unwrap().get_version(), 0);
+        assert_eq!(twice.find_group("roommates").unwrap().get_version(), 2);
+    }
+
+    #[test]
+    fn test_import_ledger() {
+        let alice: User = User::new("alice").unwrap();
+        let carol: User = User::new("carol").unwrap();
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+        let mut ledger: Ledger = Ledger::new();
+        ledger.record_entry(
+            LedgerEntry::new(
+                "dinner",
+                date,
+                std::collections::BTreeMap::from([("alice".to_string(), 30.00)]),
+                std::collections::BTreeMap::from([
+                    ("alice".to_string(), 15.00),
+                    ("bob".to_string(), 15.00),
+                ]),
+            )
+            .unwrap(),
+        );
+        ledger.record_settlement(Settlement::new(&carol, &alice, 5.00, date).unwrap());
+        ledger.record_settlement(
+            Settlement::new(&User::new("car").unwrap(), &alice, 1.00, date).unwrap(),
+        );
+
+        let path = std::env::temp_dir().join(format!("heesab-ledger-{}.json", std::process::id()));
+        fs::write(&path, serde_json::to_string(&ledger).unwrap()).unwrap();
+        let mut store: Store = Store::new();
+        let imported: Result<(), LedgerImportError> = store.import_ledger("Ledger", &path);
+        fs::remove_file(&path).unwrap();
+        imported.unwrap();
+
+        let group: &Group = store.find_group("ledger").unwrap();
+        let members: Vec<&str> = group.get_roster().iter().map(|u| u.get_name()).collect();
+        assert_eq!(members, vec!["alice", "bob", "carol", "car"]);
+        assert_eq!(group.get_ledger(), &ledger);
+        assert_eq!(
+            store.import_ledger("Ledger", Path::new("does-not-exist.json")),
+            Err(LedgerImportError::Unreadable)
+        );
+    }
+
+    #[test]
+    fn test_import_ledger_failure_names_differ_in_case() {
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 10).unwrap();
+        let mut ledger: Ledger = Ledger::new();
+        ledger.record_settlement(
+            Settlement::new(
+                &User::new("Sam").unwrap(),
+                &User::new("alice").unwrap(),
+                5.00,
+                date,
+            )
+            .unwrap(),
+        );
+        ledger.record_settlement(
+            Settlement::new(
+                &User::new("sam").unwrap(),
+                &User::new("alice").unwrap(),
+                5.00,
+                date,
+            )
+            .unwrap(),
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("heesab-ledger-case-{}.json", std::process::id()));
+        fs::write(&path, serde_json::to_string(&ledger).unwrap()).unwrap();
+        let mut store: Store = Store::new();
+        let imported: Result<(), LedgerImportError> = store.import_ledger("Ledger", &path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            imported,
+            Err(LedgerImportError::NamesDifferInCase(vec![
+                "Sam".to_string(),
+                "sam".to_string()
+            ]))
+        );
+        assert!(store.get_groups().is_empty());
+    }
+}
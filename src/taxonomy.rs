@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq)]
+pub struct TaxonomyError;
+
+/// The categories a group files expenses under. A subcategory is written after its parent
+/// with a colon, such as `Food:Groceries`, and every parent is a category too.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Taxonomy {
+    categories: Vec<String>,
+}
+
+impl Default for Taxonomy {
+    /// Categories for a shared household.
+    fn default() -> Self {
+        Taxonomy::new(&[
+            "Housing:Rent",
+            "Housing:Utilities",
+            "Housing:Supplies",
+            "Food:Groceries",
+            "Food:Dining",
+            "Transport",
+            "Travel",
+            "Entertainment",
+            "Health",
+            "Other",
+        ])
+        .expect("the default taxonomy is valid")
+    }
+}
+
+impl Taxonomy {
+    /// Fails if a category has an empty part or two categories differ only in case.
+    pub fn new(categories: &[&str]) -> Result<Self, TaxonomyError> {
+        let mut taxonomy: Taxonomy = Taxonomy {
+            categories: Vec::new(),
+        };
+        for category in categories {
+            let parts: Vec<&str> = category.split(':').map(|p| p.trim()).collect();
+            if parts.iter().any(|p| p.is_empty()) {
+                return Err(TaxonomyError);
+            }
+            for depth in 1..=parts.len() {
+                let path: String = parts[..depth].join(":");
+                match taxonomy
+                    .categories
+                    .iter()
+                    .find(|c| c.eq_ignore_ascii_case(&path))
+                {
+                    Some(existing) if depth == parts.len() && *existing != path => {
+                        return Err(TaxonomyError)
+                    }
+                    Some(_) => {}
+                    None => taxonomy.categories.push(path),
+                }
+            }
+        }
+        Ok(taxonomy)
+    }
+
+    pub fn get_categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// Finds a category by its full name or, if no other category shares it, its last part,
+    /// ignoring case: `groceries` finds `Food:Groceries`.
+    pub fn find(&self, reference: &str) -> Result<&str, TaxonomyError> {
+        let reference: &str = reference.trim();
+        if let Some(category) = self
+            .categories
+            .iter()
+            .find(|c| c.eq_ignore_ascii_case(reference))
+        {
+            return Ok(category);
+        }
+
+        let matches: Vec<&String> = self
+            .categories
+            .iter()
+            .filter(|c| {
+                c.rsplit(':')
+                    .next()
+                    .is_some_and(|last| last.eq_ignore_ascii_case(reference))
+            })
+            .collect();
+        match matches[..] {
+            [category] => Ok(category),
+            _ => Err(TaxonomyError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_adds_parents() {
+        let taxonomy: Taxonomy = Taxonomy::new(&["Food:Groceries", "Food:Dining", "Rent"]).unwrap();
+        assert_eq!(
+            taxonomy.get_categories(),
+            ["Food", "Food:Groceries", "Food:Dining", "Rent"]
+        );
+    }
+
+    #[test]
+    fn test_new_failure() {
+        assert_eq!(Taxonomy::new(&["Food:"]), Err(TaxonomyError));
+        assert_eq!(Taxonomy::new(&["Rent", "rent"]), Err(TaxonomyError));
+    }
+
+    #[test]
+    fn test_find() {
+        let taxonomy: Taxonomy =
+            Taxonomy::new(&["Food:Groceries", "Housing:Supplies", "Travel:Supplies"]).unwrap();
+        assert_eq!(taxonomy.find("food:groceries"), Ok("Food:Groceries"));
+        assert_eq!(taxonomy.find(" Groceries "), Ok("Food:Groceries"));
+        assert_eq!(taxonomy.find("food"), Ok("Food"));
+        assert_eq!(taxonomy.find("supplies"), Err(TaxonomyError));
+        assert_eq!(taxonomy.find("rent"), Err(TaxonomyError));
+    }
+}
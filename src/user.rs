@@ -1,22 +1,134 @@
 use core::fmt;
 
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::payment::PaymentHandle;
+use crate::rounding::{from_cents, Round};
+
 #[derive(Debug, PartialEq)]
 pub struct UserError;
 
-#[derive(Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct User {
     name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    payment_handles: Vec<PaymentHandle>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    incomes: Vec<Income>,
+}
+
+/// What a user earns, or their part of a ratio such as 3 in 3:2, from a date on or from the
+/// start if there is none. Kept in cents so that users stay hashable.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+struct Income {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from: Option<NaiveDate>,
+    cents: i64,
 }
 
 impl User {
     pub fn new(name: &str) -> Result<Self, UserError> {
-        if name.len() == 0 {
+        if name.is_empty() {
             return Err(UserError);
         }
         Ok(User {
             name: name.to_string(),
+            email: None,
+            phone: None,
+            payment_handles: Vec::new(),
+            incomes: Vec::new(),
         })
     }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// First letter of each word in the name, lowercased, e.g. "Mary Jane" becomes "mj".
+    pub fn initials(&self) -> String {
+        self.name
+            .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+            .filter_map(|word| word.chars().next())
+            .flat_map(|c| c.to_lowercase())
+            .collect()
+    }
+
+    pub fn get_email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    pub fn set_email(&mut self, email: &str) -> Result<(), UserError> {
+        if !email.contains('@') {
+            return Err(UserError);
+        }
+        self.email = Some(email.to_string());
+        Ok(())
+    }
+
+    pub fn get_phone(&self) -> Option<&str> {
+        self.phone.as_deref()
+    }
+
+    pub fn set_phone(&mut self, phone: &str) -> Result<(), UserError> {
+        if phone.is_empty() {
+            return Err(UserError);
+        }
+        self.phone = Some(phone.to_string());
+        Ok(())
+    }
+
+    pub fn get_payment_handles(&self) -> &[PaymentHandle] {
+        &self.payment_handles
+    }
+
+    /// Adds a way to pay this user, replacing any earlier handle of the same kind.
+    pub fn set_payment_handle(&mut self, handle: PaymentHandle) {
+        match self
+            .payment_handles
+            .iter_mut()
+            .find(|h| h.get_kind() == handle.get_kind())
+        {
+            Some(existing) => *existing = handle,
+            None => self.payment_handles.push(handle),
+        }
+    }
+
+    /// Every income recorded, oldest first, with the date it took effect if any.
+    pub fn get_incomes(&self) -> Vec<(Option<NaiveDate>, f32)> {
+        self.incomes
+            .iter()
+            .map(|i| (i.from, from_cents(i.cents)))
+            .collect()
+    }
+
+    /// Records what the user earns, or their part of a ratio, from `from` on or from the start.
+    /// Earlier incomes are kept, so expenses dated before a raise still split by the old
+    /// income. Replaces an income from the same date. Fails if the income is negative.
+    pub fn set_income(&mut self, income: f32, from: Option<NaiveDate>) -> Result<(), UserError> {
+        if !income.is_finite() || income < 0_f32 {
+            return Err(UserError);
+        }
+        self.incomes.retain(|i| i.from != from);
+        self.incomes.push(Income {
+            from,
+            cents: income.to_cents(),
+        });
+        self.incomes.sort_by_key(|i| i.from);
+        Ok(())
+    }
+
+    /// The income in effect on `date`, if one was recorded by then.
+    pub fn income_on(&self, date: NaiveDate) -> Option<f32> {
+        self.incomes
+            .iter()
+            .rfind(|i| i.from.is_none_or(|from| from <= date))
+            .map(|i| from_cents(i.cents))
+    }
 }
 
 impl fmt::Display for User {
@@ -47,4 +159,56 @@ mod tests {
         assert!(maybe_user.is_err());
         assert_eq!(maybe_user.unwrap_err(), UserError)
     }
+
+    #[test]
+    fn test_initials() {
+        let user: User = User::new("Mary Jane-Watson").unwrap();
+        assert_eq!(user.initials(), "mjw");
+    }
+
+    #[test]
+    fn test_set_email() {
+        let mut user: User = User::new("random name").unwrap();
+
+        assert_eq!(user.set_email("not an email"), Err(UserError));
+        assert_eq!(user.get_email(), None);
+
+        assert_eq!(user.set_email("random@example.com"), Ok(()));
+        assert_eq!(user.get_email(), Some("random@example.com"));
+    }
+
+    #[test]
+    fn test_income_history() {
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let mut user: User = User::new("random name").unwrap();
+        assert_eq!(user.income_on(date("2026-01-01")), None);
+        assert_eq!(user.set_income(-1_f32, None), Err(UserError));
+
+        user.set_income(4200_f32, Some(date("2026-06-01"))).unwrap();
+        user.set_income(4000_f32, None).unwrap();
+        assert_eq!(user.income_on(date("2026-05-31")), Some(4000_f32));
+        assert_eq!(user.income_on(date("2026-06-01")), Some(4200_f32));
+
+        user.set_income(4500_f32, Some(date("2026-06-01"))).unwrap();
+        assert_eq!(
+            user.get_incomes(),
+            vec![(None, 4000_f32), (Some(date("2026-06-01")), 4500_f32)]
+        );
+    }
+
+    #[test]
+    fn test_set_payment_handle() {
+        let mut user: User = User::new("random name").unwrap();
+        user.set_payment_handle(PaymentHandle::Venmo("old".to_string()));
+        user.set_payment_handle(PaymentHandle::Upi("random@okbank".to_string()));
+        user.set_payment_handle(PaymentHandle::Venmo("new".to_string()));
+
+        assert_eq!(
+            user.get_payment_handles(),
+            &[
+                PaymentHandle::Venmo("new".to_string()),
+                PaymentHandle::Upi("random@okbank".to_string())
+            ]
+        );
+    }
 }
@@ -0,0 +1,47 @@
+use wasm_bindgen::prelude::*;
+
+use crate::bindings;
+
+/// Splits a bill in the browser with the same calculation as everywhere else. Takes and
+/// returns JSON, see `bindings::split_json`, and throws the reason a bill is invalid.
+#[wasm_bindgen]
+pub fn split(input: &str) -> Result<String, JsError> {
+    bindings::split_json(input).map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn test_split() {
+        let input: &str = r#"{
+            "users": [{"name": "alice"}, {"name": "bob"}],
+            "expenses": [{"name": "pizza", "amount": 10.0, "participants": ["alice", "bob"]}],
+            "payers": {"alice": 10.0}
+        }"#;
+
+        let output: String = split(input).unwrap();
+        assert!(output.contains(r#""settlements":[{"from":"bob","to":"alice","amount":5.0}]"#));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_split_by_presence() {
+        let input: &str = r#"{
+            "users": [{"name": "alice"}, {"name": "bob"}],
+            "expenses": [{"name": "power", "amount": 30.0, "participants": ["alice", "bob"],
+                "period": {"start": "2026-03-01", "end": "2026-03-30"},
+                "presence": {"bob": {"start": "2026-03-21", "end": "2026-03-30"}}}],
+            "payers": {"alice": 30.0}
+        }"#;
+
+        let output: String = split(input).unwrap();
+        assert!(output.contains(r#""settlements":[{"from":"bob","to":"alice","amount":7.5}]"#));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_split_failure() {
+        assert!(split(r#"{"users": [], "expenses": []}"#).is_err());
+    }
+}